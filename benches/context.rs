@@ -0,0 +1,142 @@
+use std::borrow::Cow;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+fn file_entry(index: usize) -> publish::Value {
+    publish::Value::Object(
+        [
+            (
+                "path".to_string(),
+                publish::Value::String(format!("/publish/asset/v001/file_{:05}.exr", index)),
+            ),
+            ("size".to_string(), publish::Value::Integer(index as i64)),
+        ]
+        .into_iter()
+        .collect(),
+    )
+}
+
+fn large_context(size: usize) -> publish::Context {
+    (0..size)
+        .map(|index| (format!("file_{:05}", index), file_entry(index)))
+        .collect()
+}
+
+fn large_hash_map(size: usize) -> std::collections::HashMap<String, publish::Value> {
+    large_context(size).into_iter().collect()
+}
+
+/// A publish where every stage changes a single key, like a typical stage.
+struct StagePublish;
+
+#[async_trait::async_trait]
+impl publish::Publish for StagePublish {
+    async fn pre_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set("stage", publish::Value::String("pre_publish".to_string()));
+
+        Ok(Cow::Owned(context))
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set("stage", publish::Value::String("publish".to_string()));
+
+        Ok(Cow::Owned(context))
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set("stage", publish::Value::String("post_publish".to_string()));
+
+        Ok(Cow::Owned(context))
+    }
+}
+
+/// Copy the context and change one key, which is what each stage does.
+fn bench_stage_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stage_update");
+
+    for size in SIZES {
+        let context = large_context(size);
+        let hash_map = large_hash_map(size);
+
+        group.bench_with_input(BenchmarkId::new("context", size), &context, |b, context| {
+            b.iter(|| {
+                let mut context = context.to_owned();
+                context.set("stage", publish::Value::Integer(1));
+                black_box(context)
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("std_hash_map", size),
+            &hash_map,
+            |b, hash_map| {
+                b.iter(|| {
+                    let mut hash_map = hash_map.to_owned();
+                    hash_map.insert("stage".to_string(), publish::Value::Integer(1));
+                    black_box(hash_map)
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Run a publish with a large initial context.
+fn bench_run(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run");
+
+    for size in SIZES {
+        let context = large_context(size);
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &context, |b, context| {
+            b.iter(|| {
+                futures::executor::block_on(publish::run_with_context(
+                    &StagePublish,
+                    context.clone(),
+                ))
+                .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// Merge a small context into a large one.
+fn bench_merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merge");
+
+    for size in SIZES {
+        let context = large_context(size);
+        let other = large_context(10);
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &context, |b, context| {
+            b.iter(|| {
+                let mut context = context.clone();
+                context
+                    .merge(other.clone(), publish::MergeStrategy::Overwrite)
+                    .unwrap();
+                black_box(context)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_stage_update, bench_run, bench_merge);
+criterion_main!(benches);
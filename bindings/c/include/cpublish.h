@@ -8,6 +8,21 @@
 #include <stdlib.h>
 
 
+/**
+ * The version of the plugin interface.
+ *
+ * A plugin must return this from `cpublish_plugin_abi_version`, otherwise it
+ * will not be loaded. The version is increased whenever the plugin interface,
+ * or the layout of the types it uses, changes.
+ */
+#define CPUBLISH_PLUGIN_ABI_VERSION 2
+
+typedef enum CPublishMergeStrategy {
+  CPublishMergeStrategyOverwrite,
+  CPublishMergeStrategyKeep,
+  CPublishMergeStrategyError,
+} CPublishMergeStrategy;
+
 typedef enum CPublishStatusType {
   CPublishStatusTypeOk,
   CPublishStatusTypeError,
@@ -21,12 +36,24 @@ typedef enum CPublishValueType {
   CPublishValueTypeString,
   CPublishValueTypeArray,
   CPublishValueTypeObject,
+  CPublishValueTypeSecret,
 } CPublishValueType;
 
 typedef struct CPublishContext CPublishContext;
 
 typedef struct CPublishContextIter CPublishContextIter;
 
+typedef struct CPublishPublishMetadata CPublishPublishMetadata;
+
+typedef struct CPublishRegistry CPublishRegistry;
+
+/**
+ * The bytes that a publish expects to write under each path.
+ */
+typedef struct CPublishSpaceEstimates CPublishSpaceEstimates;
+
+typedef struct CPublishTemplate CPublishTemplate;
+
 typedef struct CPublishValue CPublishValue;
 
 typedef struct CPublishValueIterArray CPublishValueIterArray;
@@ -57,6 +84,16 @@ typedef struct CPublishStringView {
   const char *string;
 } CPublishStringView;
 
+/**
+ * The predicate for `cpublish_context_retain`.
+ *
+ * Returns true if the key should be kept. The key and value are only valid for
+ * the duration of the call.
+ */
+typedef bool (*CPublishContextRetainFn)(struct CPublishStringView key,
+                                        const struct CPublishValue *value,
+                                        void *user_data);
+
 typedef struct CPublishBasePublish {
   struct CPublishContext *(*pre_publish_fn)(const struct CPublishBasePublish *publish,
                                             const struct CPublishContext *context,
@@ -76,6 +113,10 @@ typedef struct CPublishBasePublish {
   void (*rollback_post_publish_fn)(const struct CPublishBasePublish *publish,
                                    const struct CPublishContext *context,
                                    struct CPublishStatus *status);
+  void (*estimate_space_fn)(const struct CPublishBasePublish *publish,
+                            const struct CPublishContext *context,
+                            struct CPublishSpaceEstimates *estimates,
+                            struct CPublishStatus *status);
 } CPublishBasePublish;
 
 /**
@@ -112,18 +153,123 @@ typedef struct CPublishString {
   void (*destroy_fn)(struct CPublishString*);
 } CPublishString;
 
+/**
+ * Create a new publish for the registry.
+ *
+ * The function should return null if the publish could not be created.
+ */
+typedef struct CPublishBasePublish *(*CPublishCreatePublishFn)(void);
+
+/**
+ * Destroy a publish that was created by a `CPublishCreatePublishFn`.
+ */
+typedef void (*CPublishDestroyPublishFn)(struct CPublishBasePublish *publish);
+
+/**
+ * The registrar that is passed to a plugin's `cpublish_plugin_register`
+ * function.
+ *
+ * Plugins register their publishes by calling `register_fn` with the
+ * registrar. The registration happens in the host, so the plugin does not
+ * need to share the host's registry implementation.
+ */
+typedef struct CPublishPluginRegistrar {
+  /**
+   * The ABI version of the host.
+   */
+  uint32_t abi_version;
+  /**
+   * Register a publish factory under the name.
+   *
+   * The description and version may be null. Returns false if the publish
+   * could not be registered, such as if the name is already registered.
+   */
+  bool (*register_fn)(struct CPublishPluginRegistrar *registrar,
+                      const char *name,
+                      const char *description,
+                      const char *version,
+                      CPublishCreatePublishFn create_fn,
+                      CPublishDestroyPublishFn destroy_fn);
+  /**
+   * Data owned by the host.
+   *
+   * # Safety
+   *
+   * This must not be modified or read by the plugin.
+   */
+  void *host_data;
+} CPublishPluginRegistrar;
+
 struct CPublishContext *cpublish_context_clone(const struct CPublishContext *context,
                                                struct CPublishStatus *status);
 
+bool cpublish_context_contains_key(const struct CPublishContext *context,
+                                   const char *key,
+                                   struct CPublishStatus *status);
+
 void cpublish_context_destroy(struct CPublishContext *context);
 
+/**
+ * Copy all of the keys of the other context into the context, replacing any
+ * existing values.
+ */
+void cpublish_context_extend(struct CPublishContext *context,
+                             const struct CPublishContext *other,
+                             struct CPublishStatus *status);
+
+/**
+ * Freeze the key, so that later stages cannot change it.
+ */
+void cpublish_context_freeze(struct CPublishContext *context,
+                             const char *key,
+                             struct CPublishStatus *status);
+
+/**
+ * Freeze the namespace, which is the key itself and every key that starts with
+ * the namespace followed by a `.`.
+ */
+void cpublish_context_freeze_namespace(struct CPublishContext *context,
+                                       const char *namespace_,
+                                       struct CPublishStatus *status);
+
 const struct CPublishValue *cpublish_context_get(const struct CPublishContext *context,
                                                  const char *key,
                                                  struct CPublishStatus *status);
 
+/**
+ * Get a mutable pointer to the value for the key.
+ *
+ * Returns null if the key is not in the context. The value is owned by the
+ * context, and must not be destroyed or used after the key is changed.
+ */
+struct CPublishValue *cpublish_context_get_mut(struct CPublishContext *context,
+                                               const char *key,
+                                               struct CPublishStatus *status);
+
+/**
+ * Get a mutable pointer to the value for the key, inserting a copy of the
+ * default value if the key is not in the context.
+ *
+ * The value is owned by the context, and must not be destroyed or used after
+ * the key is changed.
+ */
+struct CPublishValue *cpublish_context_get_or_insert(struct CPublishContext *context,
+                                                     const char *key,
+                                                     const struct CPublishValue *default_value,
+                                                     struct CPublishStatus *status);
+
 bool cpublish_context_is_empty(const struct CPublishContext *context,
                                struct CPublishStatus *status);
 
+bool cpublish_context_is_frozen(const struct CPublishContext *context,
+                                const char *key,
+                                struct CPublishStatus *status);
+
+/**
+ * Iterate over the keys and values of the context.
+ *
+ * The keys are visited in sorted order.
+ */
 struct CPublishContextIter *cpublish_context_iter(const struct CPublishContext *context,
                                                   struct CPublishStatus *status);
 
@@ -141,8 +287,39 @@ const struct CPublishValue *cpublish_context_iter_value(struct CPublishContextIt
 
 size_t cpublish_context_len(const struct CPublishContext *context, struct CPublishStatus *status);
 
+/**
+ * Deep merge a copy of the other context into the context.
+ *
+ * See `publish::Context::merge` for how conflicts are resolved. If the strategy
+ * is `CPublishMergeStrategyError` and there is a conflict, or the merge would
+ * change a frozen key, then the status is set to an error and the context is
+ * not modified.
+ */
+void cpublish_context_merge(struct CPublishContext *context,
+                            const struct CPublishContext *other,
+                            enum CPublishMergeStrategy strategy,
+                            struct CPublishStatus *status);
+
 struct CPublishContext *cpublish_context_new(void);
 
+/**
+ * Remove the key from the context.
+ *
+ * Returns the removed value, or null if the key was not in the context. The
+ * value must be destroyed with `cpublish_value_destroy`.
+ */
+struct CPublishValue *cpublish_context_remove(struct CPublishContext *context,
+                                              const char *key,
+                                              struct CPublishStatus *status);
+
+/**
+ * Keep only the keys that the predicate returns true for.
+ */
+void cpublish_context_retain(struct CPublishContext *context,
+                             CPublishContextRetainFn retain_fn,
+                             void *user_data,
+                             struct CPublishStatus *status);
+
 void cpublish_context_set(struct CPublishContext *context,
                           const char *key,
                           const struct CPublishValue *value,
@@ -167,6 +344,14 @@ void cpublish_context_set_none(struct CPublishContext *context,
                                const char *key,
                                struct CPublishStatus *status);
 
+/**
+ * Set the key to a secret value, such as a password or API token.
+ */
+void cpublish_context_set_secret(struct CPublishContext *context,
+                                 const char *key,
+                                 const char *value,
+                                 struct CPublishStatus *status);
+
 void cpublish_context_set_string(struct CPublishContext *context,
                                  const char *key,
                                  const char *value,
@@ -176,6 +361,15 @@ struct CPublishContext *cpublish_publish_default_error_publish(const struct CPub
                                                                const struct CPublishContext *context,
                                                                struct CPublishStatus *status);
 
+/**
+ * Add no estimates, which is the default for publishes that do not know how
+ * much they will write.
+ */
+void cpublish_publish_default_estimate_space(const struct CPublishBasePublish *publish,
+                                             const struct CPublishContext *context,
+                                             struct CPublishSpaceEstimates *estimates,
+                                             struct CPublishStatus *status);
+
 struct CPublishContext *cpublish_publish_default_publish(const struct CPublishBasePublish *publish,
                                                          const struct CPublishContext *context,
                                                          struct CPublishStatus *status);
@@ -184,6 +378,38 @@ void cpublish_publish_default_rollback_publish(const struct CPublishBasePublish
                                                const struct CPublishContext *context,
                                                struct CPublishStatus *status);
 
+/**
+ * Add the bytes that the publish expects to write under each path to the
+ * estimates.
+ */
+void cpublish_publish_estimate_space(const struct CPublishBasePublish *publish,
+                                     const struct CPublishContext *context,
+                                     struct CPublishSpaceEstimates *estimates,
+                                     struct CPublishStatus *status);
+
+/**
+ * Add a field to the context schema of the metadata.
+ *
+ * If the value type is null, then any type is accepted for the key.
+ */
+void cpublish_publish_metadata_add_schema_field(struct CPublishPublishMetadata *metadata,
+                                                const char *key,
+                                                const enum CPublishValueType *value_type,
+                                                bool required,
+                                                struct CPublishStatus *status);
+
+struct CPublishString cpublish_publish_metadata_description(const struct CPublishPublishMetadata *metadata,
+                                                            struct CPublishStatus *status);
+
+void cpublish_publish_metadata_destroy(struct CPublishPublishMetadata *metadata);
+
+struct CPublishPublishMetadata *cpublish_publish_metadata_new(const char *description,
+                                                              const char *version,
+                                                              struct CPublishStatus *status);
+
+struct CPublishString cpublish_publish_metadata_version(const struct CPublishPublishMetadata *metadata,
+                                                        struct CPublishStatus *status);
+
 struct CPublishBasePublish cpublish_publish_new_default(void);
 
 struct CPublishContext *cpublish_publish_post_publish(const struct CPublishBasePublish *publish,
@@ -210,9 +436,85 @@ void cpublish_publish_rollback_publish(const struct CPublishBasePublish *publish
                                        const struct CPublishContext *context,
                                        struct CPublishStatus *status);
 
+bool cpublish_registry_contains(const struct CPublishRegistry *registry,
+                                const char *name,
+                                struct CPublishStatus *status);
+
+void cpublish_registry_destroy(struct CPublishRegistry *registry);
+
+size_t cpublish_registry_len(const struct CPublishRegistry *registry,
+                             struct CPublishStatus *status);
+
+/**
+ * Load a publish plugin from a shared library, and register its publishes.
+ *
+ * See `load_plugin` for the requirements of the plugin.
+ */
+void cpublish_registry_load_plugin(struct CPublishRegistry *registry,
+                                   const char *path,
+                                   struct CPublishStatus *status);
+
+/**
+ * Get a copy of the metadata for the name.
+ */
+struct CPublishPublishMetadata *cpublish_registry_metadata(const struct CPublishRegistry *registry,
+                                                           const char *name,
+                                                           struct CPublishStatus *status);
+
+/**
+ * Get the name at the index, with the names in sorted order.
+ */
+struct CPublishString cpublish_registry_name(const struct CPublishRegistry *registry,
+                                             size_t index,
+                                             struct CPublishStatus *status);
+
+struct CPublishRegistry *cpublish_registry_new(void);
+
+/**
+ * Register a publish factory under the name.
+ *
+ * The metadata is copied, so it still needs to be destroyed by the caller.
+ * The metadata may be null, in which case empty metadata is used.
+ */
+void cpublish_registry_register(struct CPublishRegistry *registry,
+                                const char *name,
+                                const struct CPublishPublishMetadata *metadata,
+                                CPublishCreatePublishFn create_fn,
+                                CPublishDestroyPublishFn destroy_fn,
+                                struct CPublishStatus *status);
+
+/**
+ * Create and run the publish registered under the name.
+ *
+ * If the context is null, then the publish starts with an empty context.
+ */
+struct CPublishContext *cpublish_registry_run(const struct CPublishRegistry *registry,
+                                              const char *name,
+                                              const struct CPublishContext *context,
+                                              struct CPublishStatus *status);
+
+bool cpublish_registry_unregister(struct CPublishRegistry *registry,
+                                  const char *name,
+                                  struct CPublishStatus *status);
+
 struct CPublishContext *cpublish_run(const struct CPublishBasePublish *publish,
                                      struct CPublishStatus *status);
 
+void cpublish_space_estimates_destroy(struct CPublishSpaceEstimates *estimates);
+
+size_t cpublish_space_estimates_len(const struct CPublishSpaceEstimates *estimates);
+
+struct CPublishSpaceEstimates *cpublish_space_estimates_new(void);
+
+/**
+ * Add an estimate of the bytes that the publish expects to write under the
+ * path.
+ */
+void cpublish_space_estimates_push(struct CPublishSpaceEstimates *estimates,
+                                   const char *path,
+                                   uint64_t bytes,
+                                   struct CPublishStatus *status);
+
 void cpublish_status_destroy(struct CPublishStatus *status);
 
 void cpublish_status_error(struct CPublishStatus *status, const char *message);
@@ -230,6 +532,49 @@ void cpublish_status_ok(struct CPublishStatus *status);
  */
 void cpublish_string_destroy(struct CPublishString *string);
 
+void cpublish_template_destroy(struct CPublishTemplate *template_);
+
+/**
+ * The number of fields in the template, including repeated fields.
+ */
+size_t cpublish_template_field_count(const struct CPublishTemplate *template_,
+                                     struct CPublishStatus *status);
+
+/**
+ * The key of the field at the index, in the order the fields appear.
+ */
+struct CPublishString cpublish_template_field_key(const struct CPublishTemplate *template_,
+                                                  size_t index,
+                                                  struct CPublishStatus *status);
+
+/**
+ * Create a template, such as `{show}/{asset:lower}/v{version:03}`.
+ *
+ * If the template is invalid, then the status is set to an error and null is
+ * returned.
+ */
+struct CPublishTemplate *cpublish_template_new(const char *source, struct CPublishStatus *status);
+
+/**
+ * Parse a rendered string back into a new context of the template fields.
+ *
+ * If the text does not match the template, then the status is set to an error
+ * and null is returned.
+ */
+struct CPublishContext *cpublish_template_parse(const struct CPublishTemplate *template_,
+                                                const char *text,
+                                                struct CPublishStatus *status);
+
+/**
+ * Render the template from the context.
+ *
+ * If any keys are missing, then the status is set to an error that lists all
+ * of the missing keys, and an empty string is returned.
+ */
+struct CPublishString cpublish_template_render(const struct CPublishTemplate *template_,
+                                               const struct CPublishContext *context,
+                                               struct CPublishStatus *status);
+
 const struct CPublishValue *cpublish_value_array_get(const struct CPublishValue *value,
                                                      size_t index,
                                                      struct CPublishStatus *status);
@@ -292,6 +637,14 @@ struct CPublishValue *cpublish_value_new_object(void);
 
 struct CPublishValue *cpublish_value_new_object_with_capacity(size_t capacity);
 
+/**
+ * Create a secret value, such as a password or API token.
+ *
+ * The secret is redacted whenever the value is printed. Use
+ * `cpublish_value_secret_expose` to get the clear text.
+ */
+struct CPublishValue *cpublish_value_new_secret(const char *value);
+
 struct CPublishValue *cpublish_value_new_string(const char *value);
 
 const struct CPublishValue *cpublish_value_object_get(const struct CPublishValue *value,
@@ -303,11 +656,24 @@ void cpublish_value_object_insert(struct CPublishValue *value,
                                   const struct CPublishValue *item,
                                   struct CPublishStatus *status);
 
+/**
+ * Iterate over the keys and values of an object.
+ *
+ * The keys are visited in sorted order.
+ */
 struct CPublishValueIterObject *cpublish_value_object_iter(const struct CPublishValue *value,
                                                            struct CPublishStatus *status);
 
 size_t cpublish_value_object_len(const struct CPublishValue *value, struct CPublishStatus *status);
 
+/**
+ * Get the clear text of a secret value.
+ *
+ * Take care not to log or store the returned string.
+ */
+struct CPublishString cpublish_value_secret_expose(const struct CPublishValue *value,
+                                                   struct CPublishStatus *status);
+
 struct CPublishString cpublish_value_string(const struct CPublishValue *value,
                                             struct CPublishStatus *status);
 
@@ -84,3 +84,33 @@ pub struct CPublishStringView {
     /// The string must not outlive the container that owns it.
     pub string: *const c_char,
 }
+
+/// Borrow a C style string as a `&str`.
+///
+/// If the pointer is null, or the string is not valid UTF-8, then the status is
+/// set to an error that uses `name` to describe the argument.
+pub(crate) unsafe fn str_from_ptr<'a>(
+    string: *const c_char,
+    name: &str,
+    status: *mut crate::CPublishStatus,
+) -> Option<&'a str> {
+    if string.is_null() {
+        if !status.is_null() {
+            *status = crate::CPublishStatus::new_error(format!("{} is null", name));
+        }
+
+        return None;
+    }
+
+    match std::ffi::CStr::from_ptr(string).to_str() {
+        Ok(string) => Some(string),
+        Err(_) => {
+            if !status.is_null() {
+                *status =
+                    crate::CPublishStatus::new_error(format!("{} is not a valid c-string", name));
+            }
+
+            None
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use crate::{cpublish_status_ok, CPublishStatus, CPublishStringView};
+use crate::{c_string::str_from_ptr, cpublish_status_ok, CPublishStatus, CPublishStringView};
 use std::{
-    ffi::{c_char, CStr, CString},
+    ffi::{c_char, c_void, CStr, CString},
     ptr::{null, null_mut},
 };
 pub struct CPublishContext {
@@ -335,6 +335,40 @@ pub unsafe extern "C" fn cpublish_context_set_string(
     }
 }
 
+/// Set the key to a secret value, such as a password or API token.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_set_secret(
+    context: *mut CPublishContext,
+    key: *const c_char,
+    value: *const c_char,
+    status: *mut crate::CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return;
+        }
+    };
+    let key = match str_from_ptr(key, "key", status) {
+        Some(key) => key,
+        None => return,
+    };
+    let value = match str_from_ptr(value, "value", status) {
+        Some(value) => value,
+        None => return,
+    };
+
+    context
+        .inner
+        .set(key, publish::Value::Secret(publish::Secret::new(value)));
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cpublish_context_len(
     context: *const CPublishContext,
@@ -394,6 +428,9 @@ pub unsafe extern "C" fn cpublish_context_clone(
     }
 }
 
+/// Iterate over the keys and values of the context.
+///
+/// The keys are visited in sorted order.
 #[no_mangle]
 pub unsafe extern "C" fn cpublish_context_iter(
     context: *const CPublishContext,
@@ -526,3 +563,366 @@ pub unsafe extern "C" fn cpublish_context_iter_value(
         }
     }
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_contains_key(
+    context: *const CPublishContext,
+    key: *const c_char,
+    status: *mut crate::CPublishStatus,
+) -> bool {
+    cpublish_status_ok(status);
+
+    let context = match context.as_ref() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return false;
+        }
+    };
+    let key = match str_from_ptr(key, "key", status) {
+        Some(key) => key,
+        None => return false,
+    };
+
+    context.inner.contains_key(key)
+}
+
+/// Get a mutable pointer to the value for the key.
+///
+/// Returns null if the key is not in the context. The value is owned by the
+/// context, and must not be destroyed or used after the key is changed.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_get_mut(
+    context: *mut CPublishContext,
+    key: *const c_char,
+    status: *mut crate::CPublishStatus,
+) -> *mut crate::CPublishValue {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return null_mut();
+        }
+    };
+    let key = match str_from_ptr(key, "key", status) {
+        Some(key) => key,
+        None => return null_mut(),
+    };
+
+    match context.inner.get_mut(key) {
+        Some(value) => value as *mut publish::Value as *mut crate::CPublishValue,
+        None => null_mut(),
+    }
+}
+
+/// Get a mutable pointer to the value for the key, inserting a copy of the
+/// default value if the key is not in the context.
+///
+/// The value is owned by the context, and must not be destroyed or used after
+/// the key is changed.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_get_or_insert(
+    context: *mut CPublishContext,
+    key: *const c_char,
+    default_value: *const crate::CPublishValue,
+    status: *mut crate::CPublishStatus,
+) -> *mut crate::CPublishValue {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return null_mut();
+        }
+    };
+    let key = match str_from_ptr(key, "key", status) {
+        Some(key) => key,
+        None => return null_mut(),
+    };
+    let default_value = match default_value.as_ref() {
+        Some(default_value) => &default_value.value,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("default_value is null");
+            }
+
+            return null_mut();
+        }
+    };
+
+    let value = context
+        .inner
+        .entry(key)
+        .or_insert_with(|| default_value.clone());
+
+    value as *mut publish::Value as *mut crate::CPublishValue
+}
+
+/// Remove the key from the context.
+///
+/// Returns the removed value, or null if the key was not in the context. The
+/// value must be destroyed with `cpublish_value_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_remove(
+    context: *mut CPublishContext,
+    key: *const c_char,
+    status: *mut crate::CPublishStatus,
+) -> *mut crate::CPublishValue {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return null_mut();
+        }
+    };
+    let key = match str_from_ptr(key, "key", status) {
+        Some(key) => key,
+        None => return null_mut(),
+    };
+
+    match context.inner.remove(key) {
+        Some(value) => Box::into_raw(Box::new(crate::CPublishValue::from(value))),
+        None => null_mut(),
+    }
+}
+
+/// Copy all of the keys of the other context into the context, replacing any
+/// existing values.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_extend(
+    context: *mut CPublishContext,
+    other: *const CPublishContext,
+    status: *mut crate::CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return;
+        }
+    };
+    let other = match other.as_ref() {
+        Some(other) => other,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("other is null");
+            }
+
+            return;
+        }
+    };
+
+    context.inner.extend(
+        other
+            .inner
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone())),
+    );
+}
+
+/// The predicate for `cpublish_context_retain`.
+///
+/// Returns true if the key should be kept. The key and value are only valid for
+/// the duration of the call.
+pub type CPublishContextRetainFn = unsafe extern "C" fn(
+    key: CPublishStringView,
+    value: *const crate::CPublishValue,
+    user_data: *mut c_void,
+) -> bool;
+
+/// Keep only the keys that the predicate returns true for.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_retain(
+    context: *mut CPublishContext,
+    retain_fn: CPublishContextRetainFn,
+    user_data: *mut c_void,
+    status: *mut crate::CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return;
+        }
+    };
+
+    context.inner.retain(|key, value| match CString::new(key) {
+        Ok(key) => retain_fn(
+            CPublishStringView {
+                string: key.as_ptr(),
+            },
+            value as *const publish::Value as *const crate::CPublishValue,
+            user_data,
+        ),
+        Err(_) => true,
+    });
+}
+
+#[derive(Debug, PartialEq)]
+#[repr(C)]
+pub enum CPublishMergeStrategy {
+    CPublishMergeStrategyOverwrite,
+    CPublishMergeStrategyKeep,
+    CPublishMergeStrategyError,
+}
+
+impl From<&CPublishMergeStrategy> for publish::MergeStrategy {
+    fn from(value: &CPublishMergeStrategy) -> Self {
+        match value {
+            CPublishMergeStrategy::CPublishMergeStrategyOverwrite => Self::Overwrite,
+            CPublishMergeStrategy::CPublishMergeStrategyKeep => Self::Keep,
+            CPublishMergeStrategy::CPublishMergeStrategyError => Self::Error,
+        }
+    }
+}
+
+/// Deep merge a copy of the other context into the context.
+///
+/// See `publish::Context::merge` for how conflicts are resolved. If the strategy
+/// is `CPublishMergeStrategyError` and there is a conflict, or the merge would
+/// change a frozen key, then the status is set to an error and the context is
+/// not modified.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_merge(
+    context: *mut CPublishContext,
+    other: *const CPublishContext,
+    strategy: CPublishMergeStrategy,
+    status: *mut crate::CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return;
+        }
+    };
+    let other = match other.as_ref() {
+        Some(other) => other,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("other is null");
+            }
+
+            return;
+        }
+    };
+
+    if let Err(err) = context.inner.merge(other.inner.clone(), (&strategy).into()) {
+        if !status.is_null() {
+            *status = CPublishStatus::new_error(err.to_string());
+        }
+    }
+}
+
+/// Freeze the key, so that later stages cannot change it.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_freeze(
+    context: *mut CPublishContext,
+    key: *const c_char,
+    status: *mut crate::CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return;
+        }
+    };
+    let key = match str_from_ptr(key, "key", status) {
+        Some(key) => key,
+        None => return,
+    };
+
+    context.inner.freeze(key);
+}
+
+/// Freeze the namespace, which is the key itself and every key that starts with
+/// the namespace followed by a `.`.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_freeze_namespace(
+    context: *mut CPublishContext,
+    namespace: *const c_char,
+    status: *mut crate::CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let context = match context.as_mut() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return;
+        }
+    };
+    let namespace = match str_from_ptr(namespace, "namespace", status) {
+        Some(namespace) => namespace,
+        None => return,
+    };
+
+    context.inner.freeze_namespace(namespace);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_context_is_frozen(
+    context: *const CPublishContext,
+    key: *const c_char,
+    status: *mut crate::CPublishStatus,
+) -> bool {
+    cpublish_status_ok(status);
+
+    let context = match context.as_ref() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+
+            return false;
+        }
+    };
+    let key = match str_from_ptr(key, "key", status) {
+        Some(key) => key,
+        None => return false,
+    };
+
+    context.inner.is_frozen(key)
+}
@@ -1,31 +1,58 @@
 mod c_string;
 mod context;
+mod plugin;
 mod publish;
+mod registry;
 mod runner;
 mod status;
+mod template;
 mod value;
 
 pub use c_string::{cpublish_string_destroy, CPublishString, CPublishStringView};
 pub use context::{
-    cpublish_context_clone, cpublish_context_destroy, cpublish_context_get,
-    cpublish_context_is_empty, cpublish_context_iter, cpublish_context_iter_destroy,
-    cpublish_context_iter_is_done, cpublish_context_iter_key, cpublish_context_iter_next,
-    cpublish_context_iter_value, cpublish_context_len, cpublish_context_new, cpublish_context_set,
-    cpublish_context_set_bool, cpublish_context_set_float, cpublish_context_set_int,
-    cpublish_context_set_none, cpublish_context_set_string, CPublishContext, CPublishContextIter,
+    cpublish_context_clone, cpublish_context_contains_key, cpublish_context_destroy,
+    cpublish_context_extend, cpublish_context_freeze, cpublish_context_freeze_namespace,
+    cpublish_context_get, cpublish_context_get_mut, cpublish_context_get_or_insert,
+    cpublish_context_is_empty, cpublish_context_is_frozen, cpublish_context_iter,
+    cpublish_context_iter_destroy, cpublish_context_iter_is_done, cpublish_context_iter_key,
+    cpublish_context_iter_next, cpublish_context_iter_value, cpublish_context_len,
+    cpublish_context_merge, cpublish_context_new, cpublish_context_remove, cpublish_context_retain,
+    cpublish_context_set, cpublish_context_set_bool, cpublish_context_set_float,
+    cpublish_context_set_int, cpublish_context_set_none, cpublish_context_set_secret,
+    cpublish_context_set_string, CPublishContext, CPublishContextIter, CPublishContextRetainFn,
+    CPublishMergeStrategy,
+};
+pub use plugin::{
+    cpublish_registry_load_plugin, load_plugin, CPublishPluginRegistrar,
+    CPUBLISH_PLUGIN_ABI_VERSION,
 };
 pub use publish::{
-    cpublish_publish_default_error_publish, cpublish_publish_default_publish,
-    cpublish_publish_default_rollback_publish, cpublish_publish_new_default,
-    cpublish_publish_post_publish, cpublish_publish_pre_publish, cpublish_publish_publish,
-    cpublish_publish_rollback_post_publish, cpublish_publish_rollback_pre_publish,
-    cpublish_publish_rollback_publish, CPublishBasePublish,
+    cpublish_publish_default_error_publish, cpublish_publish_default_estimate_space,
+    cpublish_publish_default_publish, cpublish_publish_default_rollback_publish,
+    cpublish_publish_estimate_space, cpublish_publish_new_default, cpublish_publish_post_publish,
+    cpublish_publish_pre_publish, cpublish_publish_publish, cpublish_publish_rollback_post_publish,
+    cpublish_publish_rollback_pre_publish, cpublish_publish_rollback_publish,
+    cpublish_space_estimates_destroy, cpublish_space_estimates_len, cpublish_space_estimates_new,
+    cpublish_space_estimates_push, CPublishBasePublish, CPublishSpaceEstimates,
+};
+pub use registry::{
+    cpublish_publish_metadata_add_schema_field, cpublish_publish_metadata_description,
+    cpublish_publish_metadata_destroy, cpublish_publish_metadata_new,
+    cpublish_publish_metadata_version, cpublish_registry_contains, cpublish_registry_destroy,
+    cpublish_registry_len, cpublish_registry_metadata, cpublish_registry_name,
+    cpublish_registry_new, cpublish_registry_register, cpublish_registry_run,
+    cpublish_registry_unregister, CPublishCreatePublishFn, CPublishDestroyPublishFn,
+    CPublishPublishMetadata, CPublishRegistry,
 };
 pub use runner::cpublish_run;
 pub use status::{
     cpublish_status_destroy, cpublish_status_error, cpublish_status_ok, CPublishStatus,
     CPublishStatusType,
 };
+pub use template::{
+    cpublish_template_destroy, cpublish_template_field_count, cpublish_template_field_key,
+    cpublish_template_new, cpublish_template_parse, cpublish_template_render, CPublishTemplate,
+};
 pub use value::{
     cpublish_value_array_get, cpublish_value_array_iter, cpublish_value_array_len,
     cpublish_value_array_push, cpublish_value_bool, cpublish_value_destroy, cpublish_value_float,
@@ -36,8 +63,9 @@ pub use value::{
     cpublish_value_iter_object_value, cpublish_value_new_array,
     cpublish_value_new_array_with_capacity, cpublish_value_new_bool, cpublish_value_new_float,
     cpublish_value_new_int, cpublish_value_new_none, cpublish_value_new_object,
-    cpublish_value_new_object_with_capacity, cpublish_value_new_string, cpublish_value_object_get,
-    cpublish_value_object_insert, cpublish_value_object_iter, cpublish_value_object_len,
-    cpublish_value_string, cpublish_value_type, CPublishValue, CPublishValueIterArray,
-    CPublishValueIterObject, CPublishValueType,
+    cpublish_value_new_object_with_capacity, cpublish_value_new_secret, cpublish_value_new_string,
+    cpublish_value_object_get, cpublish_value_object_insert, cpublish_value_object_iter,
+    cpublish_value_object_len, cpublish_value_secret_expose, cpublish_value_string,
+    cpublish_value_type, CPublishValue, CPublishValueIterArray, CPublishValueIterObject,
+    CPublishValueType,
 };
@@ -0,0 +1,239 @@
+use std::{
+    ffi::{c_char, c_void},
+    sync::Arc,
+};
+
+use crate::{
+    c_string::str_from_ptr, cpublish_status_ok, registry::COwnedPublish, CPublishCreatePublishFn,
+    CPublishDestroyPublishFn, CPublishRegistry, CPublishStatus,
+};
+
+/// The version of the plugin interface.
+///
+/// A plugin must return this from `cpublish_plugin_abi_version`, otherwise it
+/// will not be loaded. The version is increased whenever the plugin interface,
+/// or the layout of the types it uses, changes.
+pub const CPUBLISH_PLUGIN_ABI_VERSION: u32 = 2;
+
+/// The name of the function a plugin exports to report its ABI version.
+///
+/// The function has the signature `uint32_t cpublish_plugin_abi_version(void)`.
+const PLUGIN_ABI_VERSION_SYMBOL: &[u8] = b"cpublish_plugin_abi_version\0";
+
+/// The name of the function a plugin exports to register its publishes.
+///
+/// The function has the signature
+/// `bool cpublish_plugin_register(CPublishPluginRegistrar *registrar)`, and
+/// should return false if any of the publishes could not be registered.
+const PLUGIN_REGISTER_SYMBOL: &[u8] = b"cpublish_plugin_register\0";
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type RegisterFn = unsafe extern "C" fn(registrar: *mut CPublishPluginRegistrar) -> bool;
+
+/// The registrar that is passed to a plugin's `cpublish_plugin_register`
+/// function.
+///
+/// Plugins register their publishes by calling `register_fn` with the
+/// registrar. The registration happens in the host, so the plugin does not
+/// need to share the host's registry implementation.
+#[repr(C)]
+pub struct CPublishPluginRegistrar {
+    /// The ABI version of the host.
+    pub abi_version: u32,
+    /// Register a publish factory under the name.
+    ///
+    /// The description and version may be null. Returns false if the publish
+    /// could not be registered, such as if the name is already registered.
+    pub register_fn: unsafe extern "C" fn(
+        registrar: *mut Self,
+        name: *const c_char,
+        description: *const c_char,
+        version: *const c_char,
+        create_fn: CPublishCreatePublishFn,
+        destroy_fn: CPublishDestroyPublishFn,
+    ) -> bool,
+    /// Data owned by the host.
+    ///
+    /// # Safety
+    ///
+    /// This must not be modified or read by the plugin.
+    pub host_data: *mut c_void,
+}
+
+struct RegistrarData<'a> {
+    registry: &'a mut publish::Registry,
+    library: Arc<libloading::Library>,
+    names: Vec<String>,
+    errors: Vec<String>,
+}
+
+unsafe extern "C" fn register_fn(
+    registrar: *mut CPublishPluginRegistrar,
+    name: *const c_char,
+    description: *const c_char,
+    version: *const c_char,
+    create_fn: CPublishCreatePublishFn,
+    destroy_fn: CPublishDestroyPublishFn,
+) -> bool {
+    let data = match registrar.as_mut() {
+        Some(registrar) => &mut *(registrar.host_data as *mut RegistrarData),
+        None => return false,
+    };
+
+    let mut status = CPublishStatus::new_ok();
+    let name = match str_from_ptr(name, "name", &mut status) {
+        Some(name) => name,
+        None => {
+            data.errors.push("name is not a valid c-string".to_string());
+            return false;
+        }
+    };
+    let description = if description.is_null() {
+        ""
+    } else {
+        str_from_ptr(description, "description", &mut status).unwrap_or_default()
+    };
+    let version = if version.is_null() {
+        ""
+    } else {
+        str_from_ptr(version, "version", &mut status).unwrap_or_default()
+    };
+    let metadata =
+        publish::PublishMetadata::new(description, version, publish::ContextSchema::default());
+    let library = data.library.clone();
+
+    let result = data.registry.register_factory(
+        name,
+        metadata,
+        Box::new(move || {
+            let publish = unsafe { create_fn() };
+            let publish = COwnedPublish::new(publish, destroy_fn, Some(library.clone()))?;
+
+            Ok(Box::new(publish) as Box<publish::DynPublish>)
+        }),
+    );
+
+    match result {
+        Ok(()) => {
+            data.names.push(name.to_string());
+            true
+        }
+        Err(err) => {
+            data.errors.push(err.to_string());
+            false
+        }
+    }
+}
+
+/// Load a publish plugin from a shared library, and register its publishes.
+///
+/// The library must export `cpublish_plugin_abi_version` and
+/// `cpublish_plugin_register`. Returns the names of the publishes that were
+/// registered. If the plugin fails to register, then any publishes it did
+/// register are removed from the registry.
+///
+/// # Safety
+///
+/// Loading a library runs its initialization code, and the exported functions
+/// must match the signatures that are documented for them.
+pub unsafe fn load_plugin<P: AsRef<std::ffi::OsStr>>(
+    path: P,
+    registry: &mut publish::Registry,
+) -> Result<Vec<String>, publish::Error> {
+    let path = path.as_ref();
+    let library = libloading::Library::new(path)
+        .map_err(|err| publish::Error::new_plugin(format!("Could not load {:?}: {}", path, err)))?;
+
+    let abi_version = {
+        let abi_version_fn = library
+            .get::<AbiVersionFn>(PLUGIN_ABI_VERSION_SYMBOL)
+            .map_err(|err| {
+                publish::Error::new_plugin(format!(
+                    "{:?} does not export cpublish_plugin_abi_version: {}",
+                    path, err
+                ))
+            })?;
+        abi_version_fn()
+    };
+
+    if abi_version != CPUBLISH_PLUGIN_ABI_VERSION {
+        return Err(publish::Error::new_plugin(format!(
+            "{:?} has ABI version {}, expected {}",
+            path, abi_version, CPUBLISH_PLUGIN_ABI_VERSION
+        )));
+    }
+
+    let plugin_register_fn = *library
+        .get::<RegisterFn>(PLUGIN_REGISTER_SYMBOL)
+        .map_err(|err| {
+            publish::Error::new_plugin(format!(
+                "{:?} does not export cpublish_plugin_register: {}",
+                path, err
+            ))
+        })?;
+
+    let mut data = RegistrarData {
+        registry,
+        library: Arc::new(library),
+        names: Vec::new(),
+        errors: Vec::new(),
+    };
+    let mut registrar = CPublishPluginRegistrar {
+        abi_version: CPUBLISH_PLUGIN_ABI_VERSION,
+        register_fn,
+        host_data: &mut data as *mut RegistrarData as *mut c_void,
+    };
+
+    let success = plugin_register_fn(&mut registrar);
+
+    if !success || !data.errors.is_empty() {
+        for name in &data.names {
+            data.registry.unregister(name);
+        }
+
+        let message = if data.errors.is_empty() {
+            "cpublish_plugin_register failed".to_string()
+        } else {
+            data.errors.join(", ")
+        };
+
+        return Err(publish::Error::new_plugin(format!(
+            "Could not register {:?}: {}",
+            path, message
+        )));
+    }
+
+    Ok(data.names)
+}
+
+/// Load a publish plugin from a shared library, and register its publishes.
+///
+/// See `load_plugin` for the requirements of the plugin.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_load_plugin(
+    registry: *mut CPublishRegistry,
+    path: *const c_char,
+    status: *mut CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let registry = match registry.as_mut() {
+        Some(registry) => registry,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("registry is null");
+            }
+            return;
+        }
+    };
+    let path = match str_from_ptr(path, "path", status) {
+        Some(path) => path,
+        None => return,
+    };
+
+    if let Err(err) = load_plugin(path, &mut registry.inner) {
+        if !status.is_null() {
+            *status = CPublishStatus::new_error(err.to_string());
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use std::{ffi::CStr, ptr::null_mut};
+use std::{
+    ffi::{c_char, CStr},
+    ptr::null_mut,
+};
 
-use crate::{cpublish_context_destroy, cpublish_status_ok, CPublishContext, CPublishStatus};
+use crate::{c_string::str_from_ptr, cpublish_status_ok, CPublishContext, CPublishStatus};
 
 #[repr(C)]
 pub struct CPublishBasePublish {
@@ -34,6 +37,17 @@ pub struct CPublishBasePublish {
         context: *const CPublishContext,
         status: *mut CPublishStatus,
     ),
+    pub estimate_space_fn: unsafe extern "C" fn(
+        publish: *const Self,
+        context: *const CPublishContext,
+        estimates: *mut CPublishSpaceEstimates,
+        status: *mut CPublishStatus,
+    ),
+}
+
+/// The bytes that a publish expects to write under each path.
+pub struct CPublishSpaceEstimates {
+    pub inner: Vec<publish::SpaceEstimate>,
 }
 
 #[async_trait::async_trait]
@@ -47,24 +61,24 @@ impl publish::Publish for CPublishBasePublish {
 
         let result = unsafe { cpublish_publish_pre_publish(self, c_context, &mut status) };
 
-        let out_result = match status.status {
-            crate::CPublishStatusType::CPublishStatusTypeOk => match unsafe { result.as_ref() } {
-                Some(c_context) => Ok(std::borrow::Cow::Owned(c_context.inner.clone())),
+        // Take ownership of the returned context, so its data is moved into the
+        // result instead of being copied.
+        let result = if result.is_null() {
+            None
+        } else {
+            Some(unsafe { Box::from_raw(result) })
+        };
+
+        match status.status {
+            crate::CPublishStatusType::CPublishStatusTypeOk => match result {
+                Some(c_context) => Ok(std::borrow::Cow::Owned(c_context.inner)),
                 None => Ok(std::borrow::Cow::Borrowed(context)),
             },
             crate::CPublishStatusType::CPublishStatusTypeError => {
                 let message = unsafe { CStr::from_ptr(status.message) };
                 Err(publish::Error::new_publish(message.to_string_lossy(), None))
             }
-        };
-
-        if !result.is_null() {
-            unsafe {
-                cpublish_context_destroy(result);
-            }
         }
-
-        out_result
     }
 
     async fn rollback_pre_publish(&self, context: &publish::Context) -> Result<(), publish::Error> {
@@ -91,24 +105,22 @@ impl publish::Publish for CPublishBasePublish {
 
         let result = unsafe { cpublish_publish_publish(self, c_context, &mut status) };
 
-        let out_result = match status.status {
-            crate::CPublishStatusType::CPublishStatusTypeOk => match unsafe { result.as_ref() } {
-                Some(c_context) => Ok(std::borrow::Cow::Owned(c_context.inner.clone())),
+        let result = if result.is_null() {
+            None
+        } else {
+            Some(unsafe { Box::from_raw(result) })
+        };
+
+        match status.status {
+            crate::CPublishStatusType::CPublishStatusTypeOk => match result {
+                Some(c_context) => Ok(std::borrow::Cow::Owned(c_context.inner)),
                 None => Ok(std::borrow::Cow::Borrowed(context)),
             },
             crate::CPublishStatusType::CPublishStatusTypeError => {
                 let message = unsafe { CStr::from_ptr(status.message) };
                 Err(publish::Error::new_publish(message.to_string_lossy(), None))
             }
-        };
-
-        if !result.is_null() {
-            unsafe {
-                cpublish_context_destroy(result);
-            }
         }
-
-        out_result
     }
 
     async fn rollback_publish(&self, context: &publish::Context) -> Result<(), publish::Error> {
@@ -135,24 +147,22 @@ impl publish::Publish for CPublishBasePublish {
 
         let result = unsafe { cpublish_publish_post_publish(self, c_context, &mut status) };
 
-        let out_result = match status.status {
-            crate::CPublishStatusType::CPublishStatusTypeOk => match unsafe { result.as_ref() } {
-                Some(c_context) => Ok(std::borrow::Cow::Owned(c_context.inner.clone())),
+        let result = if result.is_null() {
+            None
+        } else {
+            Some(unsafe { Box::from_raw(result) })
+        };
+
+        match status.status {
+            crate::CPublishStatusType::CPublishStatusTypeOk => match result {
+                Some(c_context) => Ok(std::borrow::Cow::Owned(c_context.inner)),
                 None => Ok(std::borrow::Cow::Borrowed(context)),
             },
             crate::CPublishStatusType::CPublishStatusTypeError => {
                 let message = unsafe { CStr::from_ptr(status.message) };
                 Err(publish::Error::new_publish(message.to_string_lossy(), None))
             }
-        };
-
-        if !result.is_null() {
-            unsafe {
-                cpublish_context_destroy(result);
-            }
         }
-
-        out_result
     }
 
     async fn rollback_post_publish(
@@ -172,6 +182,25 @@ impl publish::Publish for CPublishBasePublish {
             }
         }
     }
+
+    async fn estimate_space(
+        &self,
+        context: &publish::Context,
+    ) -> Result<Vec<publish::SpaceEstimate>, publish::Error> {
+        let c_context = context as *const publish::Context as *const CPublishContext;
+        let mut estimates = CPublishSpaceEstimates { inner: Vec::new() };
+        let mut status = CPublishStatus::new_ok();
+
+        unsafe { cpublish_publish_estimate_space(self, c_context, &mut estimates, &mut status) };
+
+        match status.status {
+            crate::CPublishStatusType::CPublishStatusTypeOk => Ok(estimates.inner),
+            crate::CPublishStatusType::CPublishStatusTypeError => {
+                let message = unsafe { CStr::from_ptr(status.message) };
+                Err(publish::Error::new_publish(message.to_string_lossy(), None))
+            }
+        }
+    }
 }
 
 #[no_mangle]
@@ -240,6 +269,18 @@ pub unsafe extern "C" fn cpublish_publish_default_rollback_publish(
     }
 }
 
+/// Add no estimates, which is the default for publishes that do not know how
+/// much they will write.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_publish_default_estimate_space(
+    publish: *const CPublishBasePublish,
+    context: *const CPublishContext,
+    #[allow(unused_variables)] estimates: *mut CPublishSpaceEstimates,
+    status: *mut CPublishStatus,
+) {
+    cpublish_publish_default_rollback_publish(publish, context, status);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cpublish_publish_new_default() -> CPublishBasePublish {
     CPublishBasePublish {
@@ -249,6 +290,7 @@ pub unsafe extern "C" fn cpublish_publish_new_default() -> CPublishBasePublish {
         rollback_publish_fn: cpublish_publish_default_rollback_publish,
         post_publish_fn: cpublish_publish_default_publish,
         rollback_post_publish_fn: cpublish_publish_default_rollback_publish,
+        estimate_space_fn: cpublish_publish_default_estimate_space,
     }
 }
 
@@ -362,3 +404,83 @@ pub unsafe extern "C" fn cpublish_publish_rollback_post_publish(
         }
     }
 }
+
+/// Add the bytes that the publish expects to write under each path to the
+/// estimates.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_publish_estimate_space(
+    publish: *const CPublishBasePublish,
+    context: *const CPublishContext,
+    estimates: *mut CPublishSpaceEstimates,
+    status: *mut CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    if estimates.is_null() {
+        if !status.is_null() {
+            *status = CPublishStatus::new_error("estimates is null");
+        }
+        return;
+    }
+
+    match publish.as_ref() {
+        Some(publish) => (publish.estimate_space_fn)(publish, context, estimates, status),
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("publish is null");
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_space_estimates_new() -> *mut CPublishSpaceEstimates {
+    Box::into_raw(Box::new(CPublishSpaceEstimates { inner: Vec::new() }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_space_estimates_destroy(estimates: *mut CPublishSpaceEstimates) {
+    if !estimates.is_null() {
+        drop(Box::from_raw(estimates));
+    }
+}
+
+/// Add an estimate of the bytes that the publish expects to write under the
+/// path.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_space_estimates_push(
+    estimates: *mut CPublishSpaceEstimates,
+    path: *const c_char,
+    bytes: u64,
+    status: *mut CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let estimates = match estimates.as_mut() {
+        Some(estimates) => estimates,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("estimates is null");
+            }
+            return;
+        }
+    };
+    let path = match str_from_ptr(path, "path", status) {
+        Some(path) => path,
+        None => return,
+    };
+
+    estimates
+        .inner
+        .push(publish::SpaceEstimate::new(path, bytes));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_space_estimates_len(
+    estimates: *const CPublishSpaceEstimates,
+) -> usize {
+    match estimates.as_ref() {
+        Some(estimates) => estimates.inner.len(),
+        None => 0,
+    }
+}
@@ -0,0 +1,465 @@
+use std::ptr::null_mut;
+
+use crate::{
+    c_string::str_from_ptr, cpublish_status_ok, CPublishBasePublish, CPublishContext,
+    CPublishStatus, CPublishString, CPublishValueType,
+};
+
+/// Create a new publish for the registry.
+///
+/// The function should return null if the publish could not be created.
+pub type CPublishCreatePublishFn = unsafe extern "C" fn() -> *mut CPublishBasePublish;
+
+/// Destroy a publish that was created by a `CPublishCreatePublishFn`.
+pub type CPublishDestroyPublishFn = unsafe extern "C" fn(publish: *mut CPublishBasePublish);
+
+/// A publish created by a registry factory, and destroyed when it is no longer
+/// needed.
+pub(crate) struct COwnedPublish {
+    publish: *mut CPublishBasePublish,
+    destroy_fn: CPublishDestroyPublishFn,
+    // Publishes created by a plugin must not outlive the plugin library.
+    _library: Option<std::sync::Arc<libloading::Library>>,
+}
+
+// The publish is only accessed through the function pointers, which must be
+// safe to call from any thread.
+unsafe impl Send for COwnedPublish {}
+unsafe impl Sync for COwnedPublish {}
+
+impl COwnedPublish {
+    pub(crate) fn new(
+        publish: *mut CPublishBasePublish,
+        destroy_fn: CPublishDestroyPublishFn,
+        library: Option<std::sync::Arc<libloading::Library>>,
+    ) -> Result<Self, publish::Error> {
+        if publish.is_null() {
+            return Err(publish::Error::new_registry(
+                "publish factory returned null",
+            ));
+        }
+
+        Ok(Self {
+            publish,
+            destroy_fn,
+            _library: library,
+        })
+    }
+
+    fn publish(&self) -> &CPublishBasePublish {
+        unsafe { &*self.publish }
+    }
+}
+
+impl Drop for COwnedPublish {
+    fn drop(&mut self) {
+        // The library is dropped after this, so the destroy function is
+        // still loaded.
+        unsafe { (self.destroy_fn)(self.publish) }
+    }
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for COwnedPublish {
+    async fn pre_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<std::borrow::Cow<'a, publish::Context>, publish::Error> {
+        self.publish().pre_publish(context).await
+    }
+
+    async fn rollback_pre_publish(&self, context: &publish::Context) -> Result<(), publish::Error> {
+        self.publish().rollback_pre_publish(context).await
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<std::borrow::Cow<'a, publish::Context>, publish::Error> {
+        self.publish().publish(context).await
+    }
+
+    async fn rollback_publish(&self, context: &publish::Context) -> Result<(), publish::Error> {
+        self.publish().rollback_publish(context).await
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<std::borrow::Cow<'a, publish::Context>, publish::Error> {
+        self.publish().post_publish(context).await
+    }
+
+    async fn rollback_post_publish(
+        &self,
+        context: &publish::Context,
+    ) -> Result<(), publish::Error> {
+        self.publish().rollback_post_publish(context).await
+    }
+
+    async fn estimate_space(
+        &self,
+        context: &publish::Context,
+    ) -> Result<Vec<publish::SpaceEstimate>, publish::Error> {
+        self.publish().estimate_space(context).await
+    }
+}
+
+pub struct CPublishPublishMetadata {
+    pub inner: publish::PublishMetadata,
+}
+
+pub struct CPublishRegistry {
+    pub inner: publish::Registry,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_publish_metadata_new(
+    description: *const std::ffi::c_char,
+    version: *const std::ffi::c_char,
+    status: *mut CPublishStatus,
+) -> *mut CPublishPublishMetadata {
+    cpublish_status_ok(status);
+
+    let description = match str_from_ptr(description, "description", status) {
+        Some(description) => description,
+        None => return null_mut(),
+    };
+    let version = match str_from_ptr(version, "version", status) {
+        Some(version) => version,
+        None => return null_mut(),
+    };
+
+    Box::into_raw(Box::new(CPublishPublishMetadata {
+        inner: publish::PublishMetadata::new(
+            description,
+            version,
+            publish::ContextSchema::default(),
+        ),
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_publish_metadata_destroy(metadata: *mut CPublishPublishMetadata) {
+    if !metadata.is_null() {
+        drop(Box::from_raw(metadata));
+    }
+}
+
+/// Add a field to the context schema of the metadata.
+///
+/// If the value type is null, then any type is accepted for the key.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_publish_metadata_add_schema_field(
+    metadata: *mut CPublishPublishMetadata,
+    key: *const std::ffi::c_char,
+    value_type: *const CPublishValueType,
+    required: bool,
+    status: *mut CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let metadata = match metadata.as_mut() {
+        Some(metadata) => metadata,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("metadata is null");
+            }
+            return;
+        }
+    };
+    let key = match str_from_ptr(key, "key", status) {
+        Some(key) => key,
+        None => return,
+    };
+
+    metadata.inner.schema.fields.push(publish::SchemaField::new(
+        key,
+        value_type.as_ref().map(publish::ValueType::from),
+        required,
+    ));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_new() -> *mut CPublishRegistry {
+    Box::into_raw(Box::new(CPublishRegistry {
+        inner: publish::Registry::new(),
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_destroy(registry: *mut CPublishRegistry) {
+    if !registry.is_null() {
+        drop(Box::from_raw(registry));
+    }
+}
+
+/// Register a publish factory under the name.
+///
+/// The metadata is copied, so it still needs to be destroyed by the caller.
+/// The metadata may be null, in which case empty metadata is used.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_register(
+    registry: *mut CPublishRegistry,
+    name: *const std::ffi::c_char,
+    metadata: *const CPublishPublishMetadata,
+    create_fn: CPublishCreatePublishFn,
+    destroy_fn: CPublishDestroyPublishFn,
+    status: *mut CPublishStatus,
+) {
+    cpublish_status_ok(status);
+
+    let registry = match registry.as_mut() {
+        Some(registry) => registry,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("registry is null");
+            }
+            return;
+        }
+    };
+    let name = match str_from_ptr(name, "name", status) {
+        Some(name) => name,
+        None => return,
+    };
+    let metadata = match metadata.as_ref() {
+        Some(metadata) => metadata.inner.clone(),
+        None => publish::PublishMetadata::default(),
+    };
+
+    let result = registry.inner.register_factory(
+        name,
+        metadata,
+        Box::new(move || {
+            let publish = unsafe { create_fn() };
+            let publish = COwnedPublish::new(publish, destroy_fn, None)?;
+
+            Ok(Box::new(publish) as Box<publish::DynPublish>)
+        }),
+    );
+
+    if let Err(err) = result {
+        if !status.is_null() {
+            *status = CPublishStatus::new_error(err.to_string());
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_unregister(
+    registry: *mut CPublishRegistry,
+    name: *const std::ffi::c_char,
+    status: *mut CPublishStatus,
+) -> bool {
+    cpublish_status_ok(status);
+
+    let registry = match registry.as_mut() {
+        Some(registry) => registry,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("registry is null");
+            }
+            return false;
+        }
+    };
+
+    match str_from_ptr(name, "name", status) {
+        Some(name) => registry.inner.unregister(name),
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_contains(
+    registry: *const CPublishRegistry,
+    name: *const std::ffi::c_char,
+    status: *mut CPublishStatus,
+) -> bool {
+    cpublish_status_ok(status);
+
+    let registry = match registry.as_ref() {
+        Some(registry) => registry,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("registry is null");
+            }
+            return false;
+        }
+    };
+
+    match str_from_ptr(name, "name", status) {
+        Some(name) => registry.inner.contains(name),
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_len(
+    registry: *const CPublishRegistry,
+    status: *mut CPublishStatus,
+) -> usize {
+    cpublish_status_ok(status);
+
+    match registry.as_ref() {
+        Some(registry) => registry.inner.len(),
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("registry is null");
+            }
+            0
+        }
+    }
+}
+
+/// Get the name at the index, with the names in sorted order.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_name(
+    registry: *const CPublishRegistry,
+    index: usize,
+    status: *mut CPublishStatus,
+) -> CPublishString {
+    cpublish_status_ok(status);
+
+    let registry = match registry.as_ref() {
+        Some(registry) => registry,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("registry is null");
+            }
+            return CPublishString::new("");
+        }
+    };
+
+    match registry.inner.names().nth(index) {
+        Some(name) => CPublishString::new(name),
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("index out of bounds");
+            }
+            CPublishString::new("")
+        }
+    }
+}
+
+/// Get a copy of the metadata for the name.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_metadata(
+    registry: *const CPublishRegistry,
+    name: *const std::ffi::c_char,
+    status: *mut CPublishStatus,
+) -> *mut CPublishPublishMetadata {
+    cpublish_status_ok(status);
+
+    let registry = match registry.as_ref() {
+        Some(registry) => registry,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("registry is null");
+            }
+            return null_mut();
+        }
+    };
+    let name = match str_from_ptr(name, "name", status) {
+        Some(name) => name,
+        None => return null_mut(),
+    };
+
+    match registry.inner.metadata(name) {
+        Some(metadata) => Box::into_raw(Box::new(CPublishPublishMetadata {
+            inner: metadata.clone(),
+        })),
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("name is not registered");
+            }
+            null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_publish_metadata_description(
+    metadata: *const CPublishPublishMetadata,
+    status: *mut CPublishStatus,
+) -> CPublishString {
+    cpublish_status_ok(status);
+
+    match metadata.as_ref() {
+        Some(metadata) => CPublishString::new(&metadata.inner.description),
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("metadata is null");
+            }
+            CPublishString::new("")
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_publish_metadata_version(
+    metadata: *const CPublishPublishMetadata,
+    status: *mut CPublishStatus,
+) -> CPublishString {
+    cpublish_status_ok(status);
+
+    match metadata.as_ref() {
+        Some(metadata) => CPublishString::new(&metadata.inner.version),
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("metadata is null");
+            }
+            CPublishString::new("")
+        }
+    }
+}
+
+/// Create and run the publish registered under the name.
+///
+/// If the context is null, then the publish starts with an empty context.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_registry_run(
+    registry: *const CPublishRegistry,
+    name: *const std::ffi::c_char,
+    context: *const CPublishContext,
+    status: *mut CPublishStatus,
+) -> *mut CPublishContext {
+    cpublish_status_ok(status);
+
+    let registry = match registry.as_ref() {
+        Some(registry) => registry,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("registry is null");
+            }
+            return null_mut();
+        }
+    };
+    let name = match str_from_ptr(name, "name", status) {
+        Some(name) => name,
+        None => return null_mut(),
+    };
+    let context = match context.as_ref() {
+        Some(context) => context.inner.clone(),
+        None => publish::Context::default(),
+    };
+
+    let rt = match tokio::runtime::Builder::new_current_thread().build() {
+        Ok(rt) => rt,
+        Err(err) => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error(format!("failed to build runtime: {}", err));
+            }
+            return null_mut();
+        }
+    };
+
+    match rt.block_on(registry.inner.run(name, context)) {
+        Ok(context) => Box::into_raw(Box::new(CPublishContext::from(context))),
+        Err(err) => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error(err.to_string());
+            }
+            null_mut()
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::{ffi::c_char, ptr::null_mut};
+
+use crate::{
+    c_string::str_from_ptr, cpublish_status_ok, CPublishContext, CPublishStatus, CPublishString,
+};
+
+pub struct CPublishTemplate {
+    pub inner: publish::Template,
+}
+
+/// Create a template, such as `{show}/{asset:lower}/v{version:03}`.
+///
+/// If the template is invalid, then the status is set to an error and null is
+/// returned.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_template_new(
+    source: *const c_char,
+    status: *mut CPublishStatus,
+) -> *mut CPublishTemplate {
+    cpublish_status_ok(status);
+
+    let source = match str_from_ptr(source, "source", status) {
+        Some(source) => source,
+        None => return null_mut(),
+    };
+
+    match publish::Template::new(source) {
+        Ok(inner) => Box::into_raw(Box::new(CPublishTemplate { inner })),
+        Err(err) => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error(err.to_string());
+            }
+
+            null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_template_destroy(template: *mut CPublishTemplate) {
+    if !template.is_null() {
+        drop(Box::from_raw(template));
+    }
+}
+
+/// Render the template from the context.
+///
+/// If any keys are missing, then the status is set to an error that lists all
+/// of the missing keys, and an empty string is returned.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_template_render(
+    template: *const CPublishTemplate,
+    context: *const CPublishContext,
+    status: *mut CPublishStatus,
+) -> CPublishString {
+    cpublish_status_ok(status);
+
+    let template = match template.as_ref() {
+        Some(template) => template,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("template is null");
+            }
+            return CPublishString::new("");
+        }
+    };
+    let context = match context.as_ref() {
+        Some(context) => context,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("context is null");
+            }
+            return CPublishString::new("");
+        }
+    };
+
+    match template.inner.render(&context.inner) {
+        Ok(text) => CPublishString::new(text),
+        Err(err) => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error(err.to_string());
+            }
+            CPublishString::new("")
+        }
+    }
+}
+
+/// Parse a rendered string back into a new context of the template fields.
+///
+/// If the text does not match the template, then the status is set to an error
+/// and null is returned.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_template_parse(
+    template: *const CPublishTemplate,
+    text: *const c_char,
+    status: *mut CPublishStatus,
+) -> *mut CPublishContext {
+    cpublish_status_ok(status);
+
+    let template = match template.as_ref() {
+        Some(template) => template,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("template is null");
+            }
+            return null_mut();
+        }
+    };
+    let text = match str_from_ptr(text, "text", status) {
+        Some(text) => text,
+        None => return null_mut(),
+    };
+
+    match template.inner.parse(text) {
+        Ok(context) => Box::into_raw(Box::new(CPublishContext::from(context))),
+        Err(err) => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error(err.to_string());
+            }
+            null_mut()
+        }
+    }
+}
+
+/// The number of fields in the template, including repeated fields.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_template_field_count(
+    template: *const CPublishTemplate,
+    status: *mut CPublishStatus,
+) -> usize {
+    cpublish_status_ok(status);
+
+    match template.as_ref() {
+        Some(template) => template.inner.fields().count(),
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("template is null");
+            }
+            0
+        }
+    }
+}
+
+/// The key of the field at the index, in the order the fields appear.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_template_field_key(
+    template: *const CPublishTemplate,
+    index: usize,
+    status: *mut CPublishStatus,
+) -> CPublishString {
+    cpublish_status_ok(status);
+
+    let template = match template.as_ref() {
+        Some(template) => template,
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("template is null");
+            }
+            return CPublishString::new("");
+        }
+    };
+
+    match template.inner.fields().nth(index) {
+        Some(field) => CPublishString::new(&field.key),
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("index out of bounds");
+            }
+            CPublishString::new("")
+        }
+    }
+}
@@ -25,6 +25,37 @@ pub enum CPublishValueType {
     CPublishValueTypeString,
     CPublishValueTypeArray,
     CPublishValueTypeObject,
+    CPublishValueTypeSecret,
+}
+
+impl From<publish::ValueType> for CPublishValueType {
+    fn from(value: publish::ValueType) -> Self {
+        match value {
+            publish::ValueType::None => Self::CPublishValueTypeNone,
+            publish::ValueType::Boolean => Self::CPublishValueTypeBoolean,
+            publish::ValueType::Integer => Self::CPublishValueTypeInteger,
+            publish::ValueType::Float => Self::CPublishValueTypeFloat,
+            publish::ValueType::String => Self::CPublishValueTypeString,
+            publish::ValueType::Array => Self::CPublishValueTypeArray,
+            publish::ValueType::Object => Self::CPublishValueTypeObject,
+            publish::ValueType::Secret => Self::CPublishValueTypeSecret,
+        }
+    }
+}
+
+impl From<&CPublishValueType> for publish::ValueType {
+    fn from(value: &CPublishValueType) -> Self {
+        match value {
+            CPublishValueType::CPublishValueTypeNone => Self::None,
+            CPublishValueType::CPublishValueTypeBoolean => Self::Boolean,
+            CPublishValueType::CPublishValueTypeInteger => Self::Integer,
+            CPublishValueType::CPublishValueTypeFloat => Self::Float,
+            CPublishValueType::CPublishValueTypeString => Self::String,
+            CPublishValueType::CPublishValueTypeArray => Self::Array,
+            CPublishValueType::CPublishValueTypeObject => Self::Object,
+            CPublishValueType::CPublishValueTypeSecret => Self::Secret,
+        }
+    }
 }
 
 #[no_mangle]
@@ -57,9 +88,21 @@ pub unsafe extern "C" fn cpublish_value_new_string(value: *const c_char) -> *mut
     Box::into_raw(Box::new(CPublishValue { value }))
 }
 
+/// Create a secret value, such as a password or API token.
+///
+/// The secret is redacted whenever the value is printed. Use
+/// `cpublish_value_secret_expose` to get the clear text.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_value_new_secret(value: *const c_char) -> *mut CPublishValue {
+    let value = publish::Value::Secret(publish::Secret::new(
+        CStr::from_ptr(value).to_string_lossy(),
+    ));
+    Box::into_raw(Box::new(CPublishValue { value }))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cpublish_value_new_array() -> *mut CPublishValue {
-    let value = publish::Value::Array(Vec::new());
+    let value = publish::Value::Array(im::Vector::new());
     Box::into_raw(Box::new(CPublishValue { value }))
 }
 
@@ -67,13 +110,15 @@ pub unsafe extern "C" fn cpublish_value_new_array() -> *mut CPublishValue {
 pub unsafe extern "C" fn cpublish_value_new_array_with_capacity(
     capacity: usize,
 ) -> *mut CPublishValue {
-    let value = publish::Value::Array(Vec::with_capacity(capacity));
+    // Arrays are persistent vectors, which do not preallocate.
+    let _ = capacity;
+    let value = publish::Value::Array(im::Vector::new());
     Box::into_raw(Box::new(CPublishValue { value }))
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn cpublish_value_new_object() -> *mut CPublishValue {
-    let value = publish::Value::Object(std::collections::HashMap::new());
+    let value = publish::Value::Object(im::OrdMap::new());
     Box::into_raw(Box::new(CPublishValue { value }))
 }
 
@@ -81,7 +126,9 @@ pub unsafe extern "C" fn cpublish_value_new_object() -> *mut CPublishValue {
 pub unsafe extern "C" fn cpublish_value_new_object_with_capacity(
     capacity: usize,
 ) -> *mut CPublishValue {
-    let value = publish::Value::Object(std::collections::HashMap::with_capacity(capacity));
+    // Objects are persistent maps, which do not preallocate.
+    let _ = capacity;
+    let value = publish::Value::Object(im::OrdMap::new());
     Box::into_raw(Box::new(CPublishValue { value }))
 }
 
@@ -106,15 +153,7 @@ pub unsafe extern "C" fn cpublish_value_type(
         return CPublishValueType::CPublishValueTypeNone;
     }
 
-    match (*value).value {
-        publish::Value::None => CPublishValueType::CPublishValueTypeNone,
-        publish::Value::Boolean(_) => CPublishValueType::CPublishValueTypeBoolean,
-        publish::Value::Integer(_) => CPublishValueType::CPublishValueTypeInteger,
-        publish::Value::Float(_) => CPublishValueType::CPublishValueTypeFloat,
-        publish::Value::String(_) => CPublishValueType::CPublishValueTypeString,
-        publish::Value::Array(_) => CPublishValueType::CPublishValueTypeArray,
-        publish::Value::Object(_) => CPublishValueType::CPublishValueTypeObject,
-    }
+    (*value).value.value_type().into()
 }
 
 #[no_mangle]
@@ -221,6 +260,35 @@ pub unsafe extern "C" fn cpublish_value_string(
     }
 }
 
+/// Get the clear text of a secret value.
+///
+/// Take care not to log or store the returned string.
+#[no_mangle]
+pub unsafe extern "C" fn cpublish_value_secret_expose(
+    value: *const CPublishValue,
+    status: *mut CPublishStatus,
+) -> CPublishString {
+    cpublish_status_ok(status);
+
+    match value.as_ref() {
+        Some(value) => match &value.value {
+            publish::Value::Secret(value) => CPublishString::new(value.expose()),
+            _ => {
+                if !status.is_null() {
+                    *status = CPublishStatus::new_error("value is not a secret");
+                }
+                CPublishString::new("")
+            }
+        },
+        None => {
+            if !status.is_null() {
+                *status = CPublishStatus::new_error("value is null");
+            }
+            CPublishString::new("")
+        }
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cpublish_value_array_len(
     value: *const CPublishValue,
@@ -299,7 +367,7 @@ pub unsafe extern "C" fn cpublish_value_array_push(
 
     match value.as_mut() {
         Some(value) => match &mut value.value {
-            publish::Value::Array(value) => value.push((*item).value.clone()),
+            publish::Value::Array(value) => value.push_back((*item).value.clone()),
             _ => {
                 if !status.is_null() {
                     *status = CPublishStatus::new_error("value is not an array");
@@ -476,6 +544,9 @@ pub unsafe extern "C" fn cpublish_value_object_insert(
     }
 }
 
+/// Iterate over the keys and values of an object.
+///
+/// The keys are visited in sorted order.
 #[no_mangle]
 pub unsafe extern "C" fn cpublish_value_object_iter(
     value: *const CPublishValue,
@@ -527,7 +598,7 @@ pub unsafe extern "C" fn cpublish_value_object_iter(
 }
 
 pub struct CPublishValueIterArray {
-    iter: Option<std::slice::Iter<'static, publish::Value>>,
+    iter: Option<im::vector::Iter<'static, publish::Value>>,
     value: *const CPublishValue,
 }
 
@@ -606,7 +677,7 @@ pub unsafe extern "C" fn cpublish_value_iter_array_value(
 }
 
 pub struct CPublishValueIterObject {
-    iter: Option<std::collections::hash_map::Iter<'static, String, publish::Value>>,
+    iter: Option<im::ordmap::Iter<'static, String, publish::Value>>,
     key: *const c_char,
     value: *const CPublishValue,
 }
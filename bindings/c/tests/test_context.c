@@ -248,6 +248,100 @@ static void test_cpublish_context_clone_success(void **state) {
   cpublish_context_destroy(cloned_context);
 }
 
+static bool retain_not_b(CPublishStringView key, const CPublishValue *value,
+                         void *user_data) {
+  return strcmp(key.string, "b") != 0;
+}
+
+static void test_cpublish_context_mutation_success(void **state) {
+  CPublishStatus status;
+
+  CPublishContext *context = cpublish_context_new();
+  assert_non_null(context);
+
+  cpublish_context_set_int(context, "a", 1, &status);
+  validate_status_ok(&status);
+  assert_true(cpublish_context_contains_key(context, "a", &status));
+  validate_status_ok(&status);
+
+  CPublishValue *default_value = cpublish_value_new_int(2);
+  CPublishValue *value =
+      cpublish_context_get_or_insert(context, "b", default_value, &status);
+  validate_status_ok(&status);
+  assert_int_equal(cpublish_value_int(value, &status), 2);
+  cpublish_value_destroy(default_value);
+
+  cpublish_context_retain(context, retain_not_b, NULL, &status);
+  validate_status_ok(&status);
+  assert_false(cpublish_context_contains_key(context, "b", &status));
+
+  CPublishValue *removed = cpublish_context_remove(context, "a", &status);
+  validate_status_ok(&status);
+  assert_non_null(removed);
+  assert_int_equal(cpublish_value_int(removed, &status), 1);
+  cpublish_value_destroy(removed);
+
+  assert_int_equal(cpublish_context_is_empty(context, &status), true);
+
+  cpublish_context_destroy(context);
+}
+
+static void test_cpublish_context_merge_success(void **state) {
+  CPublishStatus status;
+
+  CPublishContext *context = cpublish_context_new();
+  CPublishContext *other = cpublish_context_new();
+
+  cpublish_context_set_int(context, "a", 1, &status);
+  cpublish_context_set_int(other, "a", 2, &status);
+  cpublish_context_set_int(other, "b", 3, &status);
+
+  cpublish_context_merge(context, other, CPublishMergeStrategyError, &status);
+  assert_int_equal(status.status, CPublishStatusTypeError);
+  cpublish_status_destroy(&status);
+  assert_int_equal(cpublish_context_len(context, &status), 1);
+
+  cpublish_context_merge(context, other, CPublishMergeStrategyOverwrite,
+                         &status);
+  validate_status_ok(&status);
+  assert_int_equal(cpublish_context_len(context, &status), 2);
+
+  const CPublishValue *value = cpublish_context_get(context, "a", &status);
+  assert_int_equal(cpublish_value_int(value, &status), 2);
+
+  cpublish_context_destroy(other);
+  cpublish_context_destroy(context);
+}
+
+static void test_cpublish_context_freeze_success(void **state) {
+  CPublishStatus status;
+
+  CPublishContext *context = cpublish_context_new();
+  CPublishContext *other = cpublish_context_new();
+
+  cpublish_context_set_int(context, "version", 1, &status);
+  cpublish_context_freeze(context, "version", &status);
+  validate_status_ok(&status);
+  cpublish_context_freeze_namespace(context, "asset", &status);
+  validate_status_ok(&status);
+
+  assert_true(cpublish_context_is_frozen(context, "version", &status));
+  assert_true(cpublish_context_is_frozen(context, "asset.name", &status));
+  assert_false(cpublish_context_is_frozen(context, "directory", &status));
+
+  cpublish_context_set_int(other, "version", 2, &status);
+  cpublish_context_merge(context, other, CPublishMergeStrategyOverwrite,
+                         &status);
+  assert_int_equal(status.status, CPublishStatusTypeError);
+  cpublish_status_destroy(&status);
+
+  const CPublishValue *value = cpublish_context_get(context, "version", &status);
+  assert_int_equal(cpublish_value_int(value, &status), 1);
+
+  cpublish_context_destroy(other);
+  cpublish_context_destroy(context);
+}
+
 int main(void) {
   const struct CMUnitTest tests[] = {
       cmocka_unit_test(test_cpublish_context_set_none_success),
@@ -257,6 +351,9 @@ int main(void) {
       cmocka_unit_test(test_cpublish_context_set_string_success),
       cmocka_unit_test(test_cpublish_context_set_success),
       cmocka_unit_test(test_cpublish_context_clone_success),
+      cmocka_unit_test(test_cpublish_context_mutation_success),
+      cmocka_unit_test(test_cpublish_context_merge_success),
+      cmocka_unit_test(test_cpublish_context_freeze_success),
   };
 
   return cmocka_run_group_tests(tests, NULL, NULL);
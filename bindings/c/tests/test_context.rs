@@ -1,6 +1,6 @@
 use std::{
     ffi::{CStr, CString},
-    ptr::null_mut,
+    ptr::{null, null_mut},
 };
 
 use proptest::prelude::*;
@@ -14,12 +14,15 @@ fn arb_value() -> impl Strategy<Value = publish::Value> {
         any::<i64>().prop_map(publish::Value::Integer),
         any::<f64>().prop_map(publish::Value::Float),
         "[^\0]*".prop_map(publish::Value::String),
+        "[^\0]*".prop_map(|v| publish::Value::Secret(publish::Secret::new(v))),
     ];
 
     leaf.prop_recursive(8, 256, 10, |inner| {
         prop_oneof![
-            prop::collection::vec(inner.clone(), 0..10).prop_map(publish::Value::Array),
-            prop::collection::hash_map("[^\0]*", inner, 0..10).prop_map(publish::Value::Object),
+            prop::collection::vec(inner.clone(), 0..10)
+                .prop_map(|v| publish::Value::Array(v.into())),
+            prop::collection::hash_map("[^\0]*", inner, 0..10)
+                .prop_map(|v| publish::Value::Object(v.into_iter().collect())),
         ]
     })
 }
@@ -36,6 +39,10 @@ unsafe fn to_c_value(value: &publish::Value, status: *mut CPublishStatus) -> *mu
             let v = CString::new::<&str>(v.as_ref()).unwrap();
             cpublish_value_new_string(v.as_ptr())
         }
+        publish::Value::Secret(v) => {
+            let v = CString::new(v.expose()).unwrap();
+            cpublish_value_new_secret(v.as_ptr())
+        }
         publish::Value::Array(v) => {
             let array = cpublish_value_new_array();
             for value in v {
@@ -174,6 +181,15 @@ proptest! {
                         assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk, "Err: {}", CStr::from_ptr(status.message).to_string_lossy());
                         assert_eq!(&(*out_value).value, value);
                     },
+                    publish::Value::Secret(v) => {
+                        let c_v = CString::new(v.expose()).unwrap();
+                        cpublish_context_set_secret(context, key.as_ptr(), c_v.as_ptr(), &mut status);
+                        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk, "Err: {}", CStr::from_ptr(status.message).to_string_lossy());
+                        let out_value = cpublish_context_get(context, key.as_ptr(), &mut status);
+                        assert!(!out_value.is_null());
+                        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk, "Err: {}", CStr::from_ptr(status.message).to_string_lossy());
+                        assert_eq!(&(*out_value).value, value);
+                    },
                     publish::Value::String(v) => {
                         let c_v = CString::new(v.as_str()).unwrap();
                         cpublish_context_set_string(context, key.as_ptr(), c_v.as_ptr(), &mut status);
@@ -262,3 +278,204 @@ proptest! {
         }
     }
 }
+
+unsafe extern "C" fn retain_not_b(
+    key: CPublishStringView,
+    _value: *const CPublishValue,
+    user_data: *mut std::ffi::c_void,
+) -> bool {
+    *(user_data as *mut usize) += 1;
+
+    CStr::from_ptr(key.string).to_str().unwrap() != "b"
+}
+
+#[test]
+fn test_cpublish_context_mutation_success() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let context = cpublish_context_new();
+        let a = CString::new("a").unwrap();
+        let b = CString::new("b").unwrap();
+
+        cpublish_context_set_int(context, a.as_ptr(), 1, &mut status);
+        assert!(cpublish_context_contains_key(
+            context,
+            a.as_ptr(),
+            &mut status
+        ));
+        assert!(!cpublish_context_contains_key(
+            context,
+            b.as_ptr(),
+            &mut status
+        ));
+
+        let value = cpublish_context_get_mut(context, a.as_ptr(), &mut status);
+        assert!(!value.is_null());
+        (*value).value = publish::Value::Integer(2);
+        assert_eq!(
+            (*context).inner.get("a").unwrap(),
+            &publish::Value::Integer(2)
+        );
+        assert!(cpublish_context_get_mut(context, b.as_ptr(), &mut status).is_null());
+
+        let default_value = cpublish_value_new_int(3);
+        let value = cpublish_context_get_or_insert(context, b.as_ptr(), default_value, &mut status);
+        assert_eq!((*value).value, publish::Value::Integer(3));
+        let value = cpublish_context_get_or_insert(context, a.as_ptr(), default_value, &mut status);
+        assert_eq!((*value).value, publish::Value::Integer(2));
+        cpublish_value_destroy(default_value);
+
+        let mut calls = 0usize;
+        cpublish_context_retain(
+            context,
+            retain_not_b,
+            &mut calls as *mut usize as *mut std::ffi::c_void,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        assert_eq!(calls, 2);
+        assert_eq!(cpublish_context_len(context, &mut status), 1);
+
+        let removed = cpublish_context_remove(context, a.as_ptr(), &mut status);
+        assert_eq!((*removed).value, publish::Value::Integer(2));
+        cpublish_value_destroy(removed);
+        assert!(cpublish_context_remove(context, a.as_ptr(), &mut status).is_null());
+        assert!(cpublish_context_is_empty(context, &mut status));
+
+        cpublish_context_destroy(context);
+    }
+}
+
+#[test]
+fn test_cpublish_context_merge_success() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let context = cpublish_context_new();
+        let other = cpublish_context_new();
+        let a = CString::new("a").unwrap();
+        let b = CString::new("b").unwrap();
+
+        cpublish_context_set_int(context, a.as_ptr(), 1, &mut status);
+        cpublish_context_set_int(other, a.as_ptr(), 2, &mut status);
+        cpublish_context_set_int(other, b.as_ptr(), 3, &mut status);
+
+        cpublish_context_merge(
+            context,
+            other,
+            CPublishMergeStrategy::CPublishMergeStrategyError,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+        assert_eq!(cpublish_context_len(context, &mut status), 1);
+
+        cpublish_context_merge(
+            context,
+            other,
+            CPublishMergeStrategy::CPublishMergeStrategyKeep,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        assert_eq!(
+            (*context).inner.get("a").unwrap(),
+            &publish::Value::Integer(1)
+        );
+        assert_eq!(
+            (*context).inner.get("b").unwrap(),
+            &publish::Value::Integer(3)
+        );
+
+        cpublish_context_extend(context, other, &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        assert_eq!(
+            (*context).inner.get("a").unwrap(),
+            &publish::Value::Integer(2)
+        );
+
+        cpublish_context_merge(
+            context,
+            null_mut(),
+            CPublishMergeStrategy::CPublishMergeStrategyOverwrite,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        cpublish_context_destroy(other);
+        cpublish_context_destroy(context);
+    }
+}
+
+#[test]
+fn test_cpublish_context_iter_sorted() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let context = cpublish_context_new();
+
+        for key in ["b", "c", "a"] {
+            let key = CString::new(key).unwrap();
+            cpublish_context_set_none(context, key.as_ptr(), &mut status);
+        }
+
+        let iter = cpublish_context_iter(context, &mut status);
+        let mut keys = Vec::new();
+
+        cpublish_context_iter_next(iter, &mut status);
+        while !cpublish_context_iter_is_done(iter, &mut status) {
+            let key = cpublish_context_iter_key(iter, &mut status);
+            keys.push(CStr::from_ptr(key.string).to_str().unwrap().to_string());
+            cpublish_context_iter_next(iter, &mut status);
+        }
+
+        assert_eq!(keys, vec!["a", "b", "c"]);
+
+        cpublish_context_iter_destroy(iter);
+        cpublish_context_destroy(context);
+    }
+}
+
+#[test]
+fn test_cpublish_context_freeze_success() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let context = cpublish_context_new();
+        let other = cpublish_context_new();
+        let version = CString::new("version").unwrap();
+        let asset = CString::new("asset").unwrap();
+        let asset_name = CString::new("asset.name").unwrap();
+
+        cpublish_context_set_int(context, version.as_ptr(), 1, &mut status);
+        cpublish_context_freeze(context, version.as_ptr(), &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        cpublish_context_freeze_namespace(context, asset.as_ptr(), &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+
+        assert!(cpublish_context_is_frozen(
+            context,
+            version.as_ptr(),
+            &mut status
+        ));
+        assert!(cpublish_context_is_frozen(
+            context,
+            asset_name.as_ptr(),
+            &mut status
+        ));
+
+        cpublish_context_set_int(other, version.as_ptr(), 2, &mut status);
+        cpublish_context_merge(
+            context,
+            other,
+            CPublishMergeStrategy::CPublishMergeStrategyOverwrite,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+        assert_eq!(
+            (*context).inner.get("version").unwrap(),
+            &publish::Value::Integer(1)
+        );
+
+        cpublish_context_freeze(context, null(), &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        cpublish_context_destroy(other);
+        cpublish_context_destroy(context);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    ffi::CString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use cpublish::*;
+
+const PLUGIN_SOURCE: &str = r#"
+#include <stdlib.h>
+
+#include "cpublish.h"
+
+static CPublishContext *stage(const CPublishBasePublish *publish,
+                              const CPublishContext *context,
+                              CPublishStatus *status) {
+  return NULL;
+}
+
+static void rollback_stage(const CPublishBasePublish *publish,
+                           const CPublishContext *context,
+                           CPublishStatus *status) {}
+
+static void estimate_space(const CPublishBasePublish *publish,
+                           const CPublishContext *context,
+                           CPublishSpaceEstimates *estimates,
+                           CPublishStatus *status) {}
+
+static CPublishBasePublish *create_publish(void) {
+  CPublishBasePublish *publish = malloc(sizeof(CPublishBasePublish));
+  publish->pre_publish_fn = stage;
+  publish->rollback_pre_publish_fn = rollback_stage;
+  publish->publish_fn = stage;
+  publish->rollback_publish_fn = rollback_stage;
+  publish->post_publish_fn = stage;
+  publish->rollback_post_publish_fn = rollback_stage;
+  publish->estimate_space_fn = estimate_space;
+
+  return publish;
+}
+
+static void destroy_publish(CPublishBasePublish *publish) { free(publish); }
+
+uint32_t cpublish_plugin_abi_version(void) { return PLUGIN_ABI_VERSION; }
+
+bool cpublish_plugin_register(CPublishPluginRegistrar *registrar) {
+  return registrar->register_fn(registrar, "model", "Publish a model", "1.0.0",
+                                create_publish, destroy_publish) &&
+         registrar->register_fn(registrar, "rig", NULL, NULL, create_publish,
+                                destroy_publish);
+}
+"#;
+
+/// Build the test plugin, or return None if there is no C compiler.
+fn build_plugin(dir: &Path, abi_version: u32) -> Option<PathBuf> {
+    let source = dir.join("plugin.c");
+    let library = dir.join(format!("plugin_{}.so", abi_version));
+    std::fs::write(&source, PLUGIN_SOURCE).unwrap();
+
+    let output = Command::new("cc")
+        .arg("-shared")
+        .arg("-fPIC")
+        .arg(format!("-DPLUGIN_ABI_VERSION={}", abi_version))
+        .arg("-I")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("include"))
+        .arg("-o")
+        .arg(&library)
+        .arg(&source)
+        .output()
+        .ok()?;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Some(library)
+}
+
+#[test]
+fn test_load_plugin_success() {
+    let dir = tempfile::tempdir().unwrap();
+    let library = match build_plugin(dir.path(), CPUBLISH_PLUGIN_ABI_VERSION) {
+        Some(library) => library,
+        None => return,
+    };
+    let mut registry = publish::Registry::new();
+
+    let names = unsafe { load_plugin(&library, &mut registry) }.unwrap();
+
+    assert_eq!(names, vec!["model".to_string(), "rig".to_string()]);
+    assert_eq!(
+        registry.metadata("model").unwrap().description,
+        "Publish a model"
+    );
+    assert_eq!(registry.metadata("rig").unwrap().version, "");
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let context = publish::Context::new([("asset".to_string(), publish::Value::Integer(1))]);
+    let result = rt.block_on(registry.run("model", context)).unwrap();
+
+    assert_eq!(result.get("asset").unwrap(), &publish::Value::Integer(1));
+}
+
+#[test]
+fn test_load_plugin_abi_version_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let library = match build_plugin(dir.path(), CPUBLISH_PLUGIN_ABI_VERSION + 1) {
+        Some(library) => library,
+        None => return,
+    };
+    let mut registry = publish::Registry::new();
+
+    let result = unsafe { load_plugin(&library, &mut registry) };
+
+    assert!(matches!(result, Err(publish::Error::Plugin(_))));
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_load_plugin_duplicate_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let library = match build_plugin(dir.path(), CPUBLISH_PLUGIN_ABI_VERSION) {
+        Some(library) => library,
+        None => return,
+    };
+    let mut registry = publish::Registry::new();
+    registry
+        .register("rig", publish::PublishMetadata::default(), || unsafe {
+            cpublish_publish_new_default()
+        })
+        .unwrap();
+
+    let result = unsafe { load_plugin(&library, &mut registry) };
+
+    assert!(matches!(result, Err(publish::Error::Plugin(_))));
+    assert_eq!(registry.names().collect::<Vec<_>>(), vec!["rig"]);
+}
+
+#[test]
+fn test_cpublish_registry_load_plugin_failure() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let registry = cpublish_registry_new();
+        let path = CString::new("/path/does/not/exist.so").unwrap();
+
+        cpublish_registry_load_plugin(registry, path.as_ptr(), &mut status);
+
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+        assert_eq!(cpublish_registry_len(registry, &mut status), 0);
+
+        cpublish_registry_destroy(registry);
+    }
+}
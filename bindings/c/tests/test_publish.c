@@ -85,6 +85,8 @@ static void test_cpublish_publish_new_default_success(void **state) {
   assert_ptr_equal(publish.post_publish_fn, cpublish_publish_default_publish);
   assert_ptr_equal(publish.rollback_post_publish_fn,
                    cpublish_publish_default_rollback_publish);
+  assert_ptr_equal(publish.estimate_space_fn,
+                   cpublish_publish_default_estimate_space);
 }
 
 static void test_cpublish_publish_pre_publish_success(void **state) {
@@ -34,6 +34,10 @@ fn test_cpublish_publish_new_default_success() {
             publish.rollback_post_publish_fn as usize,
             cpublish_publish_default_rollback_publish as usize
         );
+        assert_eq!(
+            publish.estimate_space_fn as usize,
+            cpublish_publish_default_estimate_space as usize
+        );
     }
 }
 
@@ -444,3 +448,49 @@ fn test_cpublish_publish_rollback_post_publish_success() {
         );
     }
 }
+
+#[test]
+fn test_cpublish_publish_estimate_space_success() {
+    unsafe {
+        pub unsafe extern "C" fn estimate_space(
+            _publish: *const CPublishBasePublish,
+            _context: *const CPublishContext,
+            estimates: *mut CPublishSpaceEstimates,
+            status: *mut CPublishStatus,
+        ) {
+            let path = CString::new("/publish/chair").unwrap();
+            cpublish_space_estimates_push(estimates, path.as_ptr(), 1024, status);
+        }
+
+        let mut publish = cpublish_publish_new_default();
+        let context = cpublish_context_new();
+        let estimates = cpublish_space_estimates_new();
+        let mut status = CPublishStatus::new_ok();
+
+        cpublish_publish_estimate_space(&publish, context, estimates, &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        assert_eq!(cpublish_space_estimates_len(estimates), 0);
+
+        publish.estimate_space_fn = estimate_space;
+        cpublish_publish_estimate_space(&publish, context, estimates, &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        assert_eq!(cpublish_space_estimates_len(estimates), 1);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(publish::Publish::estimate_space(
+                &publish,
+                &publish::Context::default(),
+            ))
+            .unwrap();
+        assert_eq!(
+            result,
+            vec![publish::SpaceEstimate::new("/publish/chair", 1024)]
+        );
+
+        cpublish_space_estimates_destroy(estimates);
+        cpublish_context_destroy(context);
+    }
+}
@@ -0,0 +1,141 @@
+#include <setjmp.h>
+#include <stdarg.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <cmocka.h>
+
+#include "cpublish.h"
+#include "test_utils.h"
+
+CPublishContext *publish_should_pass(const CPublishBasePublish *publish,
+                                     const CPublishContext *context,
+                                     CPublishStatus *status) {
+  cpublish_status_ok(status);
+  assert_non_null(publish);
+  assert_non_null(context);
+  assert_non_null(status);
+
+  CPublishContext *ctx = cpublish_context_clone(context, status);
+
+  if (status != NULL && status->status == CPublishStatusTypeError) {
+    return NULL;
+  }
+
+  cpublish_context_set_string(ctx, "publish_key", "publish_value", status);
+
+  if (status != NULL && status->status == CPublishStatusTypeError) {
+    return NULL;
+  }
+
+  return ctx;
+}
+
+CPublishBasePublish *create_publish(void) {
+  CPublishBasePublish *publish = malloc(sizeof(CPublishBasePublish));
+  *publish = cpublish_publish_new_default();
+  publish->publish_fn = publish_should_pass;
+
+  return publish;
+}
+
+void destroy_publish(CPublishBasePublish *publish) { free(publish); }
+
+/*
+----------------------------------------------------------------------------
+  Checks
+*/
+static void test_cpublish_registry_register_success(void **state) {
+  CPublishStatus status;
+  CPublishRegistry *registry = cpublish_registry_new();
+  assert_non_null(registry);
+
+  CPublishPublishMetadata *metadata =
+      cpublish_publish_metadata_new("Publish a model", "1.0.0", &status);
+  validate_status_ok(&status);
+  CPublishValueType value_type = CPublishValueTypeString;
+  cpublish_publish_metadata_add_schema_field(metadata, "asset", &value_type,
+                                             true, &status);
+  validate_status_ok(&status);
+
+  cpublish_registry_register(registry, "model", metadata, create_publish,
+                             destroy_publish, &status);
+  validate_status_ok(&status);
+  cpublish_publish_metadata_destroy(metadata);
+
+  assert_true(cpublish_registry_contains(registry, "model", &status));
+  validate_status_ok(&status);
+  assert_false(cpublish_registry_contains(registry, "rig", &status));
+  validate_status_ok(&status);
+  assert_int_equal(cpublish_registry_len(registry, &status), 1);
+  validate_status_ok(&status);
+
+  CPublishString name = cpublish_registry_name(registry, 0, &status);
+  validate_status_ok(&status);
+  assert_string_equal(name.string, "model");
+  cpublish_string_destroy(&name);
+
+  CPublishPublishMetadata *out_metadata =
+      cpublish_registry_metadata(registry, "model", &status);
+  validate_status_ok(&status);
+  CPublishString description =
+      cpublish_publish_metadata_description(out_metadata, &status);
+  validate_status_ok(&status);
+  assert_string_equal(description.string, "Publish a model");
+  cpublish_string_destroy(&description);
+  cpublish_publish_metadata_destroy(out_metadata);
+
+  cpublish_registry_destroy(registry);
+}
+
+static void test_cpublish_registry_run_success(void **state) {
+  CPublishStatus status;
+  CPublishRegistry *registry = cpublish_registry_new();
+
+  cpublish_registry_register(registry, "model", NULL, create_publish,
+                             destroy_publish, &status);
+  validate_status_ok(&status);
+
+  CPublishContext *context =
+      cpublish_registry_run(registry, "model", NULL, &status);
+  validate_status_ok(&status);
+  assert_non_null(context);
+
+  const CPublishValue *value =
+      cpublish_context_get(context, "publish_key", &status);
+  validate_status_ok(&status);
+  assert_non_null(value);
+  CPublishString result = cpublish_value_string(value, &status);
+  validate_status_ok(&status);
+  assert_string_equal(result.string, "publish_value");
+  cpublish_string_destroy(&result);
+
+  cpublish_context_destroy(context);
+  cpublish_registry_destroy(registry);
+}
+
+static void test_cpublish_registry_run_missing_failure(void **state) {
+  CPublishStatus status;
+  CPublishRegistry *registry = cpublish_registry_new();
+
+  CPublishContext *context =
+      cpublish_registry_run(registry, "model", NULL, &status);
+  assert_int_equal(status.status, CPublishStatusTypeError);
+  assert_null(context);
+  cpublish_status_destroy(&status);
+
+  cpublish_registry_destroy(registry);
+}
+
+int main(void) {
+  const struct CMUnitTest tests[] = {
+      cmocka_unit_test(test_cpublish_registry_register_success),
+      cmocka_unit_test(test_cpublish_registry_run_success),
+      cmocka_unit_test(test_cpublish_registry_run_missing_failure),
+  };
+
+  return cmocka_run_group_tests(tests, NULL, NULL);
+}
@@ -0,0 +1,205 @@
+use std::{
+    ffi::{CStr, CString},
+    ptr::null_mut,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use cpublish::*;
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+static DESTROYED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe extern "C" fn publish_should_pass(
+    publish: *const CPublishBasePublish,
+    context: *const CPublishContext,
+    status: *mut CPublishStatus,
+) -> *mut CPublishContext {
+    cpublish_status_ok(status);
+    assert!(!publish.is_null());
+    assert!(!context.is_null());
+
+    let ctx = cpublish_context_clone(context, status);
+
+    if !status.is_null() && (*status).status == CPublishStatusType::CPublishStatusTypeError {
+        return null_mut();
+    }
+
+    let key = CString::new("publish").unwrap();
+    cpublish_context_set_bool(ctx, key.as_ptr(), true, status);
+
+    ctx
+}
+
+unsafe extern "C" fn create_publish() -> *mut CPublishBasePublish {
+    CREATED.fetch_add(1, Ordering::SeqCst);
+    let mut publish = cpublish_publish_new_default();
+    publish.publish_fn = publish_should_pass;
+
+    Box::into_raw(Box::new(publish))
+}
+
+unsafe extern "C" fn destroy_publish(publish: *mut CPublishBasePublish) {
+    DESTROYED.fetch_add(1, Ordering::SeqCst);
+    drop(Box::from_raw(publish));
+}
+
+unsafe extern "C" fn create_null_publish() -> *mut CPublishBasePublish {
+    null_mut()
+}
+
+#[test]
+fn test_cpublish_registry_register_success() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let registry = cpublish_registry_new();
+        let description = CString::new("Publish a model").unwrap();
+        let version = CString::new("1.0.0").unwrap();
+        let metadata =
+            cpublish_publish_metadata_new(description.as_ptr(), version.as_ptr(), &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+
+        let name = CString::new("model").unwrap();
+        cpublish_registry_register(
+            registry,
+            name.as_ptr(),
+            metadata,
+            create_publish,
+            destroy_publish,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        cpublish_publish_metadata_destroy(metadata);
+
+        assert!(cpublish_registry_contains(
+            registry,
+            name.as_ptr(),
+            &mut status
+        ));
+        assert_eq!(cpublish_registry_len(registry, &mut status), 1);
+
+        let out_name = cpublish_registry_name(registry, 0, &mut status);
+        assert_eq!(CStr::from_ptr(out_name.string).to_str().unwrap(), "model");
+
+        let out_metadata = cpublish_registry_metadata(registry, name.as_ptr(), &mut status);
+        assert!(!out_metadata.is_null());
+        let out_description = cpublish_publish_metadata_description(out_metadata, &mut status);
+        assert_eq!(
+            CStr::from_ptr(out_description.string).to_str().unwrap(),
+            "Publish a model"
+        );
+        let out_version = cpublish_publish_metadata_version(out_metadata, &mut status);
+        assert_eq!(
+            CStr::from_ptr(out_version.string).to_str().unwrap(),
+            "1.0.0"
+        );
+        cpublish_publish_metadata_destroy(out_metadata);
+
+        cpublish_registry_register(
+            registry,
+            name.as_ptr(),
+            null_mut(),
+            create_publish,
+            destroy_publish,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        assert!(cpublish_registry_unregister(
+            registry,
+            name.as_ptr(),
+            &mut status
+        ));
+        assert_eq!(cpublish_registry_len(registry, &mut status), 0);
+
+        cpublish_registry_destroy(registry);
+    }
+}
+
+#[test]
+fn test_cpublish_registry_run_success() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let registry = cpublish_registry_new();
+        let name = CString::new("model").unwrap();
+        let created = CREATED.load(Ordering::SeqCst);
+        let destroyed = DESTROYED.load(Ordering::SeqCst);
+
+        cpublish_registry_register(
+            registry,
+            name.as_ptr(),
+            null_mut(),
+            create_publish,
+            destroy_publish,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+
+        let context = cpublish_registry_run(registry, name.as_ptr(), null_mut(), &mut status);
+        assert_eq!(
+            status.status,
+            CPublishStatusType::CPublishStatusTypeOk,
+            "Err: {}",
+            CStr::from_ptr(status.message).to_string_lossy()
+        );
+        assert!(!context.is_null());
+        assert_eq!(
+            (*context).inner.get("publish").unwrap(),
+            &publish::Value::Boolean(true)
+        );
+        assert!(CREATED.load(Ordering::SeqCst) > created);
+        assert!(DESTROYED.load(Ordering::SeqCst) > destroyed);
+
+        cpublish_context_destroy(context);
+        cpublish_registry_destroy(registry);
+    }
+}
+
+#[test]
+fn test_cpublish_registry_run_failure() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let registry = cpublish_registry_new();
+        let name = CString::new("model").unwrap();
+        let missing_name = CString::new("rig").unwrap();
+        let key = CString::new("asset").unwrap();
+        let value_type = CPublishValueType::CPublishValueTypeString;
+        let empty = CString::new("").unwrap();
+        let metadata = cpublish_publish_metadata_new(empty.as_ptr(), empty.as_ptr(), &mut status);
+        cpublish_publish_metadata_add_schema_field(
+            metadata,
+            key.as_ptr(),
+            &value_type,
+            true,
+            &mut status,
+        );
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+
+        cpublish_registry_register(
+            registry,
+            name.as_ptr(),
+            metadata,
+            create_null_publish,
+            destroy_publish,
+            &mut status,
+        );
+        cpublish_publish_metadata_destroy(metadata);
+
+        let context =
+            cpublish_registry_run(registry, missing_name.as_ptr(), null_mut(), &mut status);
+        assert!(context.is_null());
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        let context = cpublish_registry_run(registry, name.as_ptr(), null_mut(), &mut status);
+        assert!(context.is_null());
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        let input = cpublish_context_new();
+        cpublish_context_set_string(input, key.as_ptr(), name.as_ptr(), &mut status);
+        let context = cpublish_registry_run(registry, name.as_ptr(), input, &mut status);
+        assert!(context.is_null());
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        cpublish_context_destroy(input);
+        cpublish_registry_destroy(registry);
+    }
+}
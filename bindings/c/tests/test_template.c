@@ -0,0 +1,81 @@
+#include <setjmp.h>
+#include <stdarg.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <cmocka.h>
+
+#include "cpublish.h"
+#include "test_utils.h"
+
+static void test_cpublish_template_render_success(void **state) {
+  CPublishStatus status;
+
+  CPublishTemplate *template =
+      cpublish_template_new("{show}/{asset:lower}/v{version:03}", &status);
+  validate_status_ok(&status);
+
+  CPublishContext *context = cpublish_context_new();
+  cpublish_context_set_string(context, "show", "demo", &status);
+  cpublish_context_set_string(context, "asset", "Chair", &status);
+  cpublish_context_set_int(context, "version", 7, &status);
+
+  CPublishString text = cpublish_template_render(template, context, &status);
+  validate_status_ok(&status);
+  assert_string_equal(text.string, "demo/chair/v007");
+  cpublish_string_destroy(&text);
+
+  assert_int_equal(cpublish_template_field_count(template, &status), 3);
+
+  cpublish_context_destroy(context);
+  cpublish_template_destroy(template);
+}
+
+static void test_cpublish_template_render_failure(void **state) {
+  CPublishStatus status;
+
+  CPublishTemplate *template = cpublish_template_new("{show}/{asset}", &status);
+  validate_status_ok(&status);
+
+  CPublishContext *context = cpublish_context_new();
+
+  CPublishString text = cpublish_template_render(template, context, &status);
+  assert_int_equal(status.status, CPublishStatusTypeError);
+  assert_non_null(strstr(status.message, "asset"));
+  cpublish_status_destroy(&status);
+  cpublish_string_destroy(&text);
+
+  cpublish_context_destroy(context);
+  cpublish_template_destroy(template);
+}
+
+static void test_cpublish_template_parse_success(void **state) {
+  CPublishStatus status;
+
+  CPublishTemplate *template =
+      cpublish_template_new("{asset}/v{version:03}", &status);
+  validate_status_ok(&status);
+
+  CPublishContext *context =
+      cpublish_template_parse(template, "chair/v007", &status);
+  validate_status_ok(&status);
+
+  const CPublishValue *value = cpublish_context_get(context, "version", &status);
+  assert_int_equal(cpublish_value_int(value, &status), 7);
+
+  cpublish_context_destroy(context);
+  cpublish_template_destroy(template);
+}
+
+int main(void) {
+  const struct CMUnitTest tests[] = {
+      cmocka_unit_test(test_cpublish_template_render_success),
+      cmocka_unit_test(test_cpublish_template_render_failure),
+      cmocka_unit_test(test_cpublish_template_parse_success),
+  };
+
+  return cmocka_run_group_tests(tests, NULL, NULL);
+}
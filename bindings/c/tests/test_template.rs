@@ -0,0 +1,105 @@
+use std::{
+    ffi::{CStr, CString},
+    ptr::null,
+};
+
+use cpublish::*;
+
+#[test]
+fn test_cpublish_template_render_success() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let source = CString::new("{asset:upper}/v{version:03}").unwrap();
+        let template = cpublish_template_new(source.as_ptr(), &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+
+        let context = cpublish_context_new();
+        let asset = CString::new("asset").unwrap();
+        let chair = CString::new("chair").unwrap();
+        let version = CString::new("version").unwrap();
+        cpublish_context_set_string(context, asset.as_ptr(), chair.as_ptr(), &mut status);
+        cpublish_context_set_int(context, version.as_ptr(), 7, &mut status);
+
+        let text = cpublish_template_render(template, context, &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        assert_eq!(CStr::from_ptr(text.string).to_str().unwrap(), "CHAIR/v007");
+
+        assert_eq!(cpublish_template_field_count(template, &mut status), 2);
+        let key = cpublish_template_field_key(template, 1, &mut status);
+        assert_eq!(CStr::from_ptr(key.string).to_str().unwrap(), "version");
+
+        cpublish_template_destroy(template);
+        cpublish_context_destroy(context);
+    }
+}
+
+#[test]
+fn test_cpublish_template_render_missing_keys() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let source = CString::new("{show}/{asset}").unwrap();
+        let template = cpublish_template_new(source.as_ptr(), &mut status);
+        let context = cpublish_context_new();
+
+        let text = cpublish_template_render(template, context, &mut status);
+
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+        let message = CStr::from_ptr(status.message).to_str().unwrap();
+        assert!(message.contains("\"show\""));
+        assert!(message.contains("\"asset\""));
+        assert_eq!(CStr::from_ptr(text.string).to_str().unwrap(), "");
+
+        cpublish_template_render(template, null(), &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        cpublish_template_destroy(template);
+        cpublish_context_destroy(context);
+    }
+}
+
+#[test]
+fn test_cpublish_template_new_failure() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let source = CString::new("{show").unwrap();
+
+        let template = cpublish_template_new(source.as_ptr(), &mut status);
+
+        assert!(template.is_null());
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        let template = cpublish_template_new(null(), &mut status);
+
+        assert!(template.is_null());
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+    }
+}
+
+#[test]
+fn test_cpublish_template_parse_success() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let source = CString::new("{asset}/v{version:03}").unwrap();
+        let template = cpublish_template_new(source.as_ptr(), &mut status);
+
+        let text = CString::new("chair/v007").unwrap();
+        let context = cpublish_template_parse(template, text.as_ptr(), &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        assert_eq!(
+            (*context).inner.get("version"),
+            Some(&publish::Value::Integer(7))
+        );
+        assert_eq!(
+            (*context).inner.get("asset"),
+            Some(&publish::Value::String("chair".to_string()))
+        );
+
+        let text = CString::new("chair/latest").unwrap();
+        let missing = cpublish_template_parse(template, text.as_ptr(), &mut status);
+        assert!(missing.is_null());
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        cpublish_context_destroy(context);
+        cpublish_template_destroy(template);
+    }
+}
@@ -148,6 +148,28 @@ static void test_cpublish_value_new_string_success(void **state) {
   cpublish_status_destroy(&status);
 }
 
+static void test_cpublish_value_new_secret_success(void **state) {
+  CPublishStatus status;
+
+  CPublishValue *value = cpublish_value_new_secret("hunter2");
+  assert_non_null(value);
+
+  assert_int_equal(cpublish_value_type(value, &status), CPublishValueTypeSecret);
+  validate_status_ok(&status);
+
+  CPublishString result = cpublish_value_secret_expose(value, &status);
+  validate_status_ok(&status);
+  assert_string_equal(result.string, "hunter2");
+  cpublish_string_destroy(&result);
+
+  result = cpublish_value_string(value, &status);
+  assert_int_equal(status.status, CPublishStatusTypeError);
+  cpublish_string_destroy(&result);
+
+  cpublish_value_destroy(value);
+  cpublish_status_destroy(&status);
+}
+
 static void test_cpublish_value_new_array_success(void **state) {
   CPublishStatus status;
 
@@ -333,6 +355,7 @@ int main(void) {
       cmocka_unit_test(test_cpublish_value_new_int_success),
       cmocka_unit_test(test_cpublish_value_new_float_success),
       cmocka_unit_test(test_cpublish_value_new_string_success),
+      cmocka_unit_test(test_cpublish_value_new_secret_success),
       cmocka_unit_test(test_cpublish_value_new_array_success),
       cmocka_unit_test(test_cpublish_value_new_array_with_capacity_success),
       cmocka_unit_test(test_cpublish_value_new_object_success),
@@ -6,13 +6,13 @@ use cpublish::*;
 
 fn arb_value_array() -> impl Strategy<Value = publish::Value> {
     prop::collection::vec(arb_value(), 0..10)
-        .prop_map(publish::Value::Array)
+        .prop_map(|v| publish::Value::Array(v.into()))
         .boxed()
 }
 
 fn arb_value_object() -> impl Strategy<Value = publish::Value> {
     prop::collection::hash_map("[^\0]*", arb_value(), 0..10)
-        .prop_map(publish::Value::Object)
+        .prop_map(|v| publish::Value::Object(v.into_iter().collect()))
         .boxed()
 }
 
@@ -27,8 +27,10 @@ fn arb_value() -> impl Strategy<Value = publish::Value> {
 
     leaf.prop_recursive(8, 256, 10, |inner| {
         prop_oneof![
-            prop::collection::vec(inner.clone(), 0..10).prop_map(publish::Value::Array),
-            prop::collection::hash_map("[^\0]*", inner, 0..10).prop_map(publish::Value::Object),
+            prop::collection::vec(inner.clone(), 0..10)
+                .prop_map(|v| publish::Value::Array(v.into())),
+            prop::collection::hash_map("[^\0]*", inner, 0..10)
+                .prop_map(|v| publish::Value::Object(v.into_iter().collect())),
         ]
     })
 }
@@ -356,3 +358,56 @@ proptest! {
         }
     }
 }
+
+#[test]
+fn test_cpublish_value_object_iter_sorted() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let value = cpublish_value_new_object();
+        let item = cpublish_value_new_none();
+
+        for key in ["b", "c", "a"] {
+            let key = CString::new(key).unwrap();
+            cpublish_value_object_insert(value, key.as_ptr(), item, &mut status);
+        }
+
+        let iter = cpublish_value_object_iter(value, &mut status);
+        let mut keys = Vec::new();
+
+        while !cpublish_value_iter_object_is_done(iter, &mut status) {
+            let key = cpublish_value_iter_object_key(iter, &mut status);
+            keys.push(CStr::from_ptr(key.string).to_str().unwrap().to_string());
+            cpublish_value_iter_object_next(iter, &mut status);
+        }
+
+        assert_eq!(keys, vec!["a", "b", "c"]);
+
+        cpublish_value_iter_object_destroy(iter);
+        cpublish_value_destroy(item);
+        cpublish_value_destroy(value);
+    }
+}
+
+#[test]
+fn test_cpublish_value_new_secret_success() {
+    unsafe {
+        let mut status = CPublishStatus::new_ok();
+        let secret = CString::new("hunter2").unwrap();
+        let value = cpublish_value_new_secret(secret.as_ptr());
+
+        assert_eq!(
+            cpublish_value_type(value, &mut status),
+            CPublishValueType::CPublishValueTypeSecret
+        );
+        assert!(!format!("{:?}", (*value).value).contains("hunter2"));
+
+        let exposed = cpublish_value_secret_expose(value, &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeOk);
+        assert_eq!(CStr::from_ptr(exposed.string).to_str().unwrap(), "hunter2");
+
+        cpublish_value_string(value, &mut status);
+        assert_eq!(status.status, CPublishStatusType::CPublishStatusTypeError);
+
+        cpublish_value_destroy(value);
+    }
+}
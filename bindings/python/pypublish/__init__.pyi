@@ -1,21 +1,59 @@
 from __future__ import annotations
 
-from typing import Dict, List, Union
+from typing import Callable, Dict, List, Literal, Optional, Tuple, Union
 
-Value = Union[None, bool, int, float, str, List["Value"], Dict[str, "Value"]]
+Value = Union[
+    None, bool, int, float, str, "Secret", List["Value"], Dict[str, "Value"]
+]
+
+class Secret:
+    def __init__(self, value: str) -> None: ...
+    def expose(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
 
 class Context:
     def __init__(self) -> None: ...
     def get(self, key: str) -> Value: ...
     def set(self, key: str, value: Value) -> None: ...
+    def remove(self, key: str) -> Optional[Value]: ...
+    def keys(self) -> List[str]: ...
+    def values(self) -> List[Value]: ...
+    def items(self) -> List[Tuple[str, Value]]: ...
+    def setdefault(self, key: str, default: Value) -> Value: ...
+    def update(self, other: Context) -> None: ...
+    def retain(self, predicate: Callable[[str, Value], bool]) -> None: ...
+    def merge(
+        self,
+        other: Context,
+        strategy: Literal["overwrite", "keep", "error"] = "overwrite",
+    ) -> None: ...
+    def freeze(self, key: str) -> None: ...
+    def freeze_namespace(self, namespace: str) -> None: ...
+    def is_frozen(self, key: str) -> bool: ...
     def copy(self) -> Context: ...
     def to_view(self) -> ContextView: ...
+    def __contains__(self, key: str) -> bool: ...
+    def __len__(self) -> int: ...
 
 class ContextView:
     def __init__(self, context: Context) -> None: ...
     def get(self, key: str) -> Value: ...
+    def keys(self) -> List[str]: ...
+    def values(self) -> List[Value]: ...
+    def items(self) -> List[Tuple[str, Value]]: ...
+    def is_frozen(self, key: str) -> bool: ...
     def copy(self) -> Context: ...
     def to_view(self) -> ContextView: ...
+    def __contains__(self, key: str) -> bool: ...
+    def __len__(self) -> int: ...
+
+class Template:
+    def __init__(self, source: str) -> None: ...
+    @property
+    def source(self) -> str: ...
+    def keys(self) -> List[str]: ...
+    def render(self, context: Union[Context, ContextView]) -> str: ...
+    def parse(self, text: str) -> Context: ...
 
 class Publish:
     async def pre_publish(
@@ -28,5 +66,42 @@ class Publish:
         self, context: ContextView
     ) -> Union[Context, ContextView]: ...
     async def rollback_post_publish(self, context: ContextView) -> None: ...
+    async def estimate_space(self, context: ContextView) -> List[Tuple[str, int]]: ...
+
+class SchemaField:
+    def __init__(
+        self, key: str, value_type: Optional[str] = None, required: bool = True
+    ) -> None: ...
+    @property
+    def key(self) -> str: ...
+    @property
+    def value_type(self) -> Optional[str]: ...
+    @property
+    def required(self) -> bool: ...
+
+class PublishMetadata:
+    @property
+    def description(self) -> str: ...
+    @property
+    def version(self) -> str: ...
+    @property
+    def schema(self) -> List[SchemaField]: ...
+
+class Registry:
+    def __init__(self) -> None: ...
+    def register(
+        self,
+        name: str,
+        factory: Callable[[], Publish],
+        description: str = "",
+        version: str = "",
+        schema: Optional[List[SchemaField]] = None,
+    ) -> None: ...
+    def unregister(self, name: str) -> bool: ...
+    def names(self) -> List[str]: ...
+    def metadata(self, name: str) -> PublishMetadata: ...
+    async def run(self, name: str, context: Optional[Context] = None) -> Context: ...
+    def __contains__(self, name: str) -> bool: ...
+    def __len__(self) -> int: ...
 
 async def run(publish: Publish) -> Context: ...
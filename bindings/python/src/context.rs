@@ -1,6 +1,7 @@
 use pyo3::{
+    exceptions::PyValueError,
     prelude::*,
-    types::{PyBool, PyFloat, PyInt, PyString},
+    types::{IntoPyDict, PyBool, PyFloat, PyInt, PyString},
 };
 
 #[derive(Debug, Clone)]
@@ -26,6 +27,10 @@ impl<'py> FromPyObject<'py> for Value {
             Ok(Self {
                 inner: publish::Value::Float(obj.extract::<f64>()?),
             })
+        } else if obj.is_instance_of::<Secret>() {
+            Ok(Self {
+                inner: publish::Value::Secret(obj.extract::<Secret>()?.inner),
+            })
         } else if obj.is_instance_of::<PyString>() {
             Ok(Self {
                 inner: publish::Value::String(obj.extract::<String>()?),
@@ -57,6 +62,7 @@ impl IntoPy<PyObject> for Value {
             publish::Value::Integer(value) => value.to_object(py),
             publish::Value::Float(value) => value.to_object(py),
             publish::Value::String(value) => value.to_object(py),
+            publish::Value::Secret(value) => Secret { inner: value }.into_py(py),
             publish::Value::Array(value) => value
                 .into_iter()
                 .map(|v| Value { inner: v })
@@ -65,8 +71,8 @@ impl IntoPy<PyObject> for Value {
             publish::Value::Object(value) => value
                 .into_iter()
                 .map(|(k, v)| (k, Value { inner: v }))
-                .collect::<std::collections::HashMap<String, Value>>()
-                .to_object(py),
+                .into_py_dict(py)
+                .into(),
         }
     }
 }
@@ -79,6 +85,10 @@ impl ToPyObject for Value {
             publish::Value::Integer(value) => value.to_object(py),
             publish::Value::Float(value) => value.to_object(py),
             publish::Value::String(value) => value.to_object(py),
+            publish::Value::Secret(value) => Secret {
+                inner: value.clone(),
+            }
+            .into_py(py),
             publish::Value::Array(value) => value
                 .iter()
                 .map(|v| Value { inner: v.clone() })
@@ -87,12 +97,44 @@ impl ToPyObject for Value {
             publish::Value::Object(value) => value
                 .iter()
                 .map(|(k, v)| (k, Value { inner: v.clone() }))
-                .collect::<std::collections::HashMap<&String, Value>>()
-                .to_object(py),
+                .into_py_dict(py)
+                .into(),
         }
     }
 }
 
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Secret {
+    inner: publish::Secret,
+}
+
+#[pymethods]
+impl Secret {
+    #[new]
+    fn new(value: &str) -> Self {
+        Self {
+            inner: publish::Secret::new(value),
+        }
+    }
+
+    fn expose(&self) -> &str {
+        self.inner.expose()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.inner)
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
 #[pyclass]
 #[derive(Debug, Clone)]
 pub(crate) struct Context {
@@ -133,10 +175,112 @@ impl Context {
         self.inner.set(key, value.inner);
     }
 
+    fn remove(&mut self, key: &str) -> Option<Value> {
+        self.inner.remove(key).map(|value| Value { inner: value })
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.inner.keys().cloned().collect()
+    }
+
+    fn values(&self) -> Vec<Value> {
+        self.inner
+            .values()
+            .map(|value| Value {
+                inner: value.clone(),
+            })
+            .collect()
+    }
+
+    fn items(&self) -> Vec<(String, Value)> {
+        self.inner
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.clone(),
+                    Value {
+                        inner: value.clone(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn setdefault(&mut self, key: &str, default: Value) -> Value {
+        Value {
+            inner: self.inner.entry(key).or_insert(default.inner).clone(),
+        }
+    }
+
+    fn update(&mut self, other: Context) {
+        self.inner.extend(other.inner);
+    }
+
+    fn retain(&mut self, py: Python<'_>, predicate: PyObject) -> PyResult<()> {
+        let mut error = None;
+
+        self.inner.retain(|key, value| {
+            if error.is_some() {
+                return true;
+            }
+
+            let value = Value {
+                inner: value.clone(),
+            };
+
+            match predicate
+                .call1(py, (key, value))
+                .and_then(|result| result.is_true(py))
+            {
+                Ok(keep) => keep,
+                Err(err) => {
+                    error = Some(err);
+                    true
+                }
+            }
+        });
+
+        match error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    #[pyo3(signature = (other, strategy = "overwrite"))]
+    fn merge(&mut self, other: Context, strategy: &str) -> PyResult<()> {
+        let strategy = strategy
+            .parse::<publish::MergeStrategy>()
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+        self.inner
+            .merge(other.inner, strategy)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    fn freeze(&mut self, key: &str) {
+        self.inner.freeze(key)
+    }
+
+    fn freeze_namespace(&mut self, namespace: &str) {
+        self.inner.freeze_namespace(namespace)
+    }
+
+    fn is_frozen(&self, key: &str) -> bool {
+        self.inner.is_frozen(key)
+    }
+
     fn copy(&self) -> Context {
         self.clone()
     }
 
+    fn __contains__(&self, key: &str) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
     pub(crate) fn to_view(&self) -> ContextView {
         ContextView {
             inner: self.clone(),
@@ -156,10 +300,34 @@ impl ContextView {
         self.inner.get(key)
     }
 
+    fn keys(&self) -> Vec<String> {
+        self.inner.keys()
+    }
+
+    fn values(&self) -> Vec<Value> {
+        self.inner.values()
+    }
+
+    fn items(&self) -> Vec<(String, Value)> {
+        self.inner.items()
+    }
+
+    fn is_frozen(&self, key: &str) -> bool {
+        self.inner.is_frozen(key)
+    }
+
     fn copy(&self) -> Context {
         self.inner.clone()
     }
 
+    fn __contains__(&self, key: &str) -> bool {
+        self.inner.__contains__(key)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.__len__()
+    }
+
     fn to_view(&self) -> ContextView {
         self.clone()
     }
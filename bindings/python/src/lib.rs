@@ -3,11 +3,15 @@ use pyo3::prelude::*;
 mod context;
 mod publish;
 mod publish_wrapper;
+mod registry;
 mod runner;
+mod template;
 
-use context::{Context, ContextView};
+use context::{Context, ContextView, Secret};
 use publish::Publish;
+use registry::{PublishMetadata, Registry, SchemaField};
 use runner::run;
+use template::Template;
 
 #[pymodule]
 fn pypublish(_py: Python, m: &PyModule) -> PyResult<()> {
@@ -16,6 +20,11 @@ fn pypublish(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Context>()?;
     m.add_class::<ContextView>()?;
     m.add_class::<Publish>()?;
+    m.add_class::<PublishMetadata>()?;
+    m.add_class::<Registry>()?;
+    m.add_class::<SchemaField>()?;
+    m.add_class::<Secret>()?;
+    m.add_class::<Template>()?;
 
     Ok(())
 }
@@ -53,4 +53,14 @@ impl Publish {
     ) -> PyResult<&'py PyAny> {
         pyo3_asyncio::tokio::future_into_py(py, async { Ok(()) })
     }
+
+    /// The bytes that the publish expects to write under each path, as a list
+    /// of `(path, bytes)` tuples. Defaults to no estimates.
+    fn estimate_space<'py>(
+        &self,
+        py: Python<'py>,
+        #[allow(unused_variables)] context: ContextView,
+    ) -> PyResult<&'py PyAny> {
+        pyo3_asyncio::tokio::future_into_py(py, async { Ok(Vec::<(String, u64)>::new()) })
+    }
 }
@@ -2,16 +2,30 @@ use pyo3::{exceptions::PyTypeError, intern, prelude::*};
 
 pub(crate) struct PublishWrapper {
     inner: PyObject,
+    name: String,
 }
 
 impl PublishWrapper {
     pub(crate) fn new(inner: PyObject) -> Self {
-        Self { inner }
+        let name = Python::with_gil(|py| {
+            inner
+                .as_ref(py)
+                .get_type()
+                .name()
+                .map(|name| name.to_string())
+                .unwrap_or_default()
+        });
+
+        Self { inner, name }
     }
 }
 
 #[async_trait::async_trait]
 impl publish::Publish for PublishWrapper {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
     async fn pre_publish<'a>(
         &self,
         context: &'a publish::Context,
@@ -199,4 +213,34 @@ impl publish::Publish for PublishWrapper {
 
         Ok(())
     }
+
+    async fn estimate_space(
+        &self,
+        context: &publish::Context,
+    ) -> Result<Vec<publish::SpaceEstimate>, publish::Error> {
+        let context_view = crate::Context::from(context.clone()).to_view();
+
+        let result = Python::with_gil(|py| {
+            self.inner.call_method1(
+                py,
+                intern!(py, "estimate_space"),
+                (context_view.into_py(py),),
+            )
+        })
+        .map_err(|err| publish::Error::new_publish(err.to_string(), Some(Box::new(err))))?;
+
+        let result = Python::with_gil(|py| pyo3_asyncio::tokio::into_future(result.as_ref(py)))
+            .map_err(|err| publish::Error::new_publish(err.to_string(), Some(Box::new(err))))?
+            .await
+            .map_err(|err| publish::Error::new_publish(err.to_string(), Some(Box::new(err))))?;
+
+        Python::with_gil(|py| result.extract::<Vec<(std::path::PathBuf, u64)>>(py))
+            .map(|estimates| {
+                estimates
+                    .into_iter()
+                    .map(|(path, bytes)| publish::SpaceEstimate::new(path, bytes))
+                    .collect()
+            })
+            .map_err(|err| publish::Error::new_publish(err.to_string(), Some(Box::new(err))))
+    }
 }
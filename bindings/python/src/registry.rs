@@ -0,0 +1,178 @@
+use pyo3::{
+    exceptions::{PyKeyError, PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub(crate) struct SchemaField {
+    inner: publish::SchemaField,
+}
+
+#[pymethods]
+impl SchemaField {
+    #[new]
+    #[pyo3(signature = (key, value_type = None, required = true))]
+    fn new(key: &str, value_type: Option<&str>, required: bool) -> PyResult<Self> {
+        let value_type = match value_type {
+            Some(value_type) => Some(
+                value_type
+                    .parse::<publish::ValueType>()
+                    .map_err(|err| PyValueError::new_err(err.to_string()))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            inner: publish::SchemaField::new(key, value_type, required),
+        })
+    }
+
+    #[getter]
+    fn key(&self) -> &str {
+        &self.inner.key
+    }
+
+    #[getter]
+    fn value_type(&self) -> Option<String> {
+        self.inner
+            .value_type
+            .map(|value_type| value_type.to_string())
+    }
+
+    #[getter]
+    fn required(&self) -> bool {
+        self.inner.required
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub(crate) struct PublishMetadata {
+    inner: publish::PublishMetadata,
+}
+
+#[pymethods]
+impl PublishMetadata {
+    #[getter]
+    fn description(&self) -> &str {
+        &self.inner.description
+    }
+
+    #[getter]
+    fn version(&self) -> &str {
+        &self.inner.version
+    }
+
+    #[getter]
+    fn schema(&self) -> Vec<SchemaField> {
+        self.inner
+            .schema
+            .fields
+            .iter()
+            .map(|field| SchemaField {
+                inner: field.clone(),
+            })
+            .collect()
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Default)]
+pub(crate) struct Registry {
+    inner: publish::Registry,
+}
+
+#[pymethods]
+impl Registry {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    #[pyo3(signature = (name, factory, description = "", version = "", schema = None))]
+    fn register(
+        &mut self,
+        name: &str,
+        factory: PyObject,
+        description: &str,
+        version: &str,
+        schema: Option<Vec<SchemaField>>,
+    ) -> PyResult<()> {
+        let schema = publish::ContextSchema::new(
+            schema
+                .unwrap_or_default()
+                .into_iter()
+                .map(|field| field.inner),
+        );
+        let metadata = publish::PublishMetadata::new(description, version, schema);
+
+        self.inner
+            .register_factory(
+                name,
+                metadata,
+                Box::new(move || {
+                    let publish = Python::with_gil(|py| factory.call0(py)).map_err(|err| {
+                        publish::Error::new_publish(err.to_string(), Some(Box::new(err)))
+                    })?;
+
+                    Ok(
+                        Box::new(crate::publish_wrapper::PublishWrapper::new(publish))
+                            as Box<publish::DynPublish>,
+                    )
+                }),
+            )
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    fn unregister(&mut self, name: &str) -> bool {
+        self.inner.unregister(name)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.inner.names().map(|name| name.to_string()).collect()
+    }
+
+    fn metadata(&self, name: &str) -> PyResult<PublishMetadata> {
+        match self.inner.metadata(name) {
+            Some(metadata) => Ok(PublishMetadata {
+                inner: metadata.clone(),
+            }),
+            None => Err(PyKeyError::new_err(name.to_string())),
+        }
+    }
+
+    #[pyo3(signature = (name, context = None))]
+    fn run<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        context: Option<crate::Context>,
+    ) -> PyResult<&'py PyAny> {
+        let context: publish::Context = context.map(Into::into).unwrap_or_default();
+
+        self.inner
+            .validate(name, &context)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        let publish = self
+            .inner
+            .create(name)
+            .map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+
+        pyo3_asyncio::tokio::future_into_py::<_, crate::Context>(py, async move {
+            let context = publish::run_with_context(publish.as_ref(), context)
+                .await
+                .map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+
+            Ok(context.into())
+        })
+    }
+
+    fn __contains__(&self, name: &str) -> bool {
+        self.inner.contains(name)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+}
@@ -0,0 +1,67 @@
+use pyo3::{
+    exceptions::{PyKeyError, PyTypeError, PyValueError},
+    prelude::*,
+};
+
+use crate::context::{Context, ContextView};
+
+fn to_py_err(err: publish::Error) -> PyErr {
+    match err {
+        publish::Error::MissingTemplateKeys { keys, .. } => PyKeyError::new_err(keys),
+        err => PyValueError::new_err(err.to_string()),
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub(crate) struct Template {
+    inner: publish::Template,
+}
+
+#[pymethods]
+impl Template {
+    #[new]
+    fn new(source: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: publish::Template::new(source).map_err(to_py_err)?,
+        })
+    }
+
+    #[getter]
+    fn source(&self) -> &str {
+        self.inner.source()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.inner.fields().map(|field| field.key.clone()).collect()
+    }
+
+    /// Render the template from a Context or ContextView.
+    ///
+    /// Raises a KeyError with the list of all missing keys.
+    fn render(&self, context: &PyAny) -> PyResult<String> {
+        let context = if let Ok(context) = context.extract::<PyRef<Context>>() {
+            context.inner.clone()
+        } else if let Ok(view) = context.extract::<PyRef<ContextView>>() {
+            view.inner.inner.clone()
+        } else {
+            return Err(PyTypeError::new_err("expected a Context or ContextView"));
+        };
+
+        self.inner.render(&context).map_err(to_py_err)
+    }
+
+    fn parse(&self, text: &str) -> PyResult<Context> {
+        Ok(Context {
+            inner: self.inner.parse(text).map_err(to_py_err)?,
+        })
+    }
+
+    fn __str__(&self) -> &str {
+        self.inner.source()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Template({:?})", self.inner.source())
+    }
+}
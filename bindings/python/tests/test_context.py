@@ -1,6 +1,7 @@
 # ruff: noqa: D103,D100,S101
 
 import hypothesis
+import pytest
 from hypothesis import strategies
 
 import pypublish
@@ -61,3 +62,90 @@ def test_contextview_get_success(key: str, value: pypublish.Value) -> None:
     ctx_view = ctx.to_view()
 
     assert ctx_view.get(key) == value
+
+
+def test_context_map_methods_success() -> None:
+    ctx = pypublish.Context({"a": 1, "b": 2})
+
+    assert "a" in ctx
+    assert len(ctx) == 2
+    assert sorted(ctx.keys()) == ["a", "b"]
+    assert sorted(ctx.values()) == [1, 2]
+    assert sorted(ctx.items()) == [("a", 1), ("b", 2)]
+
+    assert ctx.setdefault("a", 3) == 1
+    assert ctx.setdefault("c", 3) == 3
+
+    ctx.update(pypublish.Context({"d": 4}))
+    ctx.retain(lambda key, value: value % 2 == 0)
+
+    assert sorted(ctx.keys()) == ["b", "d"]
+    assert ctx.remove("b") == 2
+    assert ctx.remove("b") is None
+    assert "b" not in ctx
+
+
+def test_context_merge_success() -> None:
+    ctx = pypublish.Context({"asset": {"name": "chair", "version": 1}})
+    other = pypublish.Context({"asset": {"version": 2}, "status": True})
+
+    kept = ctx.copy()
+    kept.merge(other, strategy="keep")
+    ctx.merge(other)
+
+    assert ctx.get("asset") == {"name": "chair", "version": 2}
+    assert ctx.get("status") is True
+    assert kept.get("asset") == {"name": "chair", "version": 1}
+
+
+def test_context_merge_failure() -> None:
+    ctx = pypublish.Context({"a": 1})
+
+    with pytest.raises(ValueError):
+        ctx.merge(pypublish.Context({"a": 2}), strategy="error")
+
+    with pytest.raises(ValueError):
+        ctx.merge(pypublish.Context(), strategy="replace")
+
+    assert ctx.get("a") == 1
+
+
+def test_context_freeze_success() -> None:
+    ctx = pypublish.Context({"version": 1})
+    ctx.freeze("version")
+    ctx.freeze_namespace("asset")
+
+    assert ctx.is_frozen("version")
+    assert ctx.is_frozen("asset.name")
+    assert not ctx.is_frozen("assets")
+    assert ctx.to_view().is_frozen("version")
+    assert ctx.copy().is_frozen("version")
+
+    with pytest.raises(ValueError):
+        ctx.merge(pypublish.Context({"version": 2}))
+
+    assert ctx.get("version") == 1
+
+
+def test_context_order_success() -> None:
+    ctx = pypublish.Context()
+
+    for key in ["b", "c", "a"]:
+        ctx.set(key, {"z": 1, "y": 2})
+
+    assert ctx.keys() == ["a", "b", "c"]
+    assert [key for key, _ in ctx.items()] == ["a", "b", "c"]
+    assert list(ctx.get("a")) == ["y", "z"]
+
+
+def test_context_secret_success() -> None:
+    secret = pypublish.Secret("hunter2")
+    ctx = pypublish.Context({"token": secret})
+
+    value = ctx.get("token")
+
+    assert isinstance(value, pypublish.Secret)
+    assert value == secret
+    assert value.expose() == "hunter2"
+    assert "hunter2" not in repr(value)
+    assert "hunter2" not in str(value)
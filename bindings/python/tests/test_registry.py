@@ -0,0 +1,80 @@
+# ruff: noqa: D103,D100,S101
+
+from __future__ import annotations
+
+from typing import TYPE_CHECKING
+
+import pytest
+
+import pypublish
+
+if TYPE_CHECKING:  # pragma: no cover
+    from typing import Union
+
+pytestmark = pytest.mark.asyncio
+
+
+class ModelPublish(pypublish.Publish):
+    async def publish(
+        self, context: pypublish.ContextView
+    ) -> Union[pypublish.Context, pypublish.ContextView]:
+        ctx = context.copy()
+        ctx.set("published", True)
+
+        return ctx
+
+
+def make_registry() -> pypublish.Registry:
+    registry = pypublish.Registry()
+    registry.register(
+        "model",
+        ModelPublish,
+        description="Publish a model",
+        version="1.0.0",
+        schema=[pypublish.SchemaField("asset", "string")],
+    )
+
+    return registry
+
+
+async def test_registry_lookup_success() -> None:
+    registry = make_registry()
+
+    assert "model" in registry
+    assert "rig" not in registry
+    assert len(registry) == 1
+    assert registry.names() == ["model"]
+
+    metadata = registry.metadata("model")
+    assert metadata.description == "Publish a model"
+    assert metadata.version == "1.0.0"
+    assert [(f.key, f.value_type, f.required) for f in metadata.schema] == [
+        ("asset", "string", True)
+    ]
+
+
+async def test_registry_register_duplicate_failure() -> None:
+    registry = make_registry()
+
+    with pytest.raises(ValueError, match="already registered"):
+        registry.register("model", ModelPublish)
+
+
+async def test_registry_run_success() -> None:
+    registry = make_registry()
+    context = pypublish.Context({"asset": "chair"})
+
+    result = await registry.run("model", context)
+
+    assert result.get("published") is True
+    assert result.get("asset") == "chair"
+
+
+async def test_registry_run_invalid_context_failure() -> None:
+    registry = make_registry()
+
+    with pytest.raises(ValueError, match="missing required key"):
+        await registry.run("model")
+
+    with pytest.raises(ValueError, match="No publish named"):
+        await registry.run("rig")
@@ -451,3 +451,9 @@ async def test_run_failure_step_raised_exception(
 
     with pytest.raises(RuntimeError):
         await pypublish.run(test_publish)
+
+
+async def test_publish_estimate_space_default() -> None:
+    context = pypublish.Context()
+
+    assert await pypublish.Publish().estimate_space(context.to_view()) == []
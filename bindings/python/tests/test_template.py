@@ -0,0 +1,43 @@
+# ruff: noqa: D103,D100,S101
+
+from __future__ import annotations
+
+import pytest
+
+import pypublish
+
+
+def test_template_render_success() -> None:
+    template = pypublish.Template("{show}/{asset:lower}/v{version:03}")
+    ctx = pypublish.Context({"show": "demo", "asset": "Chair", "version": 7})
+
+    assert template.source == "{show}/{asset:lower}/v{version:03}"
+    assert template.keys() == ["show", "asset", "version"]
+    assert template.render(ctx) == "demo/chair/v007"
+    assert template.render(ctx.to_view()) == "demo/chair/v007"
+
+
+def test_template_render_missing_keys() -> None:
+    template = pypublish.Template("{show}/{asset}/{step=main}")
+
+    with pytest.raises(KeyError) as exc_info:
+        template.render(pypublish.Context())
+
+    assert exc_info.value.args[0] == ["show", "asset"]
+
+
+def test_template_new_failure() -> None:
+    with pytest.raises(ValueError):
+        pypublish.Template("{show")
+
+
+def test_template_parse_success() -> None:
+    template = pypublish.Template("{asset}/v{version:03}")
+
+    ctx = template.parse("chair/v007")
+
+    assert ctx.get("asset") == "chair"
+    assert ctx.get("version") == 7
+
+    with pytest.raises(ValueError):
+        template.parse("chair/latest")
@@ -0,0 +1,278 @@
+/// The format of an archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl std::fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tar => write!(f, "tar"),
+            Self::TarGz => write!(f, "tar.gz"),
+            Self::Zip => write!(f, "zip"),
+        }
+    }
+}
+
+impl std::str::FromStr for ArchiveFormat {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tar" => Ok(Self::Tar),
+            "tar.gz" | "tgz" => Ok(Self::TarGz),
+            "zip" => Ok(Self::Zip),
+            _ => Err(crate::Error::new_archive(format!(
+                "Unknown archive format {:?}",
+                s
+            ))),
+        }
+    }
+}
+
+impl ArchiveFormat {
+    /// The format for the extension of the path, such as `chair.tar.gz`.
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Option<Self> {
+        let name = path.as_ref().file_name()?.to_str()?;
+
+        [
+            (".tar.gz", Self::TarGz),
+            (".tgz", Self::TarGz),
+            (".tar", Self::Tar),
+            (".zip", Self::Zip),
+        ]
+        .into_iter()
+        .find(|(extension, _)| name.ends_with(extension))
+        .map(|(_, format)| format)
+    }
+}
+
+/// The modification time of every file in an archive, 1980-01-01 00:00:00
+/// UTC, which is the earliest time that zip archives can store.
+const ARCHIVE_MTIME: u64 = 315_532_800;
+
+/// A transaction that packages publishes into archives, such as for a
+/// delivery to a vendor.
+///
+/// Archives are deterministic, so packaging the same files twice gives the
+/// same bytes. Files are added sorted by path, with the same modification
+/// time, and without owners. Each archive is written to a temporary file, and
+/// then linked into place, so readers never see a partial archive. Rolling
+/// back removes the archives, and restores any that they replaced.
+///
+/// ```no_run
+/// # use publish::Transaction;
+/// # async fn example() -> Result<(), publish::Error> {
+/// let transaction = publish::ArchiveTransaction::new();
+/// transaction
+///     .package_dir(
+///         "/publish/chair/v007",
+///         "/deliveries/chair_v007.zip",
+///         publish::ArchiveFormat::Zip,
+///     )
+///     .await?;
+/// transaction.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ArchiveTransaction {
+    filesystem: crate::FilesystemTransaction,
+}
+
+impl ArchiveTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Package all of the files in the directory.
+    pub async fn package_dir<R: AsRef<std::path::Path>, A: AsRef<std::path::Path>>(
+        &self,
+        root: R,
+        archive: A,
+        format: ArchiveFormat,
+    ) -> Result<(), crate::Error> {
+        let root = root.as_ref();
+        let files = crate::util::list_files(root).await?;
+
+        self.package_files(root, files, archive, format).await
+    }
+
+    /// Package the files in the manifest, along with the manifest itself, so
+    /// that the files can be verified once they are unpacked.
+    pub async fn package_manifest<R: AsRef<std::path::Path>, A: AsRef<std::path::Path>>(
+        &self,
+        root: R,
+        manifest: &crate::Manifest,
+        archive: A,
+        format: ArchiveFormat,
+    ) -> Result<(), crate::Error> {
+        let files = manifest
+            .files
+            .iter()
+            .map(|entry| std::path::PathBuf::from(&entry.path))
+            .chain([crate::Manifest::FILE_NAME.into()]);
+
+        self.package_files(root, files, archive, format).await
+    }
+
+    /// Package the files, either as absolute paths in the root, or as paths
+    /// relative to it. The files are stored relative to the root.
+    pub async fn package_files<
+        R: AsRef<std::path::Path>,
+        I: IntoIterator<Item = P>,
+        P: Into<std::path::PathBuf>,
+        A: AsRef<std::path::Path>,
+    >(
+        &self,
+        root: R,
+        files: I,
+        archive: A,
+        format: ArchiveFormat,
+    ) -> Result<(), crate::Error> {
+        let root = root.as_ref();
+        let archive = archive.as_ref();
+        let mut entries = std::collections::BTreeMap::new();
+
+        for file in files {
+            let path = root.join(file.into());
+            entries.insert(crate::manifest::relative_path(root, &path)?, path);
+        }
+
+        if let Some(parent) = archive.parent() {
+            self.filesystem.create_dir_all(parent).await?;
+        }
+
+        let temp_path = crate::util::unique_path(archive, "tmp");
+        let result = async {
+            let blocking_path = temp_path.clone();
+
+            match tokio::task::spawn_blocking(move || {
+                write_archive(&blocking_path, entries, format)
+            })
+            .await
+            {
+                Ok(result) => result?,
+                Err(err) => return Err(crate::Error::new_runtime(err.to_string())),
+            }
+
+            self.filesystem.hard_link(&temp_path, archive).await
+        }
+        .await;
+        let _ = tokio::fs::remove_file(&temp_path).await;
+
+        result
+    }
+
+    /// The archives that were written, in the order that they were first
+    /// written.
+    pub fn written_files(&self) -> Vec<std::path::PathBuf> {
+        self.filesystem.written_files()
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Transaction for ArchiveTransaction {
+    async fn commit(&self) -> Result<(), crate::Error> {
+        self.filesystem.commit().await
+    }
+
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        self.filesystem.rollback().await
+    }
+}
+
+fn write_archive(
+    path: &std::path::Path,
+    entries: std::collections::BTreeMap<String, std::path::PathBuf>,
+    format: ArchiveFormat,
+) -> Result<(), crate::Error> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    let file = match format {
+        ArchiveFormat::Tar => write_tar(file, &entries)?,
+        ArchiveFormat::TarGz => {
+            // The gzip header has no file name or time, so it is deterministic
+            // too.
+            let encoder = flate2::GzBuilder::new()
+                .mtime(0)
+                .write(file, flate2::Compression::default());
+            write_tar(encoder, &entries)?.finish()?
+        }
+        ArchiveFormat::Zip => write_zip(file, &entries)?,
+    };
+
+    file.into_inner()
+        .map_err(|err| err.into_error())?
+        .sync_all()?;
+
+    Ok(())
+}
+
+fn write_tar<W: std::io::Write>(
+    writer: W,
+    entries: &std::collections::BTreeMap<String, std::path::PathBuf>,
+) -> Result<W, crate::Error> {
+    let mut builder = tar::Builder::new(writer);
+
+    for (name, path) in entries {
+        let file = std::fs::File::open(path)?;
+        let metadata = file.metadata()?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(metadata.len());
+        header.set_mode(mode(&metadata));
+        header.set_mtime(ARCHIVE_MTIME);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_entry_type(tar::EntryType::Regular);
+        builder.append_data(&mut header, name, file)?;
+    }
+
+    Ok(builder.into_inner()?)
+}
+
+fn write_zip<W: std::io::Write + std::io::Seek>(
+    writer: W,
+    entries: &std::collections::BTreeMap<String, std::path::PathBuf>,
+) -> Result<W, crate::Error> {
+    let mut zip = zip::ZipWriter::new(writer);
+
+    for (name, path) in entries {
+        let mut file = std::fs::File::open(path)?;
+        let options = zip::write::FileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .last_modified_time(zip::DateTime::default())
+            .unix_permissions(mode(&file.metadata()?));
+        zip.start_file(name, options).map_err(zip_error)?;
+        std::io::copy(&mut file, &mut zip)?;
+    }
+
+    zip.finish().map_err(zip_error)
+}
+
+fn zip_error(err: zip::result::ZipError) -> crate::Error {
+    match err {
+        zip::result::ZipError::Io(err) => err.into(),
+        err => crate::Error::new_archive(err.to_string()),
+    }
+}
+
+/// The permissions of the file in the archive. Only whether the file is
+/// executable is kept, so that the archive does not depend on the umask.
+#[cfg(unix)]
+fn mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    if metadata.permissions().mode() & 0o111 != 0 {
+        0o755
+    } else {
+        0o644
+    }
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &std::fs::Metadata) -> u32 {
+    0o644
+}
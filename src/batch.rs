@@ -0,0 +1,161 @@
+use futures_util::StreamExt;
+
+/// A publish, and the context it should start from.
+#[derive(Debug, Clone)]
+pub struct Job<P> {
+    pub publish: P,
+    pub context: crate::Context,
+}
+
+impl<P> Job<P> {
+    pub fn new(publish: P, context: crate::Context) -> Self {
+        Self { publish, context }
+    }
+}
+
+/// The result of running a single job in a batch.
+#[derive(Debug)]
+pub enum JobOutcome {
+    /// The publish finished, and contains the final context, including the
+    /// provenance of its keys if the job context tracks it.
+    Succeeded(crate::Context),
+    /// The publish failed, and all of the stages that ran were rolled back.
+    RolledBack(crate::Error),
+    /// The publish failed, and rolling back one of the stages also failed, or
+    /// the job panicked before it could be rolled back.
+    RollbackFailed(crate::Error),
+}
+
+impl JobOutcome {
+    fn from_result(result: Result<crate::Context, crate::Error>) -> Self {
+        match result {
+            Ok(context) => Self::Succeeded(context),
+            Err(err @ crate::Error::Rollback { .. }) => Self::RollbackFailed(err),
+            Err(err) => Self::RolledBack(err),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Succeeded(_))
+    }
+}
+
+/// The report for a single job in a batch.
+#[derive(Debug)]
+pub struct JobReport {
+    /// The position of the job in the iterator given to [`run_many`].
+    pub index: usize,
+    pub outcome: JobOutcome,
+}
+
+/// The aggregated report of a batch run.
+///
+/// The job reports are ordered by the position of the job in the iterator
+/// given to [`run_many`], not by the order that they finished in.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub jobs: Vec<JobReport>,
+}
+
+impl BatchReport {
+    /// The jobs that finished successfully.
+    pub fn succeeded(&self) -> impl Iterator<Item = (usize, &crate::Context)> {
+        self.jobs.iter().filter_map(|job| match &job.outcome {
+            JobOutcome::Succeeded(context) => Some((job.index, context)),
+            _ => None,
+        })
+    }
+
+    /// The jobs that failed, whether or not they were rolled back.
+    pub fn failed(&self) -> impl Iterator<Item = (usize, &crate::Error)> {
+        self.jobs.iter().filter_map(|job| match &job.outcome {
+            JobOutcome::RolledBack(err) | JobOutcome::RollbackFailed(err) => Some((job.index, err)),
+            _ => None,
+        })
+    }
+
+    /// The jobs that failed, and could not be rolled back.
+    pub fn rollback_failed(&self) -> impl Iterator<Item = (usize, &crate::Error)> {
+        self.jobs.iter().filter_map(|job| match &job.outcome {
+            JobOutcome::RollbackFailed(err) => Some((job.index, err)),
+            _ => None,
+        })
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.jobs.iter().all(|job| job.outcome.is_success())
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+/// Run many publishes on the current Tokio runtime, with at most
+/// `concurrency` publishes running at once.
+///
+/// See [`run_many_with_executor`].
+#[cfg(feature = "tokio")]
+pub async fn run_many<I, P>(jobs: I, concurrency: usize) -> BatchReport
+where
+    I: IntoIterator<Item = Job<P>>,
+    P: crate::Publish + Send + Sync + 'static,
+{
+    run_many_with_executor(&crate::TokioExecutor, jobs, concurrency).await
+}
+
+/// Run many publishes, with at most `concurrency` publishes running at once.
+///
+/// Each job is spawned onto the executor, and run as if by
+/// [`crate::run_with_context`], so a failed job is rolled back without
+/// affecting the other jobs. A job that panics is reported as
+/// [`JobOutcome::RollbackFailed`]. A concurrency of 0 is treated as 1.
+pub async fn run_many_with_executor<E, I, P>(
+    executor: &E,
+    jobs: I,
+    concurrency: usize,
+) -> BatchReport
+where
+    E: crate::Executor + ?Sized,
+    I: IntoIterator<Item = Job<P>>,
+    P: crate::Publish + Send + Sync + 'static,
+{
+    let mut reports = futures_util::stream::iter(jobs.into_iter().enumerate())
+        .map(|(index, job)| {
+            let result = std::sync::Arc::new(std::sync::Mutex::new(None));
+            let handle = executor.spawn(Box::pin({
+                let result = result.clone();
+
+                async move {
+                    let output = crate::run_with_context(&job.publish, job.context).await;
+                    *crate::util::lock(&result) = Some(output);
+                }
+            }));
+
+            async move {
+                handle.await;
+
+                // The job only finishes without a result if it panicked, so
+                // none of its stages were rolled back.
+                let outcome = match crate::util::lock(&result).take() {
+                    Some(result) => JobOutcome::from_result(result),
+                    None => {
+                        JobOutcome::RollbackFailed(crate::Error::new_runtime("The job panicked"))
+                    }
+                };
+
+                JobReport { index, outcome }
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    reports.sort_by_key(|report| report.index);
+
+    BatchReport { jobs: reports }
+}
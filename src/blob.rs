@@ -0,0 +1,321 @@
+/// A content-addressable store of files.
+///
+/// Each file is stored once, as an object named after its checksum, and is
+/// hard linked into the publish directories that use it. So files that are
+/// published unchanged across versions only take up space once.
+///
+/// The hard links are the reference counts. An object that is only linked
+/// from the store is no longer referenced, and can be removed. Objects are
+/// read-only, since writing to a published file would change every publish
+/// that links to it.
+///
+/// On platforms without link counts, objects are always treated as
+/// referenced, so they are never removed.
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: std::path::PathBuf,
+    algorithm: crate::ChecksumAlgorithm,
+}
+
+impl BlobStore {
+    /// Create a store under the root, which must be on the same filesystem as
+    /// the publish directories.
+    pub fn new<P: Into<std::path::PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            algorithm: crate::ChecksumAlgorithm::default(),
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: crate::ChecksumAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn root(&self) -> &std::path::Path {
+        &self.root
+    }
+
+    pub fn algorithm(&self) -> crate::ChecksumAlgorithm {
+        self.algorithm
+    }
+
+    /// The path of the object for the checksum.
+    ///
+    /// Objects are sharded by the first two characters of the checksum, so
+    /// that no directory gets too large.
+    pub fn object_path(&self, checksum: &str) -> std::path::PathBuf {
+        let shard = checksum.get(..2).unwrap_or(checksum);
+
+        self.root.join("objects").join(shard).join(checksum)
+    }
+
+    pub async fn contains(&self, checksum: &str) -> Result<bool, crate::Error> {
+        match tokio::fs::metadata(self.object_path(checksum)).await {
+            Ok(_) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The number of links to the object outside of the store, or `None` if
+    /// the object does not exist.
+    pub async fn ref_count(&self, checksum: &str) -> Result<Option<u64>, crate::Error> {
+        match tokio::fs::metadata(self.object_path(checksum)).await {
+            Ok(metadata) => Ok(Some(link_count(&metadata).saturating_sub(1))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Copy the file into the store, returning its checksum, and whether the
+    /// object was created. If the store already has the object, then the copy
+    /// is discarded.
+    async fn insert(&self, source: &std::path::Path) -> Result<(String, bool), crate::Error> {
+        let temp_dir = self.root.join("tmp");
+        tokio::fs::create_dir_all(&temp_dir).await?;
+
+        // The file is hashed after it is copied, so that a source that changes
+        // in the meantime cannot end up with the wrong checksum.
+        let temp_path = crate::util::unique_path(&temp_dir.join("object"), "tmp");
+        let result = self.insert_temp(source, &temp_path).await;
+        let _ = tokio::fs::remove_file(&temp_path).await;
+
+        result
+    }
+
+    async fn insert_temp(
+        &self,
+        source: &std::path::Path,
+        temp_path: &std::path::Path,
+    ) -> Result<(String, bool), crate::Error> {
+        tokio::fs::copy(source, temp_path).await?;
+        let checksum = self.algorithm.hash_file(temp_path).await?;
+
+        let mut permissions = tokio::fs::metadata(temp_path).await?.permissions();
+        permissions.set_readonly(true);
+        tokio::fs::set_permissions(temp_path, permissions).await?;
+
+        let path = self.object_path(&checksum);
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        match tokio::fs::hard_link(temp_path, &path).await {
+            Ok(()) => Ok((checksum, true)),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Ok((checksum, false)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Remove the object if it is no longer referenced. Returns true if the
+    /// object was removed.
+    pub async fn remove_unreferenced(&self, checksum: &str) -> Result<bool, crate::Error> {
+        let path = self.object_path(checksum);
+
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) if link_count(&metadata) <= 1 => {}
+            Ok(_) => return Ok(false),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        }
+
+        // Move the object aside before checking it again, so that a publish
+        // that links it in the meantime either sees it is missing and stores
+        // it again, or links it before the check and keeps it.
+        let removed_path = crate::util::unique_path(&path, "removed");
+
+        match tokio::fs::rename(&path, &removed_path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        }
+
+        let removed = link_count(&tokio::fs::metadata(&removed_path).await?) <= 1;
+
+        if !removed {
+            match tokio::fs::hard_link(&removed_path, &path).await {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        tokio::fs::remove_file(&removed_path).await?;
+
+        Ok(removed)
+    }
+
+    /// Remove all of the objects that are no longer referenced, returning
+    /// their checksums.
+    pub async fn remove_all_unreferenced(&self) -> Result<Vec<String>, crate::Error> {
+        let mut removed = Vec::new();
+
+        for checksum in self.checksums().await? {
+            if self.remove_unreferenced(&checksum).await? {
+                removed.push(checksum);
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// The checksums of all of the objects in the store, sorted.
+    pub async fn checksums(&self) -> Result<Vec<String>, crate::Error> {
+        let mut checksums = Vec::new();
+        let mut shards = match tokio::fs::read_dir(self.root.join("objects")).await {
+            Ok(shards) => shards,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(checksums),
+            Err(err) => return Err(err.into()),
+        };
+
+        while let Some(shard) = shards.next_entry().await? {
+            if !shard.file_type().await?.is_dir() {
+                continue;
+            }
+
+            let mut objects = tokio::fs::read_dir(shard.path()).await?;
+
+            while let Some(object) = objects.next_entry().await? {
+                // Objects that are being removed have an extension, and are
+                // skipped.
+                match object.file_name().to_str() {
+                    Some(name) if !name.contains('.') => checksums.push(name.to_string()),
+                    _ => {}
+                }
+            }
+        }
+
+        checksums.sort();
+
+        Ok(checksums)
+    }
+}
+
+/// A transaction that links files from a [`BlobStore`] into a publish
+/// directory.
+///
+/// Rolling back removes the links, and then any objects that the transaction
+/// stored that are no longer referenced. Files removed by the transaction are
+/// kept until it is committed, and then their objects are removed if nothing
+/// else references them.
+#[derive(Debug)]
+pub struct BlobTransaction {
+    store: BlobStore,
+    filesystem: crate::FilesystemTransaction,
+    created: std::sync::Mutex<Vec<String>>,
+    removed: std::sync::Mutex<Vec<String>>,
+}
+
+impl BlobTransaction {
+    pub fn new(store: BlobStore) -> Self {
+        Self {
+            store,
+            filesystem: crate::FilesystemTransaction::new(),
+            created: std::sync::Mutex::new(Vec::new()),
+            removed: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn store(&self) -> &BlobStore {
+        &self.store
+    }
+
+    /// Store the file, and link it to the destination, replacing the
+    /// destination if it already exists. Returns the checksum of the file.
+    pub async fn add<F: AsRef<std::path::Path>, T: AsRef<std::path::Path>>(
+        &self,
+        from: F,
+        to: T,
+    ) -> Result<String, crate::Error> {
+        let (checksum, created) = self.store.insert(from.as_ref()).await?;
+
+        if created {
+            crate::util::lock(&self.created).push(checksum.clone());
+        }
+
+        self.filesystem
+            .hard_link(self.store.object_path(&checksum), to)
+            .await?;
+
+        Ok(checksum)
+    }
+
+    /// Remove a file that was linked from the store. Its object is removed
+    /// when the transaction is committed, if nothing else references it.
+    pub async fn remove<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), crate::Error> {
+        let path = path.as_ref();
+        let checksum = self.store.algorithm.hash_file(path).await?;
+        self.filesystem.remove_file(path).await?;
+        crate::util::lock(&self.removed).push(checksum);
+
+        Ok(())
+    }
+
+    /// The files that were linked, in the order that they were first linked.
+    pub fn written_files(&self) -> Vec<std::path::PathBuf> {
+        self.filesystem.written_files()
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Transaction for BlobTransaction {
+    /// Commit the files, and then remove the objects of the removed files
+    /// that are no longer referenced.
+    ///
+    /// If an object cannot be removed, then it is kept along with the objects
+    /// that are not removed yet, so that the commit can be retried.
+    async fn commit(&self) -> Result<(), crate::Error> {
+        self.filesystem.commit().await?;
+        crate::util::lock(&self.created).clear();
+
+        remove_unreferenced(&self.store, &self.removed).await
+    }
+
+    /// Roll back the files, and then remove the objects that the transaction
+    /// stored that are no longer referenced.
+    ///
+    /// If an object cannot be removed, then it is kept along with the objects
+    /// that are not removed yet, so that the rollback can be retried.
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        self.filesystem.rollback().await?;
+        crate::util::lock(&self.removed).clear();
+
+        remove_unreferenced(&self.store, &self.created).await
+    }
+}
+
+/// Remove the objects that are no longer referenced, one at a time, keeping
+/// any that fail in the list.
+async fn remove_unreferenced(
+    store: &BlobStore,
+    checksums: &std::sync::Mutex<Vec<String>>,
+) -> Result<(), crate::Error> {
+    loop {
+        let checksum = match crate::util::lock(checksums).pop() {
+            Some(checksum) => checksum,
+            None => break,
+        };
+
+        if let Err(err) = store.remove_unreferenced(&checksum).await {
+            crate::util::lock(checksums).push(checksum);
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+pub(crate) fn link_count(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.nlink()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn link_count(_metadata: &std::fs::Metadata) -> u64 {
+    // Link counts are not available, so treat every object as referenced.
+    u64::MAX
+}
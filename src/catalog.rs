@@ -0,0 +1,740 @@
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS catalog_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    version INTEGER,
+    context TEXT NOT NULL,
+    user TEXT NOT NULL,
+    started INTEGER NOT NULL,
+    finished INTEGER NOT NULL,
+    committed INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS catalog_entries_name ON catalog_entries (name, version);
+CREATE INDEX IF NOT EXISTS catalog_entries_finished ON catalog_entries (finished);
+CREATE TABLE IF NOT EXISTS catalog_files (
+    entry_id INTEGER NOT NULL REFERENCES catalog_entries (id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    PRIMARY KEY (entry_id, path)
+);
+CREATE TABLE IF NOT EXISTS catalog_tags (
+    entry_id INTEGER NOT NULL REFERENCES catalog_entries (id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (entry_id, tag)
+);
+CREATE INDEX IF NOT EXISTS catalog_tags_tag ON catalog_tags (tag);
+CREATE TABLE IF NOT EXISTS catalog_inputs (
+    entry_id INTEGER NOT NULL REFERENCES catalog_entries (id) ON DELETE CASCADE,
+    input_id INTEGER NOT NULL REFERENCES catalog_entries (id) ON DELETE RESTRICT,
+    PRIMARY KEY (entry_id, input_id)
+);
+CREATE INDEX IF NOT EXISTS catalog_inputs_input ON catalog_inputs (input_id);
+";
+
+/// A publish to record in a [`Catalog`].
+#[derive(Debug, Clone)]
+pub struct CatalogRecord {
+    /// The name of the publish, such as `model`.
+    pub name: String,
+    pub version: Option<i64>,
+    /// A snapshot of the context. Secrets are not recorded.
+    pub context: crate::Context,
+    pub files: Vec<std::path::PathBuf>,
+    pub tags: Vec<String>,
+    /// The ids of the entries that this publish was built from, such as the
+    /// rig that a cache was built from.
+    pub inputs: Vec<i64>,
+    pub user: String,
+    pub started: std::time::SystemTime,
+    pub finished: std::time::SystemTime,
+}
+
+impl CatalogRecord {
+    /// Create a record for the current user, that started and finished now.
+    pub fn new<N: AsRef<str>>(name: N, context: crate::Context) -> Self {
+        let now = std::time::SystemTime::now();
+
+        Self {
+            name: name.as_ref().to_string(),
+            version: None,
+            context,
+            files: Vec::new(),
+            tags: Vec::new(),
+            inputs: Vec::new(),
+            user: crate::util::current_user(),
+            started: now,
+            finished: now,
+        }
+    }
+
+    pub fn with_version(mut self, version: i64) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn with_files<I: IntoIterator<Item = P>, P: Into<std::path::PathBuf>>(
+        mut self,
+        files: I,
+    ) -> Self {
+        self.files.extend(files.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_tags<I: IntoIterator<Item = T>, T: AsRef<str>>(mut self, tags: I) -> Self {
+        self.tags
+            .extend(tags.into_iter().map(|tag| tag.as_ref().to_string()));
+        self
+    }
+
+    pub fn with_inputs<I: IntoIterator<Item = i64>>(mut self, inputs: I) -> Self {
+        self.inputs.extend(inputs);
+        self
+    }
+
+    pub fn with_user<U: AsRef<str>>(mut self, user: U) -> Self {
+        self.user = user.as_ref().to_string();
+        self
+    }
+
+    pub fn with_started(mut self, started: std::time::SystemTime) -> Self {
+        self.started = started;
+        self
+    }
+
+    pub fn with_finished(mut self, finished: std::time::SystemTime) -> Self {
+        self.finished = finished;
+        self
+    }
+}
+
+/// A row of the entries table, with its files, tags, and inputs.
+struct Row {
+    id: i64,
+    name: String,
+    version: Option<i64>,
+    context: String,
+    user: String,
+    started: i64,
+    finished: i64,
+    files: Vec<String>,
+    tags: Vec<String>,
+    inputs: Vec<i64>,
+}
+
+/// A publish that was recorded in a [`Catalog`].
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    pub id: i64,
+    pub record: CatalogRecord,
+}
+
+/// Filters for the entries in a [`Catalog`]. An empty query matches every
+/// entry.
+#[derive(Debug, Clone, Default)]
+pub struct CatalogQuery {
+    name: Option<String>,
+    context: Vec<(String, crate::Value)>,
+    tag: Option<String>,
+    after: Option<std::time::SystemTime>,
+    before: Option<std::time::SystemTime>,
+}
+
+impl CatalogQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name<N: AsRef<str>>(mut self, name: N) -> Self {
+        self.name = Some(name.as_ref().to_string());
+        self
+    }
+
+    /// Only match entries where the context key has the value, such as the
+    /// asset that was published.
+    pub fn with_context<K: AsRef<str>>(mut self, key: K, value: crate::Value) -> Self {
+        self.context.push((key.as_ref().to_string(), value));
+        self
+    }
+
+    pub fn with_tag<T: AsRef<str>>(mut self, tag: T) -> Self {
+        self.tag = Some(tag.as_ref().to_string());
+        self
+    }
+
+    /// Only match entries that finished at or after the time.
+    pub fn with_finished_after(mut self, time: std::time::SystemTime) -> Self {
+        self.after = Some(time);
+        self
+    }
+
+    /// Only match entries that finished before the time.
+    pub fn with_finished_before(mut self, time: std::time::SystemTime) -> Self {
+        self.before = Some(time);
+        self
+    }
+
+    fn to_sql(&self) -> Result<(String, Vec<rusqlite::types::Value>), crate::Error> {
+        use rusqlite::types::Value as SqlValue;
+
+        let mut conditions = vec!["committed = 1".to_string()];
+        let mut params = Vec::new();
+
+        if let Some(name) = &self.name {
+            conditions.push("name = ?".to_string());
+            params.push(SqlValue::Text(name.clone()));
+        }
+
+        for (key, value) in &self.context {
+            let (value_type, value) = match value {
+                crate::Value::None => ("null", SqlValue::Null),
+                crate::Value::Boolean(true) => ("true", SqlValue::Integer(1)),
+                crate::Value::Boolean(false) => ("false", SqlValue::Integer(0)),
+                crate::Value::Integer(value) => ("integer", SqlValue::Integer(*value)),
+                crate::Value::Float(value) => ("real", SqlValue::Real(*value)),
+                crate::Value::String(value) => ("text", SqlValue::Text(value.clone())),
+                crate::Value::Secret(_) => {
+                    return Err(crate::Error::new_database(format!(
+                        "Cannot query secret {:?}, since secrets are not recorded",
+                        key
+                    )))
+                }
+                crate::Value::Array(_) => ("array", SqlValue::Text(to_json(value).to_string())),
+                crate::Value::Object(_) => ("object", SqlValue::Text(to_json(value).to_string())),
+            };
+
+            conditions.push(
+                "EXISTS (SELECT 1 FROM json_each(catalog_entries.context) AS item \
+                 WHERE item.key = ? AND item.type = ? AND item.value IS ?)"
+                    .to_string(),
+            );
+            params.push(SqlValue::Text(key.clone()));
+            params.push(SqlValue::Text(value_type.to_string()));
+            params.push(value);
+        }
+
+        if let Some(tag) = &self.tag {
+            conditions.push("id IN (SELECT entry_id FROM catalog_tags WHERE tag = ?)".to_string());
+            params.push(SqlValue::Text(tag.clone()));
+        }
+
+        if let Some(after) = self.after {
+            conditions.push("finished >= ?".to_string());
+            params.push(SqlValue::Integer(to_timestamp(after)));
+        }
+
+        if let Some(before) = self.before {
+            conditions.push("finished < ?".to_string());
+            params.push(SqlValue::Integer(to_timestamp(before)));
+        }
+
+        Ok((conditions.join(" AND "), params))
+    }
+}
+
+/// A catalog of publishes, backed by a local SQLite database.
+///
+/// The catalog answers questions such as "what versions of this asset exist"
+/// without walking the filesystem. Publishes are recorded with a
+/// [`CatalogTransaction`], and only become visible to queries once the
+/// transaction is committed.
+///
+/// The catalog can be cloned cheaply, and the clones share the database.
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    database: crate::SqliteDatabase,
+}
+
+impl Catalog {
+    /// Open the catalog database, creating it if it does not exist.
+    pub async fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self, crate::Error> {
+        Self::new(crate::SqliteDatabase::open(path).await?).await
+    }
+
+    /// Open a catalog that only exists in memory, which is useful for tests.
+    pub async fn open_in_memory() -> Result<Self, crate::Error> {
+        Self::new(crate::SqliteDatabase::open_in_memory().await?).await
+    }
+
+    /// Create a catalog in the database, so that it can share the database,
+    /// and any active [`crate::DatabaseTransaction`], with other tables.
+    pub async fn new(database: crate::SqliteDatabase) -> Result<Self, crate::Error> {
+        database
+            .with_connection(|connection| {
+                connection.pragma_update(None, "foreign_keys", true)?;
+                connection.execute_batch(SCHEMA)
+            })
+            .await?;
+
+        Ok(Self { database })
+    }
+
+    pub fn database(&self) -> &crate::SqliteDatabase {
+        &self.database
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<CatalogEntry>, crate::Error> {
+        let mut entries = self
+            .select("committed = 1 AND id = ?".to_string(), vec![id.into()], "")
+            .await?;
+
+        Ok(entries.pop())
+    }
+
+    /// Find the entries that match the query, sorted by name, version, and
+    /// then the order that they were recorded.
+    pub async fn find(&self, query: &CatalogQuery) -> Result<Vec<CatalogEntry>, crate::Error> {
+        let (conditions, params) = query.to_sql()?;
+
+        self.select(conditions, params, "ORDER BY name, version, id")
+            .await
+    }
+
+    /// Find the entry with the highest version that matches the query. If
+    /// versions are equal, then the most recently recorded entry wins.
+    pub async fn latest(&self, query: &CatalogQuery) -> Result<Option<CatalogEntry>, crate::Error> {
+        let (conditions, params) = query.to_sql()?;
+        let mut entries = self
+            .select(conditions, params, "ORDER BY version DESC, id DESC LIMIT 1")
+            .await?;
+
+        Ok(entries.pop())
+    }
+
+    async fn select(
+        &self,
+        conditions: String,
+        params: Vec<rusqlite::types::Value>,
+        order: &'static str,
+    ) -> Result<Vec<CatalogEntry>, crate::Error> {
+        let rows = self
+            .database
+            .with_connection(move |connection| {
+                let sql = format!(
+                    "SELECT id, name, version, context, user, started, finished \
+                     FROM catalog_entries WHERE {} {}",
+                    conditions, order
+                );
+                let mut statement = connection.prepare(&sql)?;
+                let rows = statement
+                    .query_map(rusqlite::params_from_iter(params), |row| {
+                        Ok(Row {
+                            id: row.get(0)?,
+                            name: row.get(1)?,
+                            version: row.get(2)?,
+                            context: row.get(3)?,
+                            user: row.get(4)?,
+                            started: row.get(5)?,
+                            finished: row.get(6)?,
+                            files: Vec::new(),
+                            tags: Vec::new(),
+                            inputs: Vec::new(),
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut files_statement = connection
+                    .prepare("SELECT path FROM catalog_files WHERE entry_id = ? ORDER BY rowid")?;
+                let mut tags_statement = connection
+                    .prepare("SELECT tag FROM catalog_tags WHERE entry_id = ? ORDER BY tag")?;
+                let mut inputs_statement = connection.prepare(
+                    "SELECT input_id FROM catalog_inputs WHERE entry_id = ? ORDER BY input_id",
+                )?;
+
+                rows.into_iter()
+                    .map(|mut row| {
+                        row.files = files_statement
+                            .query_map([row.id], |row| row.get(0))?
+                            .collect::<Result<Vec<_>, _>>()?;
+                        row.tags = tags_statement
+                            .query_map([row.id], |row| row.get(0))?
+                            .collect::<Result<Vec<_>, _>>()?;
+                        row.inputs = inputs_statement
+                            .query_map([row.id], |row| row.get(0))?
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        Ok(row)
+                    })
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(CatalogEntry {
+                    id: row.id,
+                    record: CatalogRecord {
+                        name: row.name,
+                        version: row.version,
+                        context: from_json_context(&row.context)?,
+                        files: row.files.into_iter().map(Into::into).collect(),
+                        tags: row.tags,
+                        inputs: row.inputs,
+                        user: row.user,
+                        started: from_timestamp(row.started),
+                        finished: from_timestamp(row.finished),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    async fn insert(&self, record: &CatalogRecord) -> Result<i64, crate::Error> {
+        let record = record.clone();
+
+        self.database
+            .with_connection(move |connection| {
+                let transaction = connection.savepoint()?;
+                transaction.execute(
+                    "INSERT INTO catalog_entries (name, version, context, user, started, finished) \
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                    rusqlite::params![
+                        record.name,
+                        record.version,
+                        to_json_context(&record.context).to_string(),
+                        record.user,
+                        to_timestamp(record.started),
+                        to_timestamp(record.finished),
+                    ],
+                )?;
+                let id = transaction.last_insert_rowid();
+
+                for path in &record.files {
+                    transaction.execute(
+                        "INSERT OR IGNORE INTO catalog_files (entry_id, path) VALUES (?, ?)",
+                        rusqlite::params![id, path.to_string_lossy()],
+                    )?;
+                }
+
+                for tag in &record.tags {
+                    transaction.execute(
+                        "INSERT OR IGNORE INTO catalog_tags (entry_id, tag) VALUES (?, ?)",
+                        rusqlite::params![id, tag],
+                    )?;
+                }
+
+                for input in &record.inputs {
+                    transaction.execute(
+                        "INSERT OR IGNORE INTO catalog_inputs (entry_id, input_id) VALUES (?, ?)",
+                        [id, *input],
+                    )?;
+                }
+
+                transaction.commit()?;
+
+                Ok(id)
+            })
+            .await
+    }
+
+    /// The entries that the entry was built from, directly or indirectly,
+    /// sorted by id.
+    pub async fn upstream(&self, id: i64) -> Result<Vec<CatalogEntry>, crate::Error> {
+        self.select(
+            "committed = 1 AND id IN (\
+             WITH RECURSIVE upstream (id) AS (\
+             SELECT input_id FROM catalog_inputs WHERE entry_id = ? \
+             UNION SELECT input_id FROM catalog_inputs \
+             JOIN upstream ON catalog_inputs.entry_id = upstream.id) \
+             SELECT id FROM upstream)"
+                .to_string(),
+            vec![id.into()],
+            "ORDER BY id",
+        )
+        .await
+    }
+
+    /// The entries that were built from the entry, directly or indirectly,
+    /// sorted by id.
+    pub async fn downstream(&self, id: i64) -> Result<Vec<CatalogEntry>, crate::Error> {
+        self.select(
+            "committed = 1 AND id IN (\
+             WITH RECURSIVE downstream (id) AS (\
+             SELECT entry_id FROM catalog_inputs WHERE input_id = ? \
+             UNION SELECT entry_id FROM catalog_inputs \
+             JOIN downstream ON catalog_inputs.input_id = downstream.id) \
+             SELECT id FROM downstream)"
+                .to_string(),
+            vec![id.into()],
+            "ORDER BY id",
+        )
+        .await
+    }
+
+    /// The ids of the entries that use the entry as an input, including ones
+    /// that are not committed yet.
+    pub async fn dependents(&self, id: i64) -> Result<Vec<i64>, crate::Error> {
+        self.database
+            .with_connection(move |connection| dependents(connection, id))
+            .await
+    }
+
+    async fn update_entries(&self, sql: &'static str, ids: Vec<i64>) -> Result<(), crate::Error> {
+        self.database
+            .with_connection(move |connection| {
+                let transaction = connection.savepoint()?;
+
+                for id in ids {
+                    transaction.execute(sql, [id])?;
+                }
+
+                transaction.commit()
+            })
+            .await
+    }
+}
+
+/// A transaction that records publishes in a [`Catalog`].
+///
+/// Records are inserted straight away, but are hidden from queries until the
+/// transaction is committed. Rolling back deletes them.
+///
+/// Entries can also be removed, which happens when the transaction is
+/// committed. An entry that is used as an input by another entry cannot be
+/// removed.
+///
+/// ```no_run
+/// # use std::borrow::Cow;
+/// struct ModelPublish {
+///     transaction: publish::CatalogTransaction,
+/// }
+///
+/// #[async_trait::async_trait]
+/// impl publish::Publish for ModelPublish {
+///     async fn publish<'a>(
+///         &self,
+///         context: &'a publish::Context,
+///     ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+///         let record = publish::CatalogRecord::new("model", context.clone()).with_version(7);
+///         self.transaction.insert(&record).await?;
+///
+///         Ok(Cow::Borrowed(context))
+///     }
+///
+///     async fn rollback_publish(&self, _context: &publish::Context) -> Result<(), publish::Error> {
+///         use publish::Transaction;
+///
+///         self.transaction.rollback().await
+///     }
+///
+///     async fn post_publish<'a>(
+///         &self,
+///         context: &'a publish::Context,
+///     ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+///         use publish::Transaction;
+///
+///         self.transaction.commit().await?;
+///
+///         Ok(Cow::Borrowed(context))
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CatalogTransaction {
+    catalog: Catalog,
+    inserted: std::sync::Mutex<Vec<i64>>,
+    removed: std::sync::Mutex<Vec<i64>>,
+}
+
+impl CatalogTransaction {
+    pub fn new(catalog: Catalog) -> Self {
+        Self {
+            catalog,
+            inserted: std::sync::Mutex::new(Vec::new()),
+            removed: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    /// Insert the record, returning the id of its entry.
+    pub async fn insert(&self, record: &CatalogRecord) -> Result<i64, crate::Error> {
+        let id = self.catalog.insert(record).await?;
+        crate::util::lock(&self.inserted).push(id);
+
+        Ok(id)
+    }
+
+    /// Remove the entry when the transaction is committed.
+    ///
+    /// Returns an [`crate::Error::InUse`] if other entries use it as an
+    /// input. This is checked again when the transaction is committed.
+    pub async fn remove(&self, id: i64) -> Result<(), crate::Error> {
+        self.remove_all(&[id]).await
+    }
+
+    /// Remove the entries when the transaction is committed, where entries
+    /// can be used as inputs by the entries before them. Returns an
+    /// [`crate::Error::InUse`] if any other entries use them as inputs.
+    pub(crate) async fn remove_all(&self, ids: &[i64]) -> Result<(), crate::Error> {
+        for (index, id) in ids.iter().enumerate() {
+            let mut dependents = self.catalog.dependents(*id).await?;
+            dependents.retain(|dependent| !ids[..index].contains(dependent));
+
+            if !dependents.is_empty() {
+                return Err(crate::Error::new_in_use(*id, dependents));
+            }
+        }
+
+        crate::util::lock(&self.removed).extend_from_slice(ids);
+
+        Ok(())
+    }
+
+    /// The ids of the entries that were inserted, and are not committed yet.
+    pub fn inserted(&self) -> Vec<i64> {
+        crate::util::lock(&self.inserted).clone()
+    }
+
+    /// The ids of the entries that will be removed when the transaction is
+    /// committed.
+    pub fn removed(&self) -> Vec<i64> {
+        crate::util::lock(&self.removed).clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Transaction for CatalogTransaction {
+    /// Mark the inserted entries as committed, and delete the removed
+    /// entries, in a single savepoint. The entries are only forgotten once the
+    /// savepoint is committed, so a failed commit can still be rolled back.
+    async fn commit(&self) -> Result<(), crate::Error> {
+        let inserted = self.inserted();
+        let removed = self.removed();
+
+        let in_use = self
+            .catalog
+            .database
+            .with_connection(move |connection| {
+                let transaction = connection.savepoint()?;
+
+                for id in inserted {
+                    transaction.execute(
+                        "UPDATE catalog_entries SET committed = 1 WHERE id = ?",
+                        [id],
+                    )?;
+                }
+
+                for id in removed {
+                    let dependents = dependents(&transaction, id)?;
+
+                    if !dependents.is_empty() {
+                        return Ok(Some((id, dependents)));
+                    }
+
+                    transaction.execute("DELETE FROM catalog_entries WHERE id = ?", [id])?;
+                }
+
+                transaction.commit()?;
+
+                Ok(None)
+            })
+            .await?;
+
+        if let Some((id, dependents)) = in_use {
+            return Err(crate::Error::new_in_use(id, dependents));
+        }
+
+        crate::util::lock(&self.inserted).clear();
+        crate::util::lock(&self.removed).clear();
+
+        Ok(())
+    }
+
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        let mut inserted = std::mem::take(&mut *crate::util::lock(&self.inserted));
+        crate::util::lock(&self.removed).clear();
+
+        // Later entries can use earlier ones as inputs, so they are deleted
+        // first.
+        inserted.reverse();
+
+        self.catalog
+            .update_entries("DELETE FROM catalog_entries WHERE id = ?", inserted)
+            .await
+    }
+}
+
+fn dependents(connection: &rusqlite::Connection, id: i64) -> rusqlite::Result<Vec<i64>> {
+    let mut statement = connection
+        .prepare("SELECT entry_id FROM catalog_inputs WHERE input_id = ? ORDER BY entry_id")?;
+    let dependents = statement
+        .query_map([id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(dependents)
+}
+
+/// Timestamps are stored as nanoseconds since the Unix epoch.
+fn to_timestamp(time: std::time::SystemTime) -> i64 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as i64)
+        .unwrap_or_default()
+}
+
+fn from_timestamp(timestamp: i64) -> std::time::SystemTime {
+    std::time::UNIX_EPOCH + std::time::Duration::from_nanos(timestamp.max(0) as u64)
+}
+
+fn to_json_context(context: &crate::Context) -> serde_json::Value {
+    serde_json::Value::Object(
+        context
+            .iter()
+            .filter(|(_, value)| !matches!(value, crate::Value::Secret(_)))
+            .map(|(key, value)| (key.clone(), to_json(value)))
+            .collect(),
+    )
+}
+
+fn to_json(value: &crate::Value) -> serde_json::Value {
+    match value {
+        crate::Value::None | crate::Value::Secret(_) => serde_json::Value::Null,
+        crate::Value::Boolean(value) => (*value).into(),
+        crate::Value::Integer(value) => (*value).into(),
+        crate::Value::Float(value) => (*value).into(),
+        crate::Value::String(value) => value.as_str().into(),
+        crate::Value::Array(values) => values.iter().map(to_json).collect(),
+        crate::Value::Object(values) => serde_json::Value::Object(
+            values
+                .iter()
+                .filter(|(_, value)| !matches!(value, crate::Value::Secret(_)))
+                .map(|(key, value)| (key.clone(), to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn from_json_context(json: &str) -> Result<crate::Context, crate::Error> {
+    match serde_json::from_str(json) {
+        Ok(serde_json::Value::Object(values)) => Ok(crate::Context::new(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, from_json(value))),
+        )),
+        Ok(_) => Err(crate::Error::new_database("The context is not an object")),
+        Err(err) => Err(crate::Error::new_database(format!(
+            "Invalid context: {}",
+            err
+        ))),
+    }
+}
+
+fn from_json(value: serde_json::Value) -> crate::Value {
+    match value {
+        serde_json::Value::Null => crate::Value::None,
+        serde_json::Value::Bool(value) => crate::Value::Boolean(value),
+        serde_json::Value::Number(value) => match value.as_i64() {
+            Some(value) => crate::Value::Integer(value),
+            None => crate::Value::Float(value.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(value) => crate::Value::String(value),
+        serde_json::Value::Array(values) => {
+            crate::Value::Array(values.into_iter().map(from_json).collect())
+        }
+        serde_json::Value::Object(values) => crate::Value::Object(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, from_json(value)))
+                .collect(),
+        ),
+    }
+}
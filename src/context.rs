@@ -1,3 +1,9 @@
+/// A value in a [`Context`].
+///
+/// Arrays and objects are persistent collections, so cloning a value shares
+/// its data instead of copying it, and modifying a clone only copies the parts
+/// that changed. Objects are sorted by key, so they always iterate in the same
+/// order.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     None,
@@ -5,8 +11,76 @@ pub enum Value {
     Integer(i64),
     Float(f64),
     String(String),
-    Array(Vec<Value>),
-    Object(std::collections::HashMap<String, Value>),
+    /// A sensitive string, which is redacted when the value is printed.
+    Secret(crate::Secret),
+    Array(im::Vector<Value>),
+    Object(im::OrdMap<String, Value>),
+}
+
+impl Value {
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::None => ValueType::None,
+            Self::Boolean(_) => ValueType::Boolean,
+            Self::Integer(_) => ValueType::Integer,
+            Self::Float(_) => ValueType::Float,
+            Self::String(_) => ValueType::String,
+            Self::Secret(_) => ValueType::Secret,
+            Self::Array(_) => ValueType::Array,
+            Self::Object(_) => ValueType::Object,
+        }
+    }
+}
+
+/// The type of a [`Value`], without the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    None,
+    Boolean,
+    Integer,
+    Float,
+    String,
+    Secret,
+    Array,
+    Object,
+}
+
+impl std::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::None => "none",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::String => "string",
+            Self::Secret => "secret",
+            Self::Array => "array",
+            Self::Object => "object",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for ValueType {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "boolean" => Ok(Self::Boolean),
+            "integer" => Ok(Self::Integer),
+            "float" => Ok(Self::Float),
+            "string" => Ok(Self::String),
+            "secret" => Ok(Self::Secret),
+            "array" => Ok(Self::Array),
+            "object" => Ok(Self::Object),
+            _ => Err(crate::Error::new_runtime(format!(
+                "{:?} is not a value type",
+                s
+            ))),
+        }
+    }
 }
 
 impl From<bool> for Value {
@@ -33,32 +107,346 @@ impl From<String> for Value {
     }
 }
 
+impl From<crate::Secret> for Value {
+    fn from(value: crate::Secret) -> Self {
+        Self::Secret(value)
+    }
+}
+
+/// How [`Context::merge`] resolves a key that has a different value in both
+/// contexts.
+///
+/// Objects are merged key by key, so only values that are not both objects
+/// can conflict. Equal values are never a conflict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MergeStrategy {
+    /// Use the value from the other context.
+    #[default]
+    Overwrite,
+    /// Keep the existing value.
+    Keep,
+    /// Fail the merge, and leave the context unchanged.
+    Error,
+}
+
+impl std::fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Overwrite => "overwrite",
+            Self::Keep => "keep",
+            Self::Error => "error",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for MergeStrategy {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "overwrite" => Ok(Self::Overwrite),
+            "keep" => Ok(Self::Keep),
+            "error" => Ok(Self::Error),
+            _ => Err(crate::Error::new_runtime(format!(
+                "{:?} is not a merge strategy",
+                s
+            ))),
+        }
+    }
+}
+
+/// The data that is passed between the stages of a publish.
+///
+/// The context is a persistent map, and each value is shared behind an `Arc`.
+/// Cloning a context is cheap, and a stage that changes a few keys only copies
+/// those keys, while the rest of the data is shared with its input context.
+///
+/// Keys are sorted, so iterating over a context is deterministic.
+///
+/// A context can also track the provenance of each key, which is the stage and
+/// publish that last wrote it. See [`Context::with_provenance`].
+///
+/// Keys can be frozen with [`Context::freeze`], after which the runner fails
+/// any stage that changes them.
 #[derive(Debug, Clone, Default)]
 pub struct Context {
-    data: std::collections::HashMap<String, Value>,
+    data: im::OrdMap<String, std::sync::Arc<Value>>,
+    provenance: Option<im::OrdMap<String, crate::Provenance>>,
+    frozen_keys: im::OrdSet<String>,
+    frozen_namespaces: im::OrdSet<String>,
 }
 
 impl Context {
     pub fn new<T: std::iter::IntoIterator<Item = (String, Value)>>(context: T) -> Self {
         Self {
-            data: context.into_iter().collect(),
+            data: context
+                .into_iter()
+                .map(|(key, value)| (key, std::sync::Arc::new(value)))
+                .collect(),
+            provenance: None,
+            frozen_keys: im::OrdSet::new(),
+            frozen_namespaces: im::OrdSet::new(),
+        }
+    }
+
+    /// Freeze the key, so that later stages cannot change it.
+    ///
+    /// The key does not need to exist yet, in which case later stages cannot
+    /// add it. Freezing cannot be undone.
+    pub fn freeze(&mut self, key: &str) {
+        self.frozen_keys.insert(key.to_string());
+    }
+
+    /// Freeze the namespace, which is the key itself and every key that starts
+    /// with the namespace followed by a `.`, such as `asset` and `asset.name`.
+    pub fn freeze_namespace(&mut self, namespace: &str) {
+        self.frozen_namespaces.insert(namespace.to_string());
+    }
+
+    pub fn is_frozen(&self, key: &str) -> bool {
+        self.frozen_keys.contains(key)
+            || self.frozen_namespaces.iter().any(|namespace| {
+                key.strip_prefix(namespace.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+    }
+
+    /// Set the key, failing if the key is frozen.
+    pub fn try_set(&mut self, key: &str, value: Value) -> Result<(), crate::Error> {
+        if self.is_frozen(key) {
+            return Err(crate::Error::new_frozen_key(key));
         }
+
+        self.set(key, value);
+
+        Ok(())
+    }
+
+    /// Check that none of the frozen keys of the previous context were changed,
+    /// and carry the frozen keys over to this context.
+    pub(crate) fn enforce_frozen(&mut self, previous: &Context) -> Result<(), crate::Error> {
+        if previous.frozen_keys.is_empty() && previous.frozen_namespaces.is_empty() {
+            return Ok(());
+        }
+
+        for item in previous.data.diff(&self.data) {
+            let key = match item {
+                im::ordmap::DiffItem::Add(key, _)
+                | im::ordmap::DiffItem::Update { new: (key, _), .. }
+                | im::ordmap::DiffItem::Remove(key, _) => key,
+            };
+
+            if previous.is_frozen(key) {
+                return Err(crate::Error::new_frozen_key(key));
+            }
+        }
+
+        self.frozen_keys = self.frozen_keys.clone().union(previous.frozen_keys.clone());
+        self.frozen_namespaces = self
+            .frozen_namespaces
+            .clone()
+            .union(previous.frozen_namespaces.clone());
+
+        Ok(())
+    }
+
+    /// Track which stage and publish last wrote each key.
+    ///
+    /// The runner records the provenance of every key that a stage adds or
+    /// changes. Keys that were in the context before tracking was enabled have
+    /// no provenance.
+    pub fn with_provenance(mut self) -> Self {
+        self.provenance.get_or_insert_with(im::OrdMap::new);
+        self
+    }
+
+    pub fn is_tracking_provenance(&self) -> bool {
+        self.provenance.is_some()
+    }
+
+    /// Get the provenance of the key, if it is tracked and was written by a
+    /// stage.
+    pub fn provenance(&self, key: &str) -> Option<&crate::Provenance> {
+        self.provenance
+            .as_ref()
+            .and_then(|provenance| provenance.get(key))
+    }
+
+    /// Iterate over the provenance of every key that has one, in key order.
+    pub fn provenance_iter(&self) -> impl Iterator<Item = (&String, &crate::Provenance)> {
+        self.provenance
+            .iter()
+            .flat_map(|provenance| provenance.iter())
+    }
+
+    /// Record the provenance of the keys that changed since the previous
+    /// context.
+    ///
+    /// If the previous context tracks provenance, then the tracking is carried
+    /// over, even if the stage built this context from scratch.
+    pub(crate) fn record_provenance(
+        &mut self,
+        previous: &Context,
+        stage: crate::Stage,
+        publish: &str,
+    ) {
+        let mut provenance = match (self.provenance.take(), &previous.provenance) {
+            (Some(provenance), _) => provenance,
+            (None, Some(provenance)) => provenance.clone(),
+            (None, None) => return,
+        };
+        let timestamp = std::time::SystemTime::now();
+
+        for item in previous.data.diff(&self.data) {
+            match item {
+                im::ordmap::DiffItem::Add(key, _)
+                | im::ordmap::DiffItem::Update { new: (key, _), .. } => {
+                    provenance.insert(
+                        key.clone(),
+                        crate::Provenance::new(stage, publish, timestamp),
+                    );
+                }
+                im::ordmap::DiffItem::Remove(key, _) => {
+                    provenance.remove(key);
+                }
+            }
+        }
+
+        self.provenance = Some(provenance);
     }
 
     pub fn get(&self, key: &str) -> Option<&Value> {
-        self.data.get(key)
+        self.data.get(key).map(|value| value.as_ref())
+    }
+
+    /// Get a mutable reference to the value for the key.
+    ///
+    /// If the value is shared with another context, then it is copied first.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.data.get_mut(key).map(std::sync::Arc::make_mut)
     }
 
     pub fn set(&mut self, key: &str, value: Value) {
-        self.data.insert(key.to_string(), value);
+        self.data
+            .insert(key.to_string(), std::sync::Arc::new(value));
+    }
+
+    /// Remove the key, returning its value if it was in the context.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        if let Some(provenance) = &mut self.provenance {
+            provenance.remove(key);
+        }
+
+        self.data.remove(key).map(unwrap_value)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.data.contains_key(key)
+    }
+
+    /// Get the entry for the key, for in-place updates.
+    pub fn entry(&mut self, key: &str) -> ContextEntry<'_> {
+        ContextEntry {
+            inner: self.data.entry(key.to_string()),
+        }
+    }
+
+    /// Keep only the keys that the predicate returns true for.
+    pub fn retain<F: FnMut(&str, &Value) -> bool>(&mut self, mut f: F) {
+        let removed = self
+            .data
+            .iter()
+            .filter(|(key, value)| !f(key, value))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        for key in removed {
+            self.remove(&key);
+        }
     }
 
-    pub fn iter(&self) -> ContextIter {
+    /// Merge the other context into this one.
+    ///
+    /// Objects that exist in both contexts are merged recursively, and any other
+    /// key that exists in both contexts with different values is resolved with
+    /// the strategy. With [`MergeStrategy::Error`], the first conflict is
+    /// returned as an [`crate::Error::InvalidContext`], and the context is not
+    /// modified.
+    ///
+    /// Merging a different value into a frozen key is an
+    /// [`crate::Error::FrozenKey`]. With [`MergeStrategy::Keep`], only adding a
+    /// missing frozen key, or a missing child of a frozen object, is an error.
+    pub fn merge(&mut self, other: Context, strategy: MergeStrategy) -> Result<(), crate::Error> {
+        let frozen = other.data.iter().find(|(key, value)| {
+            self.is_frozen(key)
+                && match strategy {
+                    MergeStrategy::Keep => adds_keys(self.get(key), value),
+                    _ => self.get(key) != Some(value.as_ref()),
+                }
+        });
+
+        if let Some((key, _)) = frozen {
+            return Err(crate::Error::new_frozen_key(key));
+        }
+
+        if strategy == MergeStrategy::Error {
+            let conflict = other
+                .data
+                .iter()
+                .find_map(|(key, value)| find_conflict(key, self.get(key), value));
+
+            if let Some(key) = conflict {
+                return Err(crate::Error::new_invalid_context(format!(
+                    "conflicting values for key {:?}",
+                    key
+                )));
+            }
+        }
+
+        for (key, value) in other.data {
+            match self.data.get_mut(&key) {
+                Some(existing) if std::sync::Arc::ptr_eq(existing, &value) => {}
+                Some(existing) => match (existing.as_ref(), value.as_ref()) {
+                    (Value::Object(_), Value::Object(_)) => merge_value(
+                        std::sync::Arc::make_mut(existing),
+                        unwrap_value(value),
+                        strategy,
+                    ),
+                    _ => {
+                        if strategy != MergeStrategy::Keep {
+                            *existing = value;
+                        }
+                    }
+                },
+                None => {
+                    self.data.insert(key, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn iter(&self) -> ContextIter<'_> {
         ContextIter {
             data: self.data.iter(),
         }
     }
 
+    pub fn keys(&self) -> ContextKeys<'_> {
+        ContextKeys {
+            data: self.data.keys(),
+        }
+    }
+
+    pub fn values(&self) -> ContextValues<'_> {
+        ContextValues {
+            data: self.data.values(),
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.data.len()
     }
@@ -68,23 +456,159 @@ impl Context {
     }
 }
 
+/// Take the value out of the `Arc`, copying it only if it is still shared.
+fn unwrap_value(value: std::sync::Arc<Value>) -> Value {
+    std::sync::Arc::try_unwrap(value).unwrap_or_else(|value| value.as_ref().clone())
+}
+
+/// Find the dotted path of the first key that would conflict in a merge.
+fn find_conflict(key: &str, existing: Option<&Value>, incoming: &Value) -> Option<String> {
+    match (existing, incoming) {
+        (Some(Value::Object(existing)), Value::Object(incoming)) => incoming
+            .iter()
+            .find_map(|(child, value)| find_conflict(child, existing.get(child), value))
+            .map(|path| format!("{}.{}", key, path)),
+        (Some(existing), incoming) if existing != incoming => Some(key.to_string()),
+        _ => None,
+    }
+}
+
+/// Whether merging the incoming value with [`MergeStrategy::Keep`] would add a
+/// key or child that does not exist yet.
+fn adds_keys(existing: Option<&Value>, incoming: &Value) -> bool {
+    match (existing, incoming) {
+        (None, _) => true,
+        (Some(Value::Object(existing)), Value::Object(incoming)) => incoming
+            .iter()
+            .any(|(key, value)| adds_keys(existing.get(key), value)),
+        _ => false,
+    }
+}
+
+fn merge_value(existing: &mut Value, incoming: Value, strategy: MergeStrategy) {
+    match (existing, incoming) {
+        (Value::Object(existing), Value::Object(incoming)) => {
+            for (key, value) in incoming {
+                match existing.get_mut(&key) {
+                    Some(existing) => merge_value(existing, value, strategy),
+                    None => {
+                        existing.insert(key, value);
+                    }
+                }
+            }
+        }
+        (existing, incoming) => {
+            if strategy != MergeStrategy::Keep {
+                *existing = incoming;
+            }
+        }
+    }
+}
+
+impl Extend<(String, Value)> for Context {
+    fn extend<T: IntoIterator<Item = (String, Value)>>(&mut self, iter: T) {
+        self.data.extend(
+            iter.into_iter()
+                .map(|(key, value)| (key, std::sync::Arc::new(value))),
+        )
+    }
+}
+
+impl FromIterator<(String, Value)> for Context {
+    fn from_iter<T: IntoIterator<Item = (String, Value)>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
 impl IntoIterator for Context {
     type Item = (String, Value);
-    type IntoIter = std::collections::hash_map::IntoIter<String, Value>;
+    type IntoIter = ContextIntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.data.into_iter()
+        ContextIntoIter {
+            data: self.data.into_iter(),
+        }
+    }
+}
+
+/// A view into a single key of a [`Context`], returned by [`Context::entry`].
+pub struct ContextEntry<'a> {
+    inner: im::ordmap::Entry<'a, String, std::sync::Arc<Value>>,
+}
+
+impl<'a> ContextEntry<'a> {
+    pub fn key(&self) -> &str {
+        self.inner.key()
+    }
+
+    /// Insert the value if the key is missing, and return the value for the
+    /// key.
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        self.or_insert_with(|| default)
+    }
+
+    /// Insert the result of the function if the key is missing, and return the
+    /// value for the key.
+    pub fn or_insert_with<F: FnOnce() -> Value>(self, default: F) -> &'a mut Value {
+        std::sync::Arc::make_mut(self.inner.or_insert_with(|| std::sync::Arc::new(default())))
+    }
+
+    /// Modify the value if the key exists.
+    pub fn and_modify<F: FnOnce(&mut Value)>(self, f: F) -> Self {
+        Self {
+            inner: self
+                .inner
+                .and_modify(|value| f(std::sync::Arc::make_mut(value))),
+        }
     }
 }
 
 pub struct ContextIter<'a> {
-    data: std::collections::hash_map::Iter<'a, String, Value>,
+    data: im::ordmap::Iter<'a, String, std::sync::Arc<Value>>,
 }
 
 impl<'a> Iterator for ContextIter<'a> {
     type Item = (&'a String, &'a Value);
 
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().map(|(key, value)| (key, value.as_ref()))
+    }
+}
+
+pub struct ContextIntoIter {
+    data: im::ordmap::ConsumingIter<(String, std::sync::Arc<Value>)>,
+}
+
+impl Iterator for ContextIntoIter {
+    type Item = (String, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data
+            .next()
+            .map(|(key, value)| (key, unwrap_value(value)))
+    }
+}
+
+pub struct ContextKeys<'a> {
+    data: im::ordmap::Keys<'a, String, std::sync::Arc<Value>>,
+}
+
+impl<'a> Iterator for ContextKeys<'a> {
+    type Item = &'a String;
+
     fn next(&mut self) -> Option<Self::Item> {
         self.data.next()
     }
 }
+
+pub struct ContextValues<'a> {
+    data: im::ordmap::Values<'a, String, std::sync::Arc<Value>>,
+}
+
+impl<'a> Iterator for ContextValues<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().map(|value| value.as_ref())
+    }
+}
@@ -0,0 +1,255 @@
+/// A database connection that can begin, commit, and roll back a transaction.
+///
+/// Implement this for a database client to use it with a
+/// [`DatabaseTransaction`].
+#[async_trait::async_trait]
+pub trait DatabaseBackend: Send + Sync {
+    async fn begin(&self) -> Result<(), crate::Error>;
+    async fn commit(&self) -> Result<(), crate::Error>;
+    async fn rollback(&self) -> Result<(), crate::Error>;
+}
+
+#[async_trait::async_trait]
+impl<T> DatabaseBackend for std::sync::Arc<T>
+where
+    T: DatabaseBackend + ?Sized,
+{
+    async fn begin(&self) -> Result<(), crate::Error> {
+        (**self).begin().await
+    }
+
+    async fn commit(&self) -> Result<(), crate::Error> {
+        (**self).commit().await
+    }
+
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        (**self).rollback().await
+    }
+}
+
+/// A database transaction that spans the stages of a publish.
+///
+/// The transaction is begun explicitly, and then committed or rolled back
+/// through the [`crate::Transaction`] trait. Rolling back a transaction that
+/// is not active does nothing, so it is safe to roll back from every rollback
+/// stage. [`DatabasePublish`] does this for any publish.
+#[derive(Debug)]
+pub struct DatabaseTransaction<B> {
+    backend: B,
+    active: std::sync::atomic::AtomicBool,
+}
+
+impl<B: DatabaseBackend> DatabaseTransaction<B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            active: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Begin the transaction. Returns an error if it is already active.
+    pub async fn begin(&self) -> Result<(), crate::Error> {
+        if self.active.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            return Err(crate::Error::new_database(
+                "The transaction has already begun",
+            ));
+        }
+
+        if let Err(err) = self.backend.begin().await {
+            self.active
+                .store(false, std::sync::atomic::Ordering::SeqCst);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<B: DatabaseBackend> crate::Transaction for DatabaseTransaction<B> {
+    /// Commit the transaction. Returns an error if it is not active.
+    ///
+    /// If the commit fails, then the transaction is still active, so that it
+    /// can be rolled back.
+    async fn commit(&self) -> Result<(), crate::Error> {
+        if !self.is_active() {
+            return Err(crate::Error::new_database("The transaction has not begun"));
+        }
+
+        self.backend.commit().await?;
+        self.active
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        if !self.active.swap(false, std::sync::atomic::Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        self.backend.rollback().await
+    }
+}
+
+/// Wraps a publish in a database transaction.
+///
+/// The transaction begins before the pre-publish stage, and is committed after
+/// the post-publish stage. If any stage fails, then the transaction is rolled
+/// back after the stages of the publish are.
+///
+/// The publish shares the transaction, so that it can write to the database
+/// while it is active. A transaction can only be used by one run at a time.
+/// Overlapping runs of the same publish wait for the active run to commit or
+/// roll back, and running another publish with the same transaction while it
+/// is active fails in the pre-publish stage, without rolling back the active
+/// transaction.
+///
+/// The frozen keys are checked before the transaction is committed, so that a
+/// post-publish stage that changes a frozen key leaves nothing committed.
+///
+/// ```no_run
+/// # use std::{borrow::Cow, sync::Arc};
+/// # struct ModelPublish;
+/// # #[async_trait::async_trait]
+/// # impl publish::Publish for ModelPublish {
+/// #     async fn publish<'a>(
+/// #         &self,
+/// #         context: &'a publish::Context,
+/// #     ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+/// #         Ok(Cow::Borrowed(context))
+/// #     }
+/// # }
+/// # async fn example(
+/// #     transaction: Arc<publish::DatabaseTransaction<Arc<dyn publish::DatabaseBackend>>>,
+/// # ) -> Result<(), publish::Error> {
+/// let publish = publish::DatabasePublish::new(transaction, ModelPublish);
+/// publish::run(&publish).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DatabasePublish<P, B> {
+    transaction: std::sync::Arc<DatabaseTransaction<B>>,
+    publish: P,
+    /// Locked by the pre-publish stage until the transaction is committed or
+    /// rolled back, so that only one run of this publish uses the transaction
+    /// at a time. The value is whether the run began the transaction, so that
+    /// it is only rolled back by the run that began it.
+    run: std::sync::Arc<futures_util::lock::Mutex<bool>>,
+    guard: std::sync::Mutex<Option<futures_util::lock::OwnedMutexGuard<bool>>>,
+}
+
+impl<P, B> DatabasePublish<P, B> {
+    pub fn new(transaction: std::sync::Arc<DatabaseTransaction<B>>, publish: P) -> Self {
+        Self {
+            transaction,
+            publish,
+            run: std::sync::Arc::new(futures_util::lock::Mutex::new(false)),
+            guard: std::sync::Mutex::new(None),
+        }
+    }
+
+    pub fn transaction(&self) -> &std::sync::Arc<DatabaseTransaction<B>> {
+        &self.transaction
+    }
+
+    pub fn publish(&self) -> &P {
+        &self.publish
+    }
+}
+
+#[async_trait::async_trait]
+impl<P, B> crate::Publish for DatabasePublish<P, B>
+where
+    P: crate::Publish + Send + Sync,
+    B: DatabaseBackend,
+{
+    fn name(&self) -> &str {
+        self.publish.name()
+    }
+
+    async fn pre_publish<'a>(
+        &self,
+        context: &'a crate::Context,
+    ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+        let mut began = self.run.clone().lock_owned().await;
+        let result = self.transaction.begin().await;
+        *began = result.is_ok();
+        *crate::util::lock(&self.guard) = Some(began);
+
+        result?;
+        self.publish.pre_publish(context).await
+    }
+
+    async fn rollback_pre_publish(&self, context: &crate::Context) -> Result<(), crate::Error> {
+        use crate::Transaction;
+
+        // The pre-publish stage is rolled back for any failure, so this is
+        // where the transaction is rolled back.
+        let result = self.publish.rollback_pre_publish(context).await;
+        let began = crate::util::lock(&self.guard).take();
+
+        if began.as_deref() == Some(&true) {
+            self.transaction.rollback().await?;
+        }
+
+        result
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a crate::Context,
+    ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+        self.publish.publish(context).await
+    }
+
+    async fn rollback_publish(&self, context: &crate::Context) -> Result<(), crate::Error> {
+        self.publish.rollback_publish(context).await
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a crate::Context,
+    ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+        use crate::Transaction;
+
+        let mut output = self.publish.post_publish(context).await?;
+
+        // The runner checks the frozen keys after this stage, but by then the
+        // transaction can no longer be rolled back.
+        if let std::borrow::Cow::Owned(output) = &mut output {
+            output.enforce_frozen(context)?;
+        }
+
+        let began = crate::util::lock(&self.guard).take();
+
+        if let Err(err) = self.transaction.commit().await {
+            // Keep the run locked, so that rolling back the pre-publish stage
+            // rolls back the transaction.
+            *crate::util::lock(&self.guard) = began;
+            return Err(err);
+        }
+
+        Ok(output)
+    }
+
+    async fn rollback_post_publish(&self, context: &crate::Context) -> Result<(), crate::Error> {
+        self.publish.rollback_post_publish(context).await
+    }
+
+    async fn estimate_space(
+        &self,
+        context: &crate::Context,
+    ) -> Result<Vec<crate::SpaceEstimate>, crate::Error> {
+        self.publish.estimate_space(context).await
+    }
+}
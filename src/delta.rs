@@ -0,0 +1,237 @@
+/// A transaction that publishes a new version by copying only the files that
+/// changed since the previous version.
+///
+/// Each file is hashed and compared against the previous version's manifest.
+/// Files that are unchanged are hard linked from the previous version instead
+/// of being copied, and are recorded as carried over in the new manifest.
+/// Hard links need both versions to be on the same filesystem, so files that
+/// cannot be linked are copied instead.
+///
+/// ```no_run
+/// # use publish::Transaction;
+/// # async fn example() -> Result<(), publish::Error> {
+/// let transaction = publish::DeltaTransaction::new("/publish/chair/v007")
+///     .load_previous("/publish/chair/v006")
+///     .await?;
+/// transaction.copy("/work/chair.abc", "geo/chair.abc").await?;
+/// transaction.save_manifest().await?;
+/// transaction.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DeltaTransaction {
+    root: std::path::PathBuf,
+    previous: Option<(std::path::PathBuf, crate::Manifest)>,
+    filesystem: crate::FilesystemTransaction,
+    carried_over: std::sync::Mutex<std::collections::BTreeMap<String, crate::ManifestEntry>>,
+}
+
+impl DeltaTransaction {
+    /// Create a transaction that publishes into the root. Without a previous
+    /// version, every file is copied.
+    pub fn new<P: Into<std::path::PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            previous: None,
+            filesystem: crate::FilesystemTransaction::new(),
+            carried_over: std::sync::Mutex::new(std::collections::BTreeMap::new()),
+        }
+    }
+
+    /// Compare files against the previous version, which was published into
+    /// the directory with the manifest.
+    pub fn with_previous<P: Into<std::path::PathBuf>>(
+        mut self,
+        root: P,
+        manifest: crate::Manifest,
+    ) -> Self {
+        self.previous = Some((root.into(), manifest));
+        self
+    }
+
+    /// Compare files against the previous version, loading its manifest from
+    /// the directory.
+    pub async fn load_previous<P: Into<std::path::PathBuf>>(
+        self,
+        root: P,
+    ) -> Result<Self, crate::Error> {
+        let root = root.into();
+        let manifest = crate::Manifest::load(&root).await?;
+
+        Ok(self.with_previous(root, manifest))
+    }
+
+    pub fn root(&self) -> &std::path::Path {
+        &self.root
+    }
+
+    /// Copy the file to the destination, either as an absolute path in the
+    /// root, or as a path relative to it. Returns true if the file was
+    /// unchanged, and was linked from the previous version.
+    pub async fn copy<F: AsRef<std::path::Path>, T: AsRef<std::path::Path>>(
+        &self,
+        from: F,
+        to: T,
+    ) -> Result<bool, crate::Error> {
+        let from = from.as_ref();
+        let to = self.root.join(to);
+        let relative = crate::manifest::relative_path(&self.root, &to)?;
+        crate::util::lock(&self.carried_over).remove(&relative);
+
+        if let Some((previous_root, entry)) = self.unchanged_entry(from, &relative).await? {
+            match self
+                .filesystem
+                .hard_link(previous_root.join(&entry.path), &to)
+                .await
+            {
+                Ok(()) => {
+                    let carried_over = crate::ManifestEntry {
+                        carried_over: Some(previous_root.to_string_lossy().into_owned()),
+                        ..entry
+                    };
+                    crate::util::lock(&self.carried_over).insert(relative, carried_over);
+
+                    return Ok(true);
+                }
+                // The versions are on different filesystems, the filesystem
+                // does not support hard links, the previous file cannot be
+                // linked to, such as when it is immutable, or it has too many
+                // links already, so fall back to copying.
+                Err(crate::Error::IO(err))
+                    if matches!(
+                        err.kind(),
+                        std::io::ErrorKind::CrossesDevices
+                            | std::io::ErrorKind::Unsupported
+                            | std::io::ErrorKind::PermissionDenied
+                            | std::io::ErrorKind::TooManyLinks
+                    ) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        self.filesystem.copy(from, &to).await?;
+
+        Ok(false)
+    }
+
+    /// The previous version's entry for the path, if the file matches it, and
+    /// the previous file still matches its entry.
+    async fn unchanged_entry(
+        &self,
+        from: &std::path::Path,
+        relative: &str,
+    ) -> Result<Option<(&std::path::Path, crate::ManifestEntry)>, crate::Error> {
+        let (previous_root, manifest) = match &self.previous {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        let entry = match manifest.get(relative) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        // Compare the sizes first, so that files that obviously changed are
+        // not hashed twice.
+        if tokio::fs::metadata(from).await?.len() != entry.size {
+            return Ok(None);
+        }
+
+        if manifest.algorithm.hash_file(from).await? != entry.checksum {
+            return Ok(None);
+        }
+
+        // The previous file could have been changed or removed since it was
+        // published, in which case it cannot be reused.
+        let previous_path = previous_root.join(&entry.path);
+
+        match tokio::fs::metadata(&previous_path).await {
+            Ok(metadata) if metadata.len() == entry.size => {}
+            Ok(_) => return Ok(None),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+
+        if manifest.algorithm.hash_file(&previous_path).await? != entry.checksum {
+            return Ok(None);
+        }
+
+        Ok(Some((previous_root, entry.clone())))
+    }
+
+    /// The files that were copied or linked, in the order that they were
+    /// first written.
+    pub fn written_files(&self) -> Vec<std::path::PathBuf> {
+        self.filesystem.written_files()
+    }
+
+    /// The paths, relative to the root, of the files that were linked from
+    /// the previous version, sorted.
+    pub fn carried_over_files(&self) -> Vec<String> {
+        crate::util::lock(&self.carried_over)
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Build the manifest for the new version.
+    ///
+    /// The entries of files that were carried over are taken from the
+    /// previous manifest, so only the files that changed are hashed again.
+    /// The manifest uses the previous manifest's algorithm, so that the
+    /// checksums of the versions can be compared.
+    pub async fn build_manifest(&self) -> Result<crate::Manifest, crate::Error> {
+        let algorithm = self
+            .previous
+            .as_ref()
+            .map(|(_, manifest)| manifest.algorithm)
+            .unwrap_or_default();
+        let carried_over = crate::util::lock(&self.carried_over).clone();
+        let written = self
+            .written_files()
+            .into_iter()
+            .filter(|path| {
+                !crate::manifest::relative_path(&self.root, path)
+                    .is_ok_and(|relative| carried_over.contains_key(&relative))
+            })
+            .collect::<Vec<_>>();
+        let mut manifest = crate::ManifestBuilder::new(&self.root)
+            .with_algorithm(algorithm)
+            .add_files(written)
+            .build()
+            .await?;
+
+        manifest.files.extend(carried_over.into_values());
+        manifest
+            .files
+            .sort_by(|left, right| left.path.cmp(&right.path));
+
+        Ok(manifest)
+    }
+
+    /// Build the manifest, and write it into the root through the
+    /// transaction.
+    pub async fn save_manifest(&self) -> Result<std::path::PathBuf, crate::Error> {
+        self.build_manifest()
+            .await?
+            .save(&self.filesystem, &self.root)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Transaction for DeltaTransaction {
+    async fn commit(&self) -> Result<(), crate::Error> {
+        self.filesystem.commit().await?;
+        crate::util::lock(&self.carried_over).clear();
+
+        Ok(())
+    }
+
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        self.filesystem.rollback().await?;
+        crate::util::lock(&self.carried_over).clear();
+
+        Ok(())
+    }
+}
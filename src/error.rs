@@ -15,6 +15,36 @@ pub enum Error {
     },
     #[error("Runtime error: {0}")]
     Runtime(String),
+    #[error("Invalid context: {0}")]
+    InvalidContext(String),
+    #[error("Registry error: {0}")]
+    Registry(String),
+    #[error("Plugin error: {0}")]
+    Plugin(String),
+    #[error("Cannot write to frozen key {0:?}")]
+    FrozenKey(String),
+    #[error("Template error: {0}")]
+    Template(String),
+    #[error("Missing keys {keys:?} for template {template:?}")]
+    MissingTemplateKeys { template: String, keys: Vec<String> },
+    #[error("Lock error: {0}")]
+    Lock(String),
+    #[error("Manifest error: {0}")]
+    Manifest(String),
+    #[error("Pointer error: {0}")]
+    Pointer(String),
+    #[error("Replication error: {0}")]
+    Replication(String),
+    #[error("Archive error: {0}")]
+    Archive(String),
+    #[error("Database error: {0}")]
+    Database(String),
+    #[error("Catalog entry {id} is used by entries {dependents:?}")]
+    InUse { id: i64, dependents: Vec<i64> },
+    #[error("Not enough space: {}", format_shortfalls(.0))]
+    InsufficientSpace(Vec<crate::SpaceShortfall>),
+    #[error("Timed out after {0:?}")]
+    Timeout(std::time::Duration),
     #[error(transparent)]
     IO(#[from] std::io::Error),
 }
@@ -46,7 +76,78 @@ impl Error {
         Self::Runtime(message.as_ref().to_string())
     }
 
+    pub fn new_invalid_context<T: AsRef<str>>(message: T) -> Self {
+        Self::InvalidContext(message.as_ref().to_string())
+    }
+
+    pub fn new_registry<T: AsRef<str>>(message: T) -> Self {
+        Self::Registry(message.as_ref().to_string())
+    }
+
+    pub fn new_plugin<T: AsRef<str>>(message: T) -> Self {
+        Self::Plugin(message.as_ref().to_string())
+    }
+
+    pub fn new_frozen_key<T: AsRef<str>>(key: T) -> Self {
+        Self::FrozenKey(key.as_ref().to_string())
+    }
+
+    pub fn new_template<T: AsRef<str>>(message: T) -> Self {
+        Self::Template(message.as_ref().to_string())
+    }
+
+    pub fn new_missing_template_keys<T: AsRef<str>>(template: T, keys: Vec<String>) -> Self {
+        Self::MissingTemplateKeys {
+            template: template.as_ref().to_string(),
+            keys,
+        }
+    }
+
+    pub fn new_lock<T: AsRef<str>>(message: T) -> Self {
+        Self::Lock(message.as_ref().to_string())
+    }
+
+    pub fn new_manifest<T: AsRef<str>>(message: T) -> Self {
+        Self::Manifest(message.as_ref().to_string())
+    }
+
+    pub fn new_pointer<T: AsRef<str>>(message: T) -> Self {
+        Self::Pointer(message.as_ref().to_string())
+    }
+
+    pub fn new_replication<T: AsRef<str>>(message: T) -> Self {
+        Self::Replication(message.as_ref().to_string())
+    }
+
+    pub fn new_archive<T: AsRef<str>>(message: T) -> Self {
+        Self::Archive(message.as_ref().to_string())
+    }
+
+    pub fn new_database<T: AsRef<str>>(message: T) -> Self {
+        Self::Database(message.as_ref().to_string())
+    }
+
+    pub fn new_in_use(id: i64, dependents: Vec<i64>) -> Self {
+        Self::InUse { id, dependents }
+    }
+
+    pub fn new_insufficient_space(shortfalls: Vec<crate::SpaceShortfall>) -> Self {
+        Self::InsufficientSpace(shortfalls)
+    }
+
+    pub fn new_timeout(duration: std::time::Duration) -> Self {
+        Self::Timeout(duration)
+    }
+
     pub fn new_io(err: std::io::Error) -> Self {
         Self::IO(err)
     }
 }
+
+fn format_shortfalls(shortfalls: &[crate::SpaceShortfall]) -> String {
+    shortfalls
+        .iter()
+        .map(|shortfall| shortfall.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
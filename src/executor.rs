@@ -0,0 +1,83 @@
+use std::{future::Future, pin::Pin, time::Duration};
+
+/// A boxed future that can be passed to, or returned from, an executor.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The Executor interface.
+///
+/// The runner and publishes are plain async code, so they can be driven by
+/// any async runtime. Helpers that need to spawn tasks or wait for a period of
+/// time go through an executor instead of depending on a specific runtime. The
+/// `tokio` feature provides [`TokioExecutor`], and other runtimes such as
+/// async-std or smol can be supported by implementing this trait.
+pub trait Executor: Send + Sync {
+    /// Spawn a future onto the executor.
+    ///
+    /// The returned future resolves once the spawned future has completed.
+    fn spawn(&self, future: BoxFuture<'static, ()>) -> BoxFuture<'static, ()>;
+
+    /// Create a future that resolves after the duration has passed.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+impl<E: Executor + ?Sized> Executor for &E {
+    fn spawn(&self, future: BoxFuture<'static, ()>) -> BoxFuture<'static, ()> {
+        (**self).spawn(future)
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        (**self).sleep(duration)
+    }
+}
+
+impl<E: Executor + ?Sized> Executor for std::sync::Arc<E> {
+    fn spawn(&self, future: BoxFuture<'static, ()>) -> BoxFuture<'static, ()> {
+        (**self).spawn(future)
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        (**self).sleep(duration)
+    }
+}
+
+/// Run a future, failing with a timeout error if it does not finish in time.
+///
+/// If the timeout is reached, then the future is dropped.
+pub async fn timeout<E, F>(
+    executor: &E,
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, crate::Error>
+where
+    E: Executor + ?Sized,
+    F: Future,
+{
+    let future = std::pin::pin!(future);
+
+    match futures_util::future::select(future, executor.sleep(duration)).await {
+        futures_util::future::Either::Left((output, _)) => Ok(output),
+        futures_util::future::Either::Right(_) => Err(crate::Error::new_timeout(duration)),
+    }
+}
+
+/// An executor backed by the current Tokio runtime.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioExecutor;
+
+#[cfg(feature = "tokio")]
+impl Executor for TokioExecutor {
+    fn spawn(&self, future: BoxFuture<'static, ()>) -> BoxFuture<'static, ()> {
+        let handle = tokio::spawn(future);
+
+        Box::pin(async move {
+            // A panic in the spawned task is not recoverable by the caller, so
+            // the join result is only used to wait for completion.
+            let _ = handle.await;
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
@@ -0,0 +1,215 @@
+#[derive(Debug)]
+enum Undo {
+    RemoveFile(std::path::PathBuf),
+    RemoveDir(std::path::PathBuf),
+    Restore {
+        path: std::path::PathBuf,
+        backup: std::path::PathBuf,
+    },
+}
+
+/// A transaction for filesystem changes.
+///
+/// Each change is made straight away, and recorded so that it can be undone.
+/// Files that are replaced are moved aside instead of being overwritten, and
+/// are only deleted when the transaction is committed.
+///
+/// The transaction keeps track of the files that it wrote, so that they can
+/// be checksummed, replicated, etc. in a later stage.
+#[derive(Debug, Default)]
+pub struct FilesystemTransaction {
+    log: std::sync::Mutex<Vec<Undo>>,
+    written: std::sync::Mutex<Vec<std::path::PathBuf>>,
+}
+
+impl FilesystemTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a directory and all of its missing parents.
+    pub async fn create_dir_all<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), crate::Error> {
+        let mut missing = Vec::new();
+
+        for ancestor in path.as_ref().ancestors() {
+            if ancestor.as_os_str().is_empty() || tokio::fs::metadata(ancestor).await.is_ok() {
+                break;
+            }
+
+            missing.push(ancestor.to_path_buf());
+        }
+
+        for dir in missing.into_iter().rev() {
+            match tokio::fs::create_dir(&dir).await {
+                Ok(()) => self.push_undo(Undo::RemoveDir(dir)),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the contents to a file, replacing it if it already exists.
+    pub async fn write<P: AsRef<std::path::Path>, C: AsRef<[u8]>>(
+        &self,
+        path: P,
+        contents: C,
+    ) -> Result<(), crate::Error> {
+        let path = path.as_ref();
+        self.prepare(path).await?;
+        tokio::fs::write(path, contents).await?;
+        self.push_written(path);
+
+        Ok(())
+    }
+
+    /// Copy a file, replacing the destination if it already exists.
+    pub async fn copy<F: AsRef<std::path::Path>, T: AsRef<std::path::Path>>(
+        &self,
+        from: F,
+        to: T,
+    ) -> Result<(), crate::Error> {
+        let to = to.as_ref();
+        self.prepare(to).await?;
+        tokio::fs::copy(from, to).await?;
+        self.push_written(to);
+
+        Ok(())
+    }
+
+    /// Hard link a file, replacing the destination if it already exists.
+    pub async fn hard_link<F: AsRef<std::path::Path>, T: AsRef<std::path::Path>>(
+        &self,
+        from: F,
+        to: T,
+    ) -> Result<(), crate::Error> {
+        let to = to.as_ref();
+        self.prepare(to).await?;
+        tokio::fs::hard_link(from, to).await?;
+        self.push_written(to);
+
+        Ok(())
+    }
+
+    /// Remove a file. The file is moved aside until the transaction is
+    /// committed, so that it can be restored.
+    pub async fn remove_file<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), crate::Error> {
+        let path = path.as_ref();
+        let backup = crate::util::unique_path(path, "bak");
+        tokio::fs::rename(path, &backup).await?;
+        self.push_undo(Undo::Restore {
+            path: path.to_path_buf(),
+            backup,
+        });
+        crate::util::lock(&self.written).retain(|other| other != path);
+
+        Ok(())
+    }
+
+    /// The files that were written, copied, or linked, in the order that they
+    /// were first written.
+    pub fn written_files(&self) -> Vec<std::path::PathBuf> {
+        crate::util::lock(&self.written).clone()
+    }
+
+    /// Create the parent directory of the path, and move any existing file
+    /// aside so that it can be restored.
+    async fn prepare(&self, path: &std::path::Path) -> Result<(), crate::Error> {
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent).await?;
+        }
+
+        match tokio::fs::symlink_metadata(path).await {
+            Ok(_) => {
+                let backup = crate::util::unique_path(path, "bak");
+                tokio::fs::rename(path, &backup).await?;
+                self.push_undo(Undo::Restore {
+                    path: path.to_path_buf(),
+                    backup,
+                });
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                self.push_undo(Undo::RemoveFile(path.to_path_buf()))
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(())
+    }
+
+    fn push_undo(&self, undo: Undo) {
+        crate::util::lock(&self.log).push(undo);
+    }
+
+    fn push_written(&self, path: &std::path::Path) {
+        let mut written = crate::util::lock(&self.written);
+
+        if !written.iter().any(|other| other == path) {
+            written.push(path.to_path_buf());
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Transaction for FilesystemTransaction {
+    async fn commit(&self) -> Result<(), crate::Error> {
+        let log = std::mem::take(&mut *crate::util::lock(&self.log));
+
+        for undo in log {
+            if let Undo::Restore { backup, .. } = undo {
+                remove_if_exists(&backup).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Undo the changes, newest first.
+    ///
+    /// If a change cannot be undone, then it is kept along with the older
+    /// changes, so that the rollback can be retried once the problem is fixed.
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        loop {
+            let undo = match crate::util::lock(&self.log).pop() {
+                Some(undo) => undo,
+                None => break,
+            };
+
+            if let Err(err) = apply_undo(&undo).await {
+                crate::util::lock(&self.log).push(undo);
+                return Err(err);
+            }
+        }
+
+        crate::util::lock(&self.written).clear();
+
+        Ok(())
+    }
+}
+
+async fn apply_undo(undo: &Undo) -> Result<(), crate::Error> {
+    match undo {
+        Undo::RemoveFile(path) => remove_if_exists(path).await,
+        Undo::RemoveDir(path) => match tokio::fs::remove_dir(path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        },
+        Undo::Restore { path, backup } => Ok(tokio::fs::rename(backup, path).await?),
+    }
+}
+
+async fn remove_if_exists(path: &std::path::Path) -> Result<(), crate::Error> {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
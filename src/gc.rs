@@ -0,0 +1,492 @@
+/// Rules for which catalog entries to keep when collecting garbage.
+///
+/// An entry is kept if any rule keeps it, and the entries that a kept entry
+/// was built from are always kept. The default policy keeps everything.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    keep_last: Option<usize>,
+    group_by: Vec<String>,
+    keep_tags: Vec<String>,
+    min_age: Option<std::time::Duration>,
+    max_age: Option<std::time::Duration>,
+}
+
+impl RetentionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the last versions of each publish name, and of the context keys
+    /// from [`RetentionPolicy::with_group_by`].
+    pub fn with_keep_last(mut self, count: usize) -> Self {
+        self.keep_last = Some(count);
+        self
+    }
+
+    /// Group entries by the context keys, as well as the publish name, when
+    /// keeping the last versions. For example, `asset` keeps the last
+    /// versions of each asset.
+    pub fn with_group_by<I: IntoIterator<Item = K>, K: AsRef<str>>(mut self, keys: I) -> Self {
+        self.group_by
+            .extend(keys.into_iter().map(|key| key.as_ref().to_string()));
+        self
+    }
+
+    /// Keep entries that have any of the tags, such as `approved`.
+    pub fn with_keep_tags<I: IntoIterator<Item = T>, T: AsRef<str>>(mut self, tags: I) -> Self {
+        self.keep_tags
+            .extend(tags.into_iter().map(|tag| tag.as_ref().to_string()));
+        self
+    }
+
+    /// Keep entries that finished less than the duration ago.
+    pub fn with_min_age(mut self, age: std::time::Duration) -> Self {
+        self.min_age = Some(age);
+        self
+    }
+
+    /// Stop keeping entries that finished more than the duration ago, even if
+    /// they are one of the last versions. Tagged entries, and entries that are
+    /// used by kept entries, are still kept.
+    pub fn with_max_age(mut self, age: std::time::Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    fn keeps_by_count(&self, rank: usize, age: std::time::Duration) -> bool {
+        let over_count = self.keep_last.is_some_and(|count| rank >= count);
+        let over_age = self.max_age.is_some_and(|max_age| age > max_age);
+
+        !over_count && !over_age
+    }
+
+    fn keeps_by_tag(&self, entry: &crate::CatalogEntry) -> bool {
+        entry
+            .record
+            .tags
+            .iter()
+            .any(|tag| self.keep_tags.contains(tag))
+    }
+
+    fn keeps_by_age(&self, age: std::time::Duration) -> bool {
+        self.min_age.is_some_and(|min_age| age < min_age)
+    }
+}
+
+/// A catalog entry to delete, and the size of its files.
+#[derive(Debug, Clone)]
+pub struct PlannedDeletion {
+    pub entry: crate::CatalogEntry,
+    /// The space that deleting the files would free. A file that is hard
+    /// linked elsewhere, such as from a [`crate::BlobStore`], only frees space
+    /// once its last link is removed, so it is counted by the deletion that
+    /// removes its last link, if any.
+    pub bytes: u64,
+}
+
+/// The catalog entries that a [`GarbageCollector`] would delete.
+#[derive(Debug, Clone, Default)]
+pub struct DeletionPlan {
+    /// The deletions, with entries deleted before the entries that they were
+    /// built from.
+    pub deletions: Vec<PlannedDeletion>,
+}
+
+impl DeletionPlan {
+    pub fn is_empty(&self) -> bool {
+        self.deletions.is_empty()
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.deletions.iter().map(|deletion| deletion.bytes).sum()
+    }
+}
+
+/// What a [`GarbageCollector`] deleted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectionReport {
+    /// Whether this was a dry run, where nothing was deleted.
+    pub dry_run: bool,
+    /// The ids of the deleted catalog entries.
+    pub entries: Vec<i64>,
+    /// The deleted files.
+    pub files: Vec<std::path::PathBuf>,
+    /// The checksums of the deleted blob store objects.
+    pub blobs: Vec<String>,
+    /// The space that was freed. For a dry run, this is the size of the files
+    /// that would be deleted.
+    pub reclaimed_bytes: u64,
+}
+
+/// Deletes old publishes, according to a [`RetentionPolicy`].
+///
+/// The collector plans which catalog entries to delete, and then deletes the
+/// entries and their files together. If any deletion fails, then everything
+/// is rolled back, so a publish is never left half deleted.
+///
+/// ```no_run
+/// # async fn example(catalog: publish::Catalog) -> Result<(), publish::Error> {
+/// let policy = publish::RetentionPolicy::new()
+///     .with_keep_last(3)
+///     .with_group_by(["asset"])
+///     .with_keep_tags(["approved"]);
+/// let collector = publish::GarbageCollector::new(catalog, policy)
+///     .with_root("/publish")
+///     .with_dry_run(true);
+///
+/// let report = collector.collect().await?;
+/// println!("Would free {} bytes", report.reclaimed_bytes);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GarbageCollector {
+    catalog: crate::Catalog,
+    policy: RetentionPolicy,
+    query: crate::CatalogQuery,
+    root: Option<std::path::PathBuf>,
+    blob_store: Option<crate::BlobStore>,
+    dry_run: bool,
+}
+
+impl GarbageCollector {
+    pub fn new(catalog: crate::Catalog, policy: RetentionPolicy) -> Self {
+        Self {
+            catalog,
+            policy,
+            query: crate::CatalogQuery::new(),
+            root: None,
+            blob_store: None,
+            dry_run: false,
+        }
+    }
+
+    /// Only collect the entries that match the query. Other entries are
+    /// always kept.
+    pub fn with_query(mut self, query: crate::CatalogQuery) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Remove directories that are left empty under the root, such as the
+    /// version directories. The root itself is never removed.
+    pub fn with_root<P: Into<std::path::PathBuf>>(mut self, root: P) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Remove the objects in the store that are no longer referenced, after
+    /// the files that link to them are deleted.
+    pub fn with_blob_store(mut self, blob_store: crate::BlobStore) -> Self {
+        self.blob_store = Some(blob_store);
+        self
+    }
+
+    /// Plan the deletions, but do not delete anything.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Plan which entries to delete.
+    pub async fn plan(&self) -> Result<DeletionPlan, crate::Error> {
+        let now = std::time::SystemTime::now();
+        let entries = self.catalog.find(&self.query).await?;
+        let mut groups = std::collections::BTreeMap::<String, Vec<&crate::CatalogEntry>>::new();
+
+        for entry in &entries {
+            groups.entry(self.group_key(entry)).or_default().push(entry);
+        }
+
+        let mut candidates = std::collections::BTreeSet::new();
+
+        for group in groups.values_mut() {
+            group.sort_by_key(|entry| std::cmp::Reverse((entry.record.version, entry.id)));
+
+            for (rank, entry) in group.iter().enumerate() {
+                let age = now
+                    .duration_since(entry.record.finished)
+                    .unwrap_or_default();
+                let kept = self.policy.keeps_by_count(rank, age)
+                    || self.policy.keeps_by_tag(entry)
+                    || self.policy.keeps_by_age(age);
+
+                if !kept {
+                    candidates.insert(entry.id);
+                }
+            }
+        }
+
+        // Keep the entries that are used by an entry that is kept, until
+        // nothing changes. Checking the dependents, instead of the inputs of
+        // the kept entries, also covers entries outside of the query, and
+        // publishes that are not committed yet.
+        loop {
+            let mut used = Vec::new();
+
+            for id in &candidates {
+                let dependents = self.catalog.dependents(*id).await?;
+
+                if dependents
+                    .iter()
+                    .any(|dependent| !candidates.contains(dependent))
+                {
+                    used.push(*id);
+                }
+            }
+
+            if used.is_empty() {
+                break;
+            }
+
+            for id in used {
+                candidates.remove(&id);
+            }
+        }
+
+        let mut deletions = entries
+            .iter()
+            .filter(|entry| candidates.contains(&entry.id))
+            .map(|entry| PlannedDeletion {
+                entry: entry.clone(),
+                bytes: 0,
+            })
+            .collect::<Vec<_>>();
+
+        // Entries are always inserted after their inputs, so deleting in
+        // reverse order of id deletes dependents first.
+        deletions.sort_by_key(|deletion| std::cmp::Reverse(deletion.entry.id));
+
+        let mut reclaimed = Reclaimed::default();
+
+        for deletion in &mut deletions {
+            deletion.bytes = reclaimed.files_size(&deletion.entry.record.files).await?;
+        }
+
+        Ok(DeletionPlan { deletions })
+    }
+
+    /// Plan the deletions, and then execute them.
+    pub async fn collect(&self) -> Result<CollectionReport, crate::Error> {
+        let plan = self.plan().await?;
+
+        self.execute(&plan).await
+    }
+
+    /// Delete the entries in the plan, and their files.
+    ///
+    /// The deletions are made in a transaction, so if any of them fail, then
+    /// all of them are rolled back. For a dry run, nothing is deleted, and
+    /// the report lists what would have been.
+    pub async fn execute(&self, plan: &DeletionPlan) -> Result<CollectionReport, crate::Error> {
+        use crate::Transaction;
+
+        if self.dry_run {
+            return Ok(CollectionReport {
+                dry_run: true,
+                entries: plan
+                    .deletions
+                    .iter()
+                    .map(|deletion| deletion.entry.id)
+                    .collect(),
+                files: plan
+                    .deletions
+                    .iter()
+                    .flat_map(|deletion| deletion.entry.record.files.iter().cloned())
+                    .collect(),
+                blobs: Vec::new(),
+                reclaimed_bytes: plan.bytes(),
+            });
+        }
+
+        let catalog_transaction = crate::CatalogTransaction::new(self.catalog.clone());
+        let filesystem_transaction = crate::FilesystemTransaction::new();
+        let mut report = CollectionReport::default();
+
+        let result = async {
+            let dirs = self
+                .delete(
+                    plan,
+                    &catalog_transaction,
+                    &filesystem_transaction,
+                    &mut report,
+                )
+                .await?;
+            filesystem_transaction.commit().await?;
+
+            Ok(dirs)
+        }
+        .await;
+
+        let dirs = match result {
+            Ok(dirs) => dirs,
+            Err(err) => {
+                // Nothing is committed yet, so the files that are not removed
+                // are restored, and the catalog is left as it was.
+                if let Err(rollback_err) = filesystem_transaction.rollback().await {
+                    return Err(crate::Error::new_rollback(
+                        "Error while rolling back garbage collection",
+                        Box::new(err),
+                        Some(Box::new(rollback_err)),
+                    ));
+                }
+
+                return Err(err);
+            }
+        };
+
+        // The files are committed first, so that a failure leaves entries in
+        // the catalog without their files, which collecting again removes,
+        // rather than files that no entry refers to.
+        catalog_transaction.commit().await?;
+
+        // The removed files are kept beside them until the transaction is
+        // committed, so the directories are only empty now. Directories sort
+        // after their parents, so reversing removes the children first.
+        for dir in dirs.into_iter().rev() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            if entries.next_entry().await?.is_none() {
+                tokio::fs::remove_dir(&dir).await?;
+            }
+        }
+
+        if let Some(blob_store) = &self.blob_store {
+            for checksum in blob_store.checksums().await? {
+                if blob_store.ref_count(&checksum).await? != Some(0) {
+                    continue;
+                }
+
+                let bytes = file_size(&blob_store.object_path(&checksum)).await?;
+
+                if blob_store.remove_unreferenced(&checksum).await? {
+                    report.reclaimed_bytes += bytes;
+                    report.blobs.push(checksum);
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Remove the entries and their files, returning the directories that
+    /// could be left empty.
+    async fn delete(
+        &self,
+        plan: &DeletionPlan,
+        catalog_transaction: &crate::CatalogTransaction,
+        filesystem_transaction: &crate::FilesystemTransaction,
+        report: &mut CollectionReport,
+    ) -> Result<std::collections::BTreeSet<std::path::PathBuf>, crate::Error> {
+        let mut dirs = std::collections::BTreeSet::new();
+        let mut reclaimed = Reclaimed::default();
+        let ids = plan
+            .deletions
+            .iter()
+            .map(|deletion| deletion.entry.id)
+            .collect::<Vec<_>>();
+        catalog_transaction.remove_all(&ids).await?;
+        report.entries = ids;
+
+        for deletion in &plan.deletions {
+            for path in &deletion.entry.record.files {
+                let metadata = match tokio::fs::symlink_metadata(path).await {
+                    Ok(metadata) => metadata,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err.into()),
+                };
+
+                filesystem_transaction.remove_file(path).await?;
+                report.files.push(path.clone());
+
+                report.reclaimed_bytes += reclaimed.add(path, &metadata);
+
+                if let Some(root) = &self.root {
+                    dirs.extend(
+                        path.ancestors()
+                            .skip(1)
+                            .take_while(|dir| dir.starts_with(root) && *dir != root)
+                            .map(std::path::Path::to_path_buf),
+                    );
+                }
+            }
+        }
+
+        Ok(dirs)
+    }
+
+    fn group_key(&self, entry: &crate::CatalogEntry) -> String {
+        let mut key = format!("{:?}", entry.record.name);
+
+        for context_key in &self.policy.group_by {
+            key.push_str(&format!("\0{:?}", entry.record.context.get(context_key)));
+        }
+
+        key
+    }
+}
+
+/// The space that removing a set of files frees. A file that is hard linked
+/// more than once only frees space once all of its links are removed.
+#[derive(Debug, Default)]
+struct Reclaimed {
+    paths: std::collections::HashSet<std::path::PathBuf>,
+    /// The number of links of each file that have been removed.
+    links: std::collections::HashMap<(u64, u64), u64>,
+}
+
+impl Reclaimed {
+    /// Remove the files, returning the space that this frees.
+    async fn files_size(&mut self, paths: &[std::path::PathBuf]) -> Result<u64, crate::Error> {
+        let mut bytes = 0;
+
+        for path in paths {
+            match tokio::fs::symlink_metadata(path).await {
+                Ok(metadata) => bytes += self.add(path, &metadata),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Remove the file, returning the space that this frees.
+    fn add(&mut self, path: &std::path::Path, metadata: &std::fs::Metadata) -> u64 {
+        if !self.paths.insert(path.to_path_buf()) {
+            return 0;
+        }
+
+        let link_count = crate::blob::link_count(metadata);
+
+        if link_count <= 1 {
+            return metadata.len();
+        }
+
+        match crate::util::file_id(metadata) {
+            Some(id) => {
+                let removed = self.links.entry(id).or_default();
+                *removed += 1;
+
+                if *removed == link_count {
+                    metadata.len()
+                } else {
+                    0
+                }
+            }
+            None => 0,
+        }
+    }
+}
+
+async fn file_size(path: &std::path::Path) -> Result<u64, crate::Error> {
+    match tokio::fs::symlink_metadata(path).await {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -1,11 +1,95 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "archive")]
+mod archive;
+mod batch;
+#[cfg(feature = "manifest")]
+mod blob;
+#[cfg(feature = "sqlite")]
+mod catalog;
 mod context;
+mod database;
+#[cfg(feature = "manifest")]
+mod delta;
 mod error;
+mod executor;
+#[cfg(feature = "tokio")]
+mod filesystem;
+#[cfg(feature = "sqlite")]
+mod gc;
+#[cfg(feature = "lock")]
+mod lock;
+#[cfg(feature = "manifest")]
+mod manifest;
+#[cfg(feature = "tokio")]
+mod pointer;
+mod preflight;
+mod provenance;
 mod publish;
+mod registry;
+#[cfg(feature = "manifest")]
+mod replication;
 mod runner;
+mod schema;
+mod secret;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod template;
+mod transaction;
+mod util;
+#[cfg(feature = "tokio")]
+mod version;
 
-pub use self::context::{Context, ContextIter, Value};
+#[cfg(feature = "archive")]
+pub use self::archive::{ArchiveFormat, ArchiveTransaction};
+#[cfg(feature = "tokio")]
+pub use self::batch::run_many;
+pub use self::batch::{run_many_with_executor, BatchReport, Job, JobOutcome, JobReport};
+#[cfg(feature = "manifest")]
+pub use self::blob::{BlobStore, BlobTransaction};
+#[cfg(feature = "sqlite")]
+pub use self::catalog::{Catalog, CatalogEntry, CatalogQuery, CatalogRecord, CatalogTransaction};
+pub use self::context::{
+    Context, ContextEntry, ContextIntoIter, ContextIter, ContextKeys, ContextValues, MergeStrategy,
+    Value, ValueType,
+};
+pub use self::database::{DatabaseBackend, DatabasePublish, DatabaseTransaction};
+#[cfg(feature = "manifest")]
+pub use self::delta::DeltaTransaction;
 pub use self::error::Error;
-pub use self::publish::Publish;
-pub use self::runner::run;
+#[cfg(feature = "tokio")]
+pub use self::executor::TokioExecutor;
+pub use self::executor::{timeout, BoxFuture, Executor};
+#[cfg(feature = "tokio")]
+pub use self::filesystem::FilesystemTransaction;
+#[cfg(feature = "sqlite")]
+pub use self::gc::{
+    CollectionReport, DeletionPlan, GarbageCollector, PlannedDeletion, RetentionPolicy,
+};
+#[cfg(feature = "lock")]
+pub use self::lock::{LockInfo, LockManager, PublishLock};
+#[cfg(feature = "manifest")]
+pub use self::manifest::{
+    ChecksumAlgorithm, Manifest, ManifestBuilder, ManifestEntry, ManifestProblem,
+};
+#[cfg(feature = "tokio")]
+pub use self::pointer::{PointerStore, PointerTransaction};
+#[cfg(feature = "preflight")]
+pub use self::preflight::Preflight;
+pub use self::preflight::{ShortfallKind, SpaceEstimate, SpaceShortfall};
+pub use self::provenance::{Provenance, Stage};
+pub use self::publish::{DynPublish, Publish};
+pub use self::registry::{PublishFactory, PublishMetadata, Registry};
+#[cfg(feature = "manifest")]
+pub use self::replication::{
+    QueuedReplication, ReplicationResult, ReplicationStatus, ReplicationTarget, Replicator,
+};
+pub use self::runner::{run, run_with_context, run_with_options, RunOptions, RunReport};
+pub use self::schema::{ContextSchema, SchemaField};
+pub use self::secret::Secret;
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::SqliteDatabase;
+pub use self::template::{Template, TemplateField, TemplateFormat};
+pub use self::transaction::Transaction;
+#[cfg(feature = "tokio")]
+pub use self::version::{VersionAllocator, VersionReservation};
@@ -0,0 +1,427 @@
+/// Who holds a publish lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockInfo {
+    pub owner: String,
+    pub pid: u32,
+    pub host: String,
+    pub timestamp: std::time::SystemTime,
+}
+
+impl LockInfo {
+    pub fn new<O: AsRef<str>, H: AsRef<str>>(
+        owner: O,
+        pid: u32,
+        host: H,
+        timestamp: std::time::SystemTime,
+    ) -> Self {
+        Self {
+            owner: owner.as_ref().to_string(),
+            pid,
+            host: host.as_ref().to_string(),
+            timestamp,
+        }
+    }
+
+    /// The lock info for the current user, process, and host.
+    pub fn current() -> Self {
+        let host = gethostname::gethostname().to_string_lossy().into_owned();
+
+        Self::new(
+            crate::util::current_user(),
+            std::process::id(),
+            host,
+            std::time::SystemTime::now(),
+        )
+    }
+
+    fn to_file_contents(&self) -> String {
+        let timestamp = self
+            .timestamp
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        format!(
+            "owner={}\npid={}\nhost={}\ntimestamp={}.{:09}\n",
+            self.owner,
+            self.pid,
+            self.host,
+            timestamp.as_secs(),
+            timestamp.subsec_nanos()
+        )
+    }
+
+    fn from_file_contents(contents: &str) -> Option<Self> {
+        let mut owner = None;
+        let mut pid = None;
+        let mut host = None;
+        let mut timestamp = None;
+
+        for line in contents.lines() {
+            match line.split_once('=')? {
+                ("owner", value) => owner = Some(value),
+                ("pid", value) => pid = value.parse().ok(),
+                ("host", value) => host = Some(value),
+                ("timestamp", value) => {
+                    let (secs, nanos) = value.split_once('.')?;
+                    let duration =
+                        std::time::Duration::new(secs.parse().ok()?, nanos.parse().ok()?);
+                    timestamp = std::time::UNIX_EPOCH.checked_add(duration);
+                }
+                _ => {}
+            }
+        }
+
+        Some(Self::new(owner?, pid?, host?, timestamp?))
+    }
+}
+
+impl std::fmt::Display for LockInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} on {} (pid {})", self.owner, self.host, self.pid)
+    }
+}
+
+/// Advisory locks that stop two publishes of the same asset from running at
+/// once.
+///
+/// Each lock is a file in the lock directory, which records the owner, PID,
+/// host, and time that it was acquired. The file is written in full before it
+/// is atomically linked into place, so other processes, including ones on
+/// other hosts that share the directory, never see a partial lock.
+///
+/// A lock is stale if its holder was a process on this host that has exited,
+/// or, for holders on other hosts, if it is older than the stale timeout.
+///
+/// Locks are advisory, so they only exclude publishes that use the same lock
+/// directory.
+#[derive(Clone)]
+pub struct LockManager {
+    directory: std::path::PathBuf,
+    stale_after: Option<std::time::Duration>,
+    timeout: Option<std::time::Duration>,
+    poll_interval: std::time::Duration,
+    executor: std::sync::Arc<dyn crate::Executor>,
+}
+
+impl std::fmt::Debug for LockManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LockManager")
+            .field("directory", &self.directory)
+            .field("stale_after", &self.stale_after)
+            .field("timeout", &self.timeout)
+            .field("poll_interval", &self.poll_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LockManager {
+    /// Create a lock manager that stores its locks in the directory.
+    ///
+    /// By default, only locks whose holder has exited are stale, and acquiring
+    /// a held lock fails straight away. Waiting uses the Tokio executor.
+    pub fn new<P: Into<std::path::PathBuf>>(directory: P) -> Self {
+        Self {
+            directory: directory.into(),
+            stale_after: None,
+            timeout: Some(std::time::Duration::ZERO),
+            poll_interval: std::time::Duration::from_millis(100),
+            executor: std::sync::Arc::new(crate::TokioExecutor),
+        }
+    }
+
+    /// Treat locks from other hosts that are older than the duration as
+    /// stale, and break them. The holders on other hosts cannot be checked,
+    /// so their age is the only sign that they are gone.
+    ///
+    /// This should be longer than the longest publish, since a lock is not
+    /// refreshed while it is held.
+    pub fn with_stale_after(mut self, stale_after: std::time::Duration) -> Self {
+        self.stale_after = Some(stale_after);
+        self
+    }
+
+    /// Wait up to the duration for a held lock to be released. `None` waits
+    /// forever.
+    pub fn with_timeout(mut self, timeout: Option<std::time::Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How often to check a held lock while waiting for it.
+    pub fn with_poll_interval(mut self, poll_interval: std::time::Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// The executor used to wait between checks of a held lock.
+    pub fn with_executor<E: crate::Executor + 'static>(mut self, executor: E) -> Self {
+        self.executor = std::sync::Arc::new(executor);
+        self
+    }
+
+    pub fn directory(&self) -> &std::path::Path {
+        &self.directory
+    }
+
+    /// The path of the lock file for the name.
+    ///
+    /// The name is percent-encoded, so names such as `demo/chair` can be used,
+    /// and different names always have different lock files.
+    pub fn path(&self, name: &str) -> std::path::PathBuf {
+        self.directory.join(format!("{}.lock", escape_name(name)))
+    }
+
+    /// Who holds the lock, if anyone.
+    pub async fn info(&self, name: &str) -> Result<Option<LockInfo>, crate::Error> {
+        read_info(&self.path(name)).await
+    }
+
+    /// Try to acquire the lock once, breaking it if it is stale.
+    ///
+    /// Returns `None` if the lock is held by someone else.
+    pub async fn try_acquire(&self, name: &str) -> Result<Option<PublishLock>, crate::Error> {
+        tokio::fs::create_dir_all(&self.directory).await?;
+
+        let path = self.path(name);
+
+        loop {
+            let info = LockInfo::current();
+
+            // Write the lock to a unique file first, then link it into place,
+            // which fails if the lock already exists.
+            let temp_path = crate::util::unique_path(&path, "tmp");
+            tokio::fs::write(&temp_path, info.to_file_contents()).await?;
+            let result = tokio::fs::hard_link(&temp_path, &path).await;
+            let _ = tokio::fs::remove_file(&temp_path).await;
+
+            match result {
+                Ok(()) => {
+                    return Ok(Some(PublishLock {
+                        name: name.to_string(),
+                        path,
+                        info,
+                        released: false,
+                    }))
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err.into()),
+            }
+
+            let retry = match read_info(&path).await? {
+                Some(holder) if self.is_stale(&holder) => self.break_stale(name, &holder).await?,
+                Some(_) => false,
+                // The lock was released in the meantime, so try again.
+                None => true,
+            };
+
+            if !retry {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Acquire the lock, waiting for it to be released up to the timeout.
+    ///
+    /// If the lock is still held after the timeout, then an
+    /// [`crate::Error::Lock`] that names the holder is returned.
+    pub async fn acquire(&self, name: &str) -> Result<PublishLock, crate::Error> {
+        let start = std::time::Instant::now();
+
+        loop {
+            if let Some(lock) = self.try_acquire(name).await? {
+                return Ok(lock);
+            }
+
+            if let Some(timeout) = self.timeout {
+                if start.elapsed() >= timeout {
+                    let holder = match self.info(name).await? {
+                        Some(holder) => holder.to_string(),
+                        None => "unknown".to_string(),
+                    };
+
+                    return Err(crate::Error::new_lock(format!(
+                        "{:?} is locked by {}",
+                        name, holder
+                    )));
+                }
+            }
+
+            self.executor.sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Break the lock, whether or not it is stale. Returns false if the lock
+    /// was not held.
+    ///
+    /// This should only be used when the holder is known to be gone, such as
+    /// after a crash.
+    pub async fn break_lock(&self, name: &str) -> Result<bool, crate::Error> {
+        match tokio::fs::remove_file(self.path(name)).await {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn is_stale(&self, holder: &LockInfo) -> bool {
+        // A holder on this host can be checked directly, which is more
+        // reliable than its age.
+        if holder.host == gethostname::gethostname().to_string_lossy() {
+            if let Some(alive) = process_is_alive(holder.pid) {
+                return !alive;
+            }
+        }
+
+        match (self.stale_after, holder.timestamp.elapsed()) {
+            (Some(stale_after), Ok(age)) => age >= stale_after,
+            _ => false,
+        }
+    }
+
+    /// Break a stale lock, making sure that a fresh lock that replaced it in
+    /// the meantime is left alone. Returns false if another process got the
+    /// lock first.
+    async fn break_stale(&self, name: &str, holder: &LockInfo) -> Result<bool, crate::Error> {
+        let path = self.path(name);
+        let stale_path = crate::util::unique_path(&path, "stale");
+
+        match tokio::fs::rename(&path, &stale_path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+            Err(err) => return Err(err.into()),
+        }
+
+        let mut broken = true;
+
+        if read_info(&stale_path).await?.as_ref() != Some(holder) {
+            // Another process broke the lock and acquired it first, so put
+            // its lock back.
+            broken = false;
+
+            match tokio::fs::hard_link(&stale_path, &path).await {
+                Ok(()) => {}
+                // A third process acquired the lock after it was moved aside,
+                // so the lock that was put back is lost, and this process lost
+                // the race.
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        tokio::fs::remove_file(&stale_path).await?;
+
+        Ok(broken)
+    }
+}
+
+/// A held publish lock.
+///
+/// The lock is released when it is dropped, but [`PublishLock::release`]
+/// should be preferred, since it reports errors.
+#[derive(Debug)]
+pub struct PublishLock {
+    name: String,
+    path: std::path::PathBuf,
+    info: LockInfo,
+    released: bool,
+}
+
+impl PublishLock {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    pub fn info(&self) -> &LockInfo {
+        &self.info
+    }
+
+    /// Release the lock.
+    ///
+    /// If the lock was broken and acquired by someone else, then their lock is
+    /// left alone and an error is returned.
+    pub async fn release(mut self) -> Result<(), crate::Error> {
+        self.released = true;
+
+        match read_info(&self.path).await? {
+            Some(holder) if holder == self.info => {
+                tokio::fs::remove_file(&self.path).await?;
+                Ok(())
+            }
+            _ => Err(crate::Error::new_lock(format!(
+                "{:?} was broken while it was held",
+                self.name
+            ))),
+        }
+    }
+}
+
+impl Drop for PublishLock {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+
+        let holder = std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|contents| LockInfo::from_file_contents(&contents));
+
+        if holder.as_ref() == Some(&self.info) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Percent-encode the name, so that it is a single file name, and different
+/// names never share a file.
+fn escape_name(name: &str) -> String {
+    let mut escaped = String::new();
+
+    for byte in name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => {
+                escaped.push(byte as char)
+            }
+            _ => escaped.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    escaped
+}
+
+/// Whether the process is still running, or `None` if that cannot be
+/// checked.
+#[cfg(unix)]
+fn process_is_alive(pid: u32) -> Option<bool> {
+    let pid = rustix::process::Pid::from_raw(i32::try_from(pid).ok()?)?;
+
+    match rustix::process::test_kill_process(pid) {
+        Ok(()) => Some(true),
+        // The process exists, but belongs to another user.
+        Err(rustix::io::Errno::PERM) => Some(true),
+        Err(rustix::io::Errno::SRCH) => Some(false),
+        Err(_) => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn process_is_alive(_pid: u32) -> Option<bool> {
+    None
+}
+
+async fn read_info(path: &std::path::Path) -> Result<Option<LockInfo>, crate::Error> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => match LockInfo::from_file_contents(&contents) {
+            Some(info) => Ok(Some(info)),
+            None => Err(crate::Error::new_lock(format!(
+                "Invalid lock file {:?}",
+                path
+            ))),
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
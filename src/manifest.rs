@@ -0,0 +1,402 @@
+/// The hash algorithm used for manifest checksums.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+impl std::fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sha256 => write!(f, "sha256"),
+            Self::Sha512 => write!(f, "sha512"),
+        }
+    }
+}
+
+impl std::str::FromStr for ChecksumAlgorithm {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(Self::Sha256),
+            "sha512" => Ok(Self::Sha512),
+            _ => Err(crate::Error::new_manifest(format!(
+                "Unknown checksum algorithm {:?}",
+                s
+            ))),
+        }
+    }
+}
+
+impl ChecksumAlgorithm {
+    /// Hash the file, returning the checksum as lowercase hex.
+    pub async fn hash_file<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<String, crate::Error> {
+        let algorithm = *self;
+        let path = path.as_ref().to_path_buf();
+
+        // Hashing is CPU bound, so it runs on the blocking pool instead of
+        // stalling the other tasks.
+        match tokio::task::spawn_blocking(move || algorithm.hash_file_blocking(&path)).await {
+            Ok(result) => result.map_err(crate::Error::from),
+            Err(err) => Err(crate::Error::new_runtime(err.to_string())),
+        }
+    }
+
+    fn hash_file_blocking(&self, path: &std::path::Path) -> std::io::Result<String> {
+        let file = std::fs::File::open(path)?;
+
+        match self {
+            Self::Sha256 => hash_reader::<sha2::Sha256>(file),
+            Self::Sha512 => hash_reader::<sha2::Sha512>(file),
+        }
+    }
+}
+
+fn hash_reader<D: sha2::Digest>(mut reader: impl std::io::Read) -> std::io::Result<String> {
+    let mut hasher = D::new();
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => break,
+            read => hasher.update(&buffer[..read]),
+        }
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// A file recorded in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ManifestEntry {
+    /// The path relative to the publish directory, with `/` separators.
+    pub path: String,
+    pub size: u64,
+    /// The modification time, in seconds since the Unix epoch.
+    pub modified: u64,
+    /// The Unix permission bits. On other platforms, this is `0o444` for
+    /// read-only files, and `0o644` otherwise.
+    pub permissions: u32,
+    pub checksum: String,
+    /// The publish directory that the file was linked from, unchanged, by a
+    /// [`DeltaTransaction`](crate::DeltaTransaction).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub carried_over: Option<String>,
+}
+
+/// A problem found while verifying a publish against its manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestProblem {
+    Missing {
+        path: String,
+    },
+    SizeMismatch {
+        path: String,
+        expected: u64,
+        actual: u64,
+    },
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl std::fmt::Display for ManifestProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { path } => write!(f, "{:?} is missing", path),
+            Self::SizeMismatch {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{:?} is {} bytes, expected {} bytes",
+                path, actual, expected
+            ),
+            Self::ChecksumMismatch { path, .. } => write!(f, "{:?} has a different checksum", path),
+        }
+    }
+}
+
+/// The checksums and stats of the files in a publish.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Manifest {
+    pub algorithm: ChecksumAlgorithm,
+    /// The files, sorted by path.
+    pub files: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// The name of the manifest file in the publish directory.
+    pub const FILE_NAME: &'static str = "manifest.json";
+
+    pub fn get(&self, path: &str) -> Option<&ManifestEntry> {
+        self.files
+            .binary_search_by(|entry| entry.path.as_str().cmp(path))
+            .ok()
+            .map(|index| &self.files[index])
+    }
+
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs to JSON cannot fail.
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, crate::Error> {
+        let mut manifest: Self = serde_json::from_str(json)
+            .map_err(|err| crate::Error::new_manifest(format!("Invalid manifest: {}", err)))?;
+        manifest
+            .files
+            .sort_by(|left, right| left.path.cmp(&right.path));
+
+        Ok(manifest)
+    }
+
+    /// Load the manifest from the publish directory.
+    pub async fn load<P: AsRef<std::path::Path>>(root: P) -> Result<Self, crate::Error> {
+        let json = tokio::fs::read_to_string(root.as_ref().join(Self::FILE_NAME)).await?;
+
+        Self::from_json(&json)
+    }
+
+    /// Write the manifest into the publish directory, through the transaction
+    /// so that it is removed if the publish is rolled back.
+    pub async fn save<P: AsRef<std::path::Path>>(
+        &self,
+        transaction: &crate::FilesystemTransaction,
+        root: P,
+    ) -> Result<std::path::PathBuf, crate::Error> {
+        let path = root.as_ref().join(Self::FILE_NAME);
+        transaction.write(&path, self.to_json()).await?;
+
+        Ok(path)
+    }
+
+    /// Verify the files in the publish directory against the manifest.
+    ///
+    /// All of the problems are returned, so an empty list means that the
+    /// publish matches the manifest.
+    pub async fn verify<P: AsRef<std::path::Path>>(
+        &self,
+        root: P,
+    ) -> Result<Vec<ManifestProblem>, crate::Error> {
+        use futures_util::StreamExt;
+
+        let root = root.as_ref();
+        let problems = futures_util::stream::iter(&self.files)
+            .map(|entry| self.verify_entry(root, entry))
+            .buffered(default_concurrency())
+            .collect::<Vec<_>>()
+            .await;
+
+        problems.into_iter().filter_map(Result::transpose).collect()
+    }
+
+    async fn verify_entry(
+        &self,
+        root: &std::path::Path,
+        entry: &ManifestEntry,
+    ) -> Result<Option<ManifestProblem>, crate::Error> {
+        let path = root.join(&entry.path);
+        let metadata = match tokio::fs::metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Some(ManifestProblem::Missing {
+                    path: entry.path.clone(),
+                }))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        if metadata.len() != entry.size {
+            return Ok(Some(ManifestProblem::SizeMismatch {
+                path: entry.path.clone(),
+                expected: entry.size,
+                actual: metadata.len(),
+            }));
+        }
+
+        let checksum = self.algorithm.hash_file(&path).await?;
+
+        if checksum != entry.checksum {
+            return Ok(Some(ManifestProblem::ChecksumMismatch {
+                path: entry.path.clone(),
+                expected: entry.checksum.clone(),
+                actual: checksum,
+            }));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Builds a manifest for the files in a publish directory.
+///
+/// ```no_run
+/// # async fn example(
+/// #     transaction: &publish::FilesystemTransaction,
+/// # ) -> Result<(), publish::Error> {
+/// let manifest = publish::ManifestBuilder::new("/publish/chair/v007")
+///     .add_files(transaction.written_files())
+///     .build()
+///     .await?;
+/// manifest.save(transaction, "/publish/chair/v007").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManifestBuilder {
+    root: std::path::PathBuf,
+    algorithm: ChecksumAlgorithm,
+    concurrency: usize,
+    files: Vec<std::path::PathBuf>,
+}
+
+impl ManifestBuilder {
+    pub fn new<P: Into<std::path::PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            algorithm: ChecksumAlgorithm::default(),
+            concurrency: default_concurrency(),
+            files: Vec::new(),
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// The number of files to hash at once. A concurrency of 0 is treated as
+    /// 1.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Add a file, either as an absolute path in the publish directory, or as
+    /// a path relative to it.
+    pub fn add_file<P: Into<std::path::PathBuf>>(mut self, path: P) -> Self {
+        self.files.push(path.into());
+        self
+    }
+
+    pub fn add_files<I: IntoIterator<Item = P>, P: Into<std::path::PathBuf>>(
+        mut self,
+        paths: I,
+    ) -> Self {
+        self.files.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Hash the files, and build the manifest.
+    ///
+    /// The manifest file itself is skipped, so the files written by a
+    /// transaction can be added as is.
+    pub async fn build(&self) -> Result<Manifest, crate::Error> {
+        use futures_util::StreamExt;
+
+        let mut paths = std::collections::BTreeMap::new();
+
+        for file in &self.files {
+            let path = self.root.join(file);
+            let relative = relative_path(&self.root, &path)?;
+
+            if relative != Manifest::FILE_NAME {
+                paths.insert(relative, path);
+            }
+        }
+
+        let files = futures_util::stream::iter(paths)
+            .map(|(relative, path)| self.build_entry(relative, path))
+            .buffered(self.concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Manifest {
+            algorithm: self.algorithm,
+            files,
+        })
+    }
+
+    async fn build_entry(
+        &self,
+        relative: String,
+        path: std::path::PathBuf,
+    ) -> Result<ManifestEntry, crate::Error> {
+        let metadata = tokio::fs::metadata(&path).await?;
+        let modified = metadata
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Ok(ManifestEntry {
+            path: relative,
+            size: metadata.len(),
+            modified,
+            permissions: permissions(&metadata),
+            checksum: self.algorithm.hash_file(&path).await?,
+            carried_over: None,
+        })
+    }
+}
+
+pub(crate) fn relative_path(
+    root: &std::path::Path,
+    path: &std::path::Path,
+) -> Result<String, crate::Error> {
+    let relative = path
+        .strip_prefix(root)
+        .map_err(|_| crate::Error::new_manifest(format!("{:?} is not in {:?}", path, root)))?;
+    let parts = relative
+        .components()
+        .map(|component| match component {
+            std::path::Component::Normal(part) => part.to_str().ok_or_else(|| {
+                crate::Error::new_manifest(format!("{:?} is not valid UTF-8", path))
+            }),
+            _ => Err(crate::Error::new_manifest(format!(
+                "{:?} is not a normalized path",
+                path
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(parts.join("/"))
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &std::fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
+fn default_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(|concurrency| concurrency.get())
+        .unwrap_or(4)
+}
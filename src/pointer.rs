@@ -0,0 +1,230 @@
+/// Named pointers to the versions in a publish directory, such as `latest` or
+/// `approved`.
+///
+/// Each pointer is a file named `@<name>` in the directory, which contains
+/// the path of its target, usually relative to the directory. So
+/// `/publish/chair@approved` is the `@approved` pointer in `/publish/chair`.
+///
+/// Pointers are changed by writing the new target to a temporary file, and
+/// then renaming it over the pointer, so readers always see either the old
+/// target or the new one. Unlike symlinks, pointer files work on every
+/// platform.
+#[derive(Debug, Clone)]
+pub struct PointerStore {
+    directory: std::path::PathBuf,
+}
+
+impl PointerStore {
+    pub fn new<P: Into<std::path::PathBuf>>(directory: P) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    pub fn directory(&self) -> &std::path::Path {
+        &self.directory
+    }
+
+    /// The path of the pointer file.
+    pub fn path(&self, name: &str) -> Result<std::path::PathBuf, crate::Error> {
+        validate_name(name)?;
+
+        Ok(self.directory.join(format!("@{}", name)))
+    }
+
+    /// The target of the pointer as it was set, or `None` if the pointer does
+    /// not exist.
+    pub async fn get(&self, name: &str) -> Result<Option<std::path::PathBuf>, crate::Error> {
+        match tokio::fs::read_to_string(self.path(name)?).await {
+            Ok(target) => Ok(Some(target.trim_end_matches('\n').into())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The path that the pointer points to, with relative targets resolved
+    /// against the directory.
+    pub async fn resolve(&self, name: &str) -> Result<Option<std::path::PathBuf>, crate::Error> {
+        Ok(self
+            .get(name)
+            .await?
+            .map(|target| self.directory.join(target)))
+    }
+
+    /// Resolve a reference such as `/publish/chair@approved`.
+    pub async fn resolve_reference(
+        reference: &str,
+    ) -> Result<Option<std::path::PathBuf>, crate::Error> {
+        match reference.rsplit_once('@') {
+            Some((directory, name)) => Self::new(directory).resolve(name).await,
+            None => Err(crate::Error::new_pointer(format!(
+                "{:?} is not a pointer reference, such as \"chair@approved\"",
+                reference
+            ))),
+        }
+    }
+
+    /// Point the pointer at the target, replacing its old target.
+    pub async fn set<T: AsRef<std::path::Path>>(
+        &self,
+        name: &str,
+        target: T,
+    ) -> Result<(), crate::Error> {
+        let path = self.path(name)?;
+        let target = target.as_ref().to_str().ok_or_else(|| {
+            crate::Error::new_pointer(format!("{:?} is not valid UTF-8", target.as_ref()))
+        })?;
+
+        tokio::fs::create_dir_all(&self.directory).await?;
+
+        let temp_path = crate::util::unique_path(&path, "tmp");
+        tokio::fs::write(&temp_path, format!("{}\n", target)).await?;
+
+        if let Err(err) = tokio::fs::rename(&temp_path, &path).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    /// Remove the pointer. Returns false if it did not exist.
+    pub async fn remove(&self, name: &str) -> Result<bool, crate::Error> {
+        match tokio::fs::remove_file(self.path(name)?).await {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The names of the pointers in the directory, sorted.
+    pub async fn names(&self) -> Result<Vec<String>, crate::Error> {
+        let mut names = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&self.directory).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(names),
+            Err(err) => return Err(err.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+
+            // Temporary files have an extension, and are skipped.
+            match file_name.to_str().and_then(|name| name.strip_prefix('@')) {
+                Some(name) if validate_name(name).is_ok() => names.push(name.to_string()),
+                _ => {}
+            }
+        }
+
+        names.sort();
+
+        Ok(names)
+    }
+}
+
+/// A transaction that changes pointers in a [`PointerStore`].
+///
+/// Each change is made straight away. Rolling back points each changed
+/// pointer back at its previous target, or removes it if it did not exist.
+/// Pointers are usually changed in `post_publish`, once the version is
+/// complete, and rolled back in `rollback_post_publish`.
+#[derive(Debug)]
+pub struct PointerTransaction {
+    store: PointerStore,
+    previous: std::sync::Mutex<Vec<(String, Option<std::path::PathBuf>)>>,
+}
+
+impl PointerTransaction {
+    pub fn new(store: PointerStore) -> Self {
+        Self {
+            store,
+            previous: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn store(&self) -> &PointerStore {
+        &self.store
+    }
+
+    /// Point the pointer at the target.
+    pub async fn set<T: AsRef<std::path::Path>>(
+        &self,
+        name: &str,
+        target: T,
+    ) -> Result<(), crate::Error> {
+        self.record_previous(name).await?;
+        self.store.set(name, target).await
+    }
+
+    /// Remove the pointer. Returns false if it did not exist.
+    pub async fn remove(&self, name: &str) -> Result<bool, crate::Error> {
+        self.record_previous(name).await?;
+        self.store.remove(name).await
+    }
+
+    /// Record the target of the pointer before the transaction first changes
+    /// it.
+    async fn record_previous(&self, name: &str) -> Result<(), crate::Error> {
+        if crate::util::lock(&self.previous)
+            .iter()
+            .any(|(other, _)| other == name)
+        {
+            return Ok(());
+        }
+
+        let target = self.store.get(name).await?;
+        let mut previous = crate::util::lock(&self.previous);
+
+        if !previous.iter().any(|(other, _)| other == name) {
+            previous.push((name.to_string(), target));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Transaction for PointerTransaction {
+    async fn commit(&self) -> Result<(), crate::Error> {
+        crate::util::lock(&self.previous).clear();
+
+        Ok(())
+    }
+
+    /// Restore the pointers, newest first.
+    ///
+    /// If a pointer cannot be restored, then it is kept along with the older
+    /// changes, so that the rollback can be retried once the problem is fixed.
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        loop {
+            let (name, target) = match crate::util::lock(&self.previous).pop() {
+                Some(previous) => previous,
+                None => break,
+            };
+
+            let result = match &target {
+                Some(target) => self.store.set(&name, target).await,
+                None => self.store.remove(&name).await.map(|_| ()),
+            };
+
+            if let Err(err) = result {
+                crate::util::lock(&self.previous).push((name, target));
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), crate::Error> {
+    // Names cannot have dots, so they never clash with temporary files.
+    if !name.is_empty() && !name.contains(['/', '\\', '@', '.']) {
+        Ok(())
+    } else {
+        Err(crate::Error::new_pointer(format!(
+            "{:?} is not a valid pointer name",
+            name
+        )))
+    }
+}
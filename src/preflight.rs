@@ -0,0 +1,309 @@
+/// The number of bytes that a publish expects to write under a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceEstimate {
+    pub path: std::path::PathBuf,
+    pub bytes: u64,
+}
+
+impl SpaceEstimate {
+    pub fn new<P: Into<std::path::PathBuf>>(path: P, bytes: u64) -> Self {
+        Self {
+            path: path.into(),
+            bytes,
+        }
+    }
+}
+
+/// What a publish would run out of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortfallKind {
+    /// The free space on a volume.
+    FreeSpace,
+    /// The quota of a project.
+    Quota,
+}
+
+/// A volume or quota that does not have enough space for a publish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceShortfall {
+    pub kind: ShortfallKind,
+    /// A path on the volume, or the root of the quota.
+    pub path: std::path::PathBuf,
+    /// The bytes that the publish needs.
+    pub required: u64,
+    /// The bytes that are available.
+    pub available: u64,
+}
+
+impl SpaceShortfall {
+    /// The number of bytes that are missing.
+    pub fn shortfall(&self) -> u64 {
+        self.required.saturating_sub(self.available)
+    }
+}
+
+impl std::fmt::Display for SpaceShortfall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            ShortfallKind::FreeSpace => "free space",
+            ShortfallKind::Quota => "quota",
+        };
+
+        write!(
+            f,
+            "{:?} needs {} bytes of {}, but only {} bytes are available",
+            self.path, self.required, kind, self.available
+        )
+    }
+}
+
+/// Checks that there is enough space for a publish before it starts writing,
+/// so that it fails early instead of halfway through.
+///
+/// The publish declares how much it expects to write with
+/// [`crate::Publish::estimate_space`]. The estimates are checked against the
+/// free space of the volumes that they are on, and against the quotas of the
+/// projects that they are in. Every shortfall is returned in a single
+/// [`crate::Error::InsufficientSpace`] error.
+///
+/// Free space is only checked on Unix.
+///
+/// ```no_run
+/// # fn example() -> Result<(), publish::Error> {
+/// let preflight = publish::Preflight::new()
+///     .with_reserve(1024 * 1024 * 1024)
+///     .with_quota(publish::Template::new("/publish/{show}")?, 10 * 1024_u64.pow(4));
+/// let options = publish::RunOptions::new().with_preflight(preflight);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "preflight")]
+#[derive(Debug, Clone, Default)]
+pub struct Preflight {
+    quotas: Vec<(crate::Template, u64)>,
+    reserve: u64,
+}
+
+#[cfg(feature = "preflight")]
+impl Preflight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the bytes used under a project root, such as `/publish/{show}`,
+    /// which is rendered from the context.
+    ///
+    /// The bytes that are already used are counted by walking the root, so
+    /// quotas are best kept to roots that are not too large.
+    pub fn with_quota(mut self, root: crate::Template, limit: u64) -> Self {
+        self.quotas.push((root, limit));
+        self
+    }
+
+    /// Keep the bytes free on each volume, on top of the estimates.
+    pub fn with_reserve(mut self, reserve: u64) -> Self {
+        self.reserve = reserve;
+        self
+    }
+
+    /// Check the publish's estimates for the context.
+    pub async fn check_publish<P>(
+        &self,
+        publish: &P,
+        context: &crate::Context,
+    ) -> Result<(), crate::Error>
+    where
+        P: crate::Publish + Send + Sync + ?Sized,
+    {
+        let estimates = publish.estimate_space(context).await?;
+
+        self.check(context, &estimates).await
+    }
+
+    /// Check the estimates, returning an error that lists every shortfall.
+    pub async fn check(
+        &self,
+        context: &crate::Context,
+        estimates: &[SpaceEstimate],
+    ) -> Result<(), crate::Error> {
+        let mut shortfalls = self.check_free_space(estimates).await?;
+        let mut paths = Vec::with_capacity(estimates.len());
+
+        if !self.quotas.is_empty() {
+            for estimate in estimates {
+                paths.push((normalize_path(&estimate.path).await?, estimate.bytes));
+            }
+        }
+
+        for (root, limit) in &self.quotas {
+            let root = std::path::PathBuf::from(root.render(context)?);
+            let normalized_root = normalize_path(&root).await?;
+            let required = paths
+                .iter()
+                .filter(|(path, _)| path.starts_with(&normalized_root))
+                .map(|(_, bytes)| bytes)
+                .sum::<u64>();
+
+            if required == 0 {
+                continue;
+            }
+
+            let available = limit.saturating_sub(used_space(&root).await?);
+
+            if required > available {
+                shortfalls.push(SpaceShortfall {
+                    kind: ShortfallKind::Quota,
+                    path: root,
+                    required,
+                    available,
+                });
+            }
+        }
+
+        if shortfalls.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::Error::new_insufficient_space(shortfalls))
+        }
+    }
+
+    /// Sum the estimates on each volume, and compare them to its free space.
+    #[cfg(unix)]
+    async fn check_free_space(
+        &self,
+        estimates: &[SpaceEstimate],
+    ) -> Result<Vec<SpaceShortfall>, crate::Error> {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut volumes = std::collections::BTreeMap::new();
+
+        for estimate in estimates {
+            // The path usually does not exist yet, so the volume is found from
+            // its nearest existing ancestor.
+            let path = existing_ancestor(&estimate.path).await?;
+            let device = tokio::fs::metadata(&path).await?.dev();
+            let (_, required) = volumes.entry(device).or_insert((path, 0_u64));
+            *required += estimate.bytes;
+        }
+
+        let mut shortfalls = Vec::new();
+
+        for (path, required) in volumes.into_values() {
+            let available = free_space(&path).await?.saturating_sub(self.reserve);
+
+            if required > available {
+                shortfalls.push(SpaceShortfall {
+                    kind: ShortfallKind::FreeSpace,
+                    path,
+                    required,
+                    available,
+                });
+            }
+        }
+
+        Ok(shortfalls)
+    }
+
+    #[cfg(not(unix))]
+    async fn check_free_space(
+        &self,
+        _estimates: &[SpaceEstimate],
+    ) -> Result<Vec<SpaceShortfall>, crate::Error> {
+        Ok(Vec::new())
+    }
+}
+
+/// The absolute path, with symlinks and `..` resolved, so that paths can be
+/// compared. The path usually does not exist yet, so only its nearest existing
+/// ancestor is resolved, and the rest of the path is appended to it.
+#[cfg(feature = "preflight")]
+async fn normalize_path(path: &std::path::Path) -> Result<std::path::PathBuf, crate::Error> {
+    for ancestor in path.ancestors() {
+        let existing = if ancestor.as_os_str().is_empty() {
+            std::path::Path::new(".")
+        } else {
+            ancestor
+        };
+
+        let mut normalized = match tokio::fs::canonicalize(existing).await {
+            Ok(normalized) => normalized,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+
+        for component in path.strip_prefix(ancestor).unwrap_or(path).components() {
+            match component {
+                std::path::Component::ParentDir => {
+                    normalized.pop();
+                }
+                std::path::Component::Normal(name) => normalized.push(name),
+                _ => {}
+            }
+        }
+
+        return Ok(normalized);
+    }
+
+    Err(std::io::Error::from(std::io::ErrorKind::NotFound).into())
+}
+
+#[cfg(all(feature = "preflight", unix))]
+async fn existing_ancestor(path: &std::path::Path) -> Result<std::path::PathBuf, crate::Error> {
+    for ancestor in path.ancestors() {
+        let ancestor = if ancestor.as_os_str().is_empty() {
+            std::path::Path::new(".")
+        } else {
+            ancestor
+        };
+
+        match tokio::fs::metadata(ancestor).await {
+            Ok(_) => return Ok(ancestor.to_path_buf()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Err(std::io::Error::from(std::io::ErrorKind::NotFound).into())
+}
+
+/// The bytes that unprivileged users can write to the volume of the path.
+#[cfg(all(feature = "preflight", unix))]
+async fn free_space(path: &std::path::Path) -> Result<u64, crate::Error> {
+    let path = path.to_path_buf();
+
+    match tokio::task::spawn_blocking(move || rustix::fs::statvfs(&path)).await {
+        Ok(Ok(stats)) => Ok(stats.f_bavail.saturating_mul(stats.f_frsize)),
+        Ok(Err(err)) => Err(std::io::Error::from(err).into()),
+        Err(err) => Err(crate::Error::new_runtime(err.to_string())),
+    }
+}
+
+/// The bytes used by the files under the root, or 0 if it does not exist.
+/// Files that are hard linked more than once under the root are only counted
+/// once.
+#[cfg(feature = "preflight")]
+async fn used_space(root: &std::path::Path) -> Result<u64, crate::Error> {
+    let mut used = 0;
+    let mut seen = std::collections::HashSet::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = tokio::fs::symlink_metadata(entry.path()).await?;
+
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+            } else if crate::util::file_id(&metadata).is_none_or(|id| seen.insert(id)) {
+                used += metadata.len();
+            }
+        }
+    }
+
+    Ok(used)
+}
@@ -0,0 +1,44 @@
+/// A publish stage that can write to the context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    PrePublish,
+    Publish,
+    PostPublish,
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::PrePublish => "pre_publish",
+            Self::Publish => "publish",
+            Self::PostPublish => "post_publish",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Where the value of a context key came from.
+///
+/// Provenance is recorded by the runner for contexts that track it. See
+/// [`crate::Context::with_provenance`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// The stage that last wrote the key.
+    pub stage: Stage,
+    /// The name of the publish that last wrote the key, from
+    /// [`crate::Publish::name`].
+    pub publish: String,
+    /// When the stage that wrote the key finished.
+    pub timestamp: std::time::SystemTime,
+}
+
+impl Provenance {
+    pub fn new<T: AsRef<str>>(stage: Stage, publish: T, timestamp: std::time::SystemTime) -> Self {
+        Self {
+            stage,
+            publish: publish.as_ref().to_string(),
+            timestamp,
+        }
+    }
+}
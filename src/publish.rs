@@ -12,6 +12,14 @@
 /// transactions can be rolled back if one of the publish stages fail.
 #[async_trait::async_trait]
 pub trait Publish {
+    /// The name of the publish.
+    ///
+    /// This is used to describe the publish in reports, such as the provenance
+    /// of context keys. Defaults to the name of the type.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
     /// Pre-publish stage.
     ///
     /// This stage should be used to prepare the main publish. For example,
@@ -59,4 +67,91 @@ pub trait Publish {
     async fn rollback_post_publish(&self, _context: &crate::Context) -> Result<(), crate::Error> {
         Ok(())
     }
+
+    /// The bytes that the publish expects to write under each path.
+    ///
+    /// This is called with the context from the pre-publish stage, and is
+    /// checked by a [`crate::Preflight`] before the publish stage begins.
+    /// Defaults to no estimates.
+    async fn estimate_space(
+        &self,
+        _context: &crate::Context,
+    ) -> Result<Vec<crate::SpaceEstimate>, crate::Error> {
+        Ok(Vec::new())
+    }
 }
+
+/// A type-erased publish.
+///
+/// This is the trait object used to store publishes of different types
+/// together, such as in a [`crate::Registry`] or a list of jobs. `Box<DynPublish>`,
+/// `Arc<DynPublish>`, and `&DynPublish` all implement [`Publish`], so they can be
+/// passed directly to the runner.
+pub type DynPublish = dyn Publish + Send + Sync;
+
+macro_rules! impl_publish_for_pointer {
+    ($($pointer:ty),+ $(,)?) => {
+        $(
+            #[async_trait::async_trait]
+            impl<T> Publish for $pointer
+            where
+                T: Publish + Send + Sync + ?Sized,
+            {
+                fn name(&self) -> &str {
+                    (**self).name()
+                }
+
+                async fn pre_publish<'a>(
+                    &self,
+                    context: &'a crate::Context,
+                ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+                    (**self).pre_publish(context).await
+                }
+
+                async fn rollback_pre_publish(
+                    &self,
+                    context: &crate::Context,
+                ) -> Result<(), crate::Error> {
+                    (**self).rollback_pre_publish(context).await
+                }
+
+                async fn publish<'a>(
+                    &self,
+                    context: &'a crate::Context,
+                ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+                    (**self).publish(context).await
+                }
+
+                async fn rollback_publish(
+                    &self,
+                    context: &crate::Context,
+                ) -> Result<(), crate::Error> {
+                    (**self).rollback_publish(context).await
+                }
+
+                async fn post_publish<'a>(
+                    &self,
+                    context: &'a crate::Context,
+                ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+                    (**self).post_publish(context).await
+                }
+
+                async fn rollback_post_publish(
+                    &self,
+                    context: &crate::Context,
+                ) -> Result<(), crate::Error> {
+                    (**self).rollback_post_publish(context).await
+                }
+
+                async fn estimate_space(
+                    &self,
+                    context: &crate::Context,
+                ) -> Result<Vec<crate::SpaceEstimate>, crate::Error> {
+                    (**self).estimate_space(context).await
+                }
+            }
+        )+
+    };
+}
+
+impl_publish_for_pointer!(&T, Box<T>, std::sync::Arc<T>);
@@ -0,0 +1,222 @@
+/// A function that creates a new publish.
+pub type PublishFactory =
+    Box<dyn Fn() -> Result<Box<crate::DynPublish>, crate::Error> + Send + Sync>;
+
+/// Information about a registered publish.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublishMetadata {
+    pub description: String,
+    pub version: String,
+    pub schema: crate::ContextSchema,
+}
+
+impl PublishMetadata {
+    pub fn new<D: AsRef<str>, V: AsRef<str>>(
+        description: D,
+        version: V,
+        schema: crate::ContextSchema,
+    ) -> Self {
+        Self {
+            description: description.as_ref().to_string(),
+            version: version.as_ref().to_string(),
+            schema,
+        }
+    }
+}
+
+struct RegistryEntry {
+    metadata: PublishMetadata,
+    factory: PublishFactory,
+}
+
+/// A publish created by the registry, which is named after its registered
+/// name instead of its type.
+struct RegisteredPublish {
+    name: String,
+    inner: Box<crate::DynPublish>,
+}
+
+#[async_trait::async_trait]
+impl crate::Publish for RegisteredPublish {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn pre_publish<'a>(
+        &self,
+        context: &'a crate::Context,
+    ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+        self.inner.pre_publish(context).await
+    }
+
+    async fn rollback_pre_publish(&self, context: &crate::Context) -> Result<(), crate::Error> {
+        self.inner.rollback_pre_publish(context).await
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a crate::Context,
+    ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+        self.inner.publish(context).await
+    }
+
+    async fn rollback_publish(&self, context: &crate::Context) -> Result<(), crate::Error> {
+        self.inner.rollback_publish(context).await
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a crate::Context,
+    ) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error> {
+        self.inner.post_publish(context).await
+    }
+
+    async fn rollback_post_publish(&self, context: &crate::Context) -> Result<(), crate::Error> {
+        self.inner.rollback_post_publish(context).await
+    }
+
+    async fn estimate_space(
+        &self,
+        context: &crate::Context,
+    ) -> Result<Vec<crate::SpaceEstimate>, crate::Error> {
+        self.inner.estimate_space(context).await
+    }
+}
+
+/// A catalog of publishes, looked up by name.
+///
+/// Tools can use the registry to pick the publish to run from a string such as
+/// "model", "rig", or "camera". The registry stores factories instead of
+/// publishes, so each run gets a fresh publish.
+#[derive(Default)]
+pub struct Registry {
+    entries: std::collections::BTreeMap<String, RegistryEntry>,
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a publish factory.
+    ///
+    /// Registering a name that is already registered is an error.
+    pub fn register<F, P>(
+        &mut self,
+        name: &str,
+        metadata: PublishMetadata,
+        factory: F,
+    ) -> Result<(), crate::Error>
+    where
+        F: Fn() -> P + Send + Sync + 'static,
+        P: crate::Publish + Send + Sync + 'static,
+    {
+        self.register_factory(
+            name,
+            metadata,
+            Box::new(move || Ok(Box::new(factory()) as Box<crate::DynPublish>)),
+        )
+    }
+
+    /// Register a boxed publish factory.
+    ///
+    /// This is useful when the factory can fail, such as when the publish is
+    /// created by a plugin or another language.
+    pub fn register_factory(
+        &mut self,
+        name: &str,
+        metadata: PublishMetadata,
+        factory: PublishFactory,
+    ) -> Result<(), crate::Error> {
+        if self.entries.contains_key(name) {
+            return Err(crate::Error::new_registry(format!(
+                "A publish named {:?} is already registered",
+                name
+            )));
+        }
+
+        self.entries
+            .insert(name.to_string(), RegistryEntry { metadata, factory });
+
+        Ok(())
+    }
+
+    /// Remove a publish from the registry, returning true if it was
+    /// registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    pub fn metadata(&self, name: &str) -> Option<&PublishMetadata> {
+        self.entries.get(name).map(|entry| &entry.metadata)
+    }
+
+    /// Create a new publish from the factory registered under the name.
+    pub fn create(&self, name: &str) -> Result<Box<crate::DynPublish>, crate::Error> {
+        match self.entries.get(name) {
+            Some(entry) => Ok(Box::new(RegisteredPublish {
+                name: name.to_string(),
+                inner: (entry.factory)()?,
+            })),
+            None => Err(crate::Error::new_registry(format!(
+                "No publish named {:?} is registered",
+                name
+            ))),
+        }
+    }
+
+    /// Check the context against the schema of the publish registered under
+    /// the name.
+    pub fn validate(&self, name: &str, context: &crate::Context) -> Result<(), crate::Error> {
+        match self.metadata(name) {
+            Some(metadata) => metadata.schema.validate(context),
+            None => Err(crate::Error::new_registry(format!(
+                "No publish named {:?} is registered",
+                name
+            ))),
+        }
+    }
+
+    /// Create the publish registered under the name, validate the context,
+    /// and run the publish.
+    pub async fn run(
+        &self,
+        name: &str,
+        context: crate::Context,
+    ) -> Result<crate::Context, crate::Error> {
+        self.validate(name, &context)?;
+        let publish = self.create(name)?;
+
+        crate::run_with_context(publish.as_ref(), context).await
+    }
+
+    /// The registered names, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|name| name.as_str())
+    }
+
+    /// The registered names and metadata, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PublishMetadata)> {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), &entry.metadata))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
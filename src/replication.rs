@@ -0,0 +1,433 @@
+/// A secondary root that publishes are replicated to, such as a mount of the
+/// storage in a remote office.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicationTarget {
+    name: String,
+    root: std::path::PathBuf,
+}
+
+impl ReplicationTarget {
+    pub fn new<N: Into<String>, P: Into<std::path::PathBuf>>(name: N, root: P) -> Self {
+        Self {
+            name: name.into(),
+            root: root.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn root(&self) -> &std::path::Path {
+        &self.root
+    }
+}
+
+/// The outcome of replicating a version to a target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplicationStatus {
+    Replicated,
+    /// The replication failed, and is queued to be retried.
+    Failed {
+        error: String,
+        attempts: u32,
+    },
+}
+
+/// The status of a version on one of the targets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicationResult {
+    /// The path of the version, relative to the source root, with `/`
+    /// separators.
+    pub path: String,
+    pub target: String,
+    pub status: ReplicationStatus,
+}
+
+/// A failed replication that is waiting to be retried.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct QueuedReplication {
+    /// The path of the version, relative to the source root, with `/`
+    /// separators.
+    pub path: String,
+    pub target: String,
+    pub attempts: u32,
+    /// The error from the last attempt.
+    pub error: String,
+    /// When the last attempt failed, in seconds since the Unix epoch.
+    pub failed: u64,
+}
+
+/// Replicates published versions from the source root to secondary roots.
+///
+/// Each version is copied into a temporary directory on the target, verified
+/// against the checksums in its manifest, and then renamed into place, so a
+/// version on a target is always complete. Versions without a manifest are
+/// verified against the checksums of the source files.
+///
+/// Failures are recorded in a queue file, which persists across runs, so that
+/// they can be retried later with [`Replicator::retry`]. The queue is locked
+/// while it is updated, so replicators in several processes can share it.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), publish::Error> {
+/// let replicator = publish::Replicator::new("/publish", "/publish/.replication.json")
+///     .with_target(publish::ReplicationTarget::new("remote", "/mnt/remote/publish"));
+/// let results = replicator.replicate("/publish/chair/v007").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Replicator {
+    source: std::path::PathBuf,
+    targets: Vec<ReplicationTarget>,
+    queue_path: std::path::PathBuf,
+    queue_lock: tokio::sync::Mutex<()>,
+}
+
+impl Replicator {
+    /// Create a replicator for the versions under the source root, which
+    /// stores its queue at the path.
+    pub fn new<S: Into<std::path::PathBuf>, Q: Into<std::path::PathBuf>>(
+        source: S,
+        queue_path: Q,
+    ) -> Self {
+        Self {
+            source: source.into(),
+            targets: Vec::new(),
+            queue_path: queue_path.into(),
+            queue_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn with_target(mut self, target: ReplicationTarget) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    pub fn source(&self) -> &std::path::Path {
+        &self.source
+    }
+
+    pub fn targets(&self) -> &[ReplicationTarget] {
+        &self.targets
+    }
+
+    pub fn queue_path(&self) -> &std::path::Path {
+        &self.queue_path
+    }
+
+    /// Replicate the version directory, either as an absolute path in the
+    /// source root, or as a path relative to it, to every target.
+    ///
+    /// A failure to replicate to a target does not fail the call. It is
+    /// returned as a failed result, and queued to be retried. An error is
+    /// only returned if the version is not in the source root, or the queue
+    /// cannot be updated.
+    pub async fn replicate<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<Vec<ReplicationResult>, crate::Error> {
+        let path = crate::manifest::relative_path(&self.source, &self.source.join(path))?;
+
+        if path.is_empty() {
+            return Err(crate::Error::new_replication(
+                "Cannot replicate the whole source root",
+            ));
+        }
+
+        let mut results = Vec::new();
+
+        for target in &self.targets {
+            let result = self.replicate_to(&path, target).await;
+            results.push(self.record(&path, &target.name, result).await?);
+        }
+
+        Ok(results)
+    }
+
+    /// Retry the queued replications. Replications that succeed are removed
+    /// from the queue, and those that fail again stay queued.
+    pub async fn retry(&self) -> Result<Vec<ReplicationResult>, crate::Error> {
+        let queue = self.queue().await?;
+        let mut results = Vec::new();
+
+        for queued in queue {
+            let result = match self
+                .targets
+                .iter()
+                .find(|target| target.name == queued.target)
+            {
+                Some(target) => self.replicate_to(&queued.path, target).await,
+                None => Err(crate::Error::new_replication(format!(
+                    "Unknown replication target {:?}",
+                    queued.target
+                ))),
+            };
+
+            results.push(self.record(&queued.path, &queued.target, result).await?);
+        }
+
+        Ok(results)
+    }
+
+    /// The replications that are waiting to be retried.
+    pub async fn queue(&self) -> Result<Vec<QueuedReplication>, crate::Error> {
+        let _guard = self.lock_queue().await?;
+
+        self.load_queue().await
+    }
+
+    /// Record the result of a replication in the queue.
+    ///
+    /// The queue is only locked while it is updated, and not while versions
+    /// are copied, so that other replicators are not blocked. It is loaded
+    /// again under the lock, so that their updates are kept.
+    async fn record(
+        &self,
+        path: &str,
+        target: &str,
+        result: Result<(), crate::Error>,
+    ) -> Result<ReplicationResult, crate::Error> {
+        let _guard = self.lock_queue().await?;
+        let mut queue = self.load_queue().await?;
+        let attempts =
+            queue_position(&queue, path, target).map_or(0, |index| queue[index].attempts);
+        let result = update_queue(&mut queue, path, target, attempts, result);
+        self.save_queue(&queue).await?;
+
+        Ok(result)
+    }
+
+    /// Lock the queue against other replicators, both in this process, and in
+    /// other processes through a lock file beside the queue. The queue is
+    /// unlocked when the guard is dropped.
+    async fn lock_queue(
+        &self,
+    ) -> Result<(tokio::sync::MutexGuard<'_, ()>, std::fs::File), crate::Error> {
+        let guard = self.queue_lock.lock().await;
+
+        if let Some(parent) = self.queue_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut lock_path = self.queue_path.as_os_str().to_os_string();
+        lock_path.push(".lock");
+
+        let file = match tokio::task::spawn_blocking(move || {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(lock_path)?;
+            file.lock()?;
+
+            Ok::<_, std::io::Error>(file)
+        })
+        .await
+        {
+            Ok(result) => result?,
+            Err(err) => return Err(crate::Error::new_runtime(err.to_string())),
+        };
+
+        Ok((guard, file))
+    }
+
+    async fn load_queue(&self) -> Result<Vec<QueuedReplication>, crate::Error> {
+        match tokio::fs::read_to_string(&self.queue_path).await {
+            Ok(json) => serde_json::from_str(&json).map_err(|err| {
+                crate::Error::new_replication(format!(
+                    "Invalid replication queue {:?}: {}",
+                    self.queue_path, err
+                ))
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Write the queue to a temporary file, and rename it into place, so that
+    /// the queue is never left half written.
+    async fn save_queue(&self, queue: &[QueuedReplication]) -> Result<(), crate::Error> {
+        // Serializing plain structs to JSON cannot fail.
+        let json = serde_json::to_string_pretty(queue).unwrap_or_default();
+        let temp_path = crate::util::unique_path(&self.queue_path, "tmp");
+        tokio::fs::write(&temp_path, json).await?;
+
+        if let Err(err) = tokio::fs::rename(&temp_path, &self.queue_path).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    async fn replicate_to(
+        &self,
+        path: &str,
+        target: &ReplicationTarget,
+    ) -> Result<(), crate::Error> {
+        let source = self.source.join(path);
+        let destination = target.root.join(path);
+        let expected = expected_checksums(&source).await?;
+
+        // Versions are immutable, so a version that is already on the target
+        // only has to be verified.
+        if tokio::fs::metadata(&destination).await.is_ok() {
+            return verify(&destination, &expected).await;
+        }
+
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let temp_path = crate::util::unique_path(&destination, "tmp");
+        let result = async {
+            copy_dir(&source, &temp_path).await?;
+            verify(&temp_path, &expected).await?;
+            tokio::fs::rename(&temp_path, &destination).await?;
+
+            Ok(())
+        }
+        .await;
+
+        if result.is_err() {
+            let _ = tokio::fs::remove_dir_all(&temp_path).await;
+        }
+
+        result
+    }
+}
+
+/// The checksums that the copies of the files in the version must match.
+async fn expected_checksums(
+    root: &std::path::Path,
+) -> Result<(crate::ChecksumAlgorithm, Vec<(String, String)>), crate::Error> {
+    match crate::Manifest::load(root).await {
+        Ok(manifest) => Ok((
+            manifest.algorithm,
+            manifest
+                .files
+                .into_iter()
+                .map(|entry| (entry.path, entry.checksum))
+                .collect(),
+        )),
+        Err(crate::Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+            let algorithm = crate::ChecksumAlgorithm::default();
+            let mut checksums = Vec::new();
+
+            for path in crate::util::list_files(root).await? {
+                let checksum = algorithm.hash_file(&path).await?;
+                checksums.push((crate::manifest::relative_path(root, &path)?, checksum));
+            }
+
+            Ok((algorithm, checksums))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+async fn verify(
+    root: &std::path::Path,
+    (algorithm, expected): &(crate::ChecksumAlgorithm, Vec<(String, String)>),
+) -> Result<(), crate::Error> {
+    for (path, checksum) in expected {
+        let actual = match algorithm.hash_file(root.join(path)).await {
+            Ok(actual) => actual,
+            Err(crate::Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(crate::Error::new_replication(format!(
+                    "{:?} is missing from {:?}",
+                    path, root
+                )))
+            }
+            Err(err) => return Err(err),
+        };
+
+        if &actual != checksum {
+            return Err(crate::Error::new_replication(format!(
+                "{:?} in {:?} has a different checksum",
+                path, root
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+async fn copy_dir(from: &std::path::Path, to: &std::path::Path) -> Result<(), crate::Error> {
+    let mut dirs = vec![(from.to_path_buf(), to.to_path_buf())];
+
+    while let Some((from, to)) = dirs.pop() {
+        tokio::fs::create_dir_all(&to).await?;
+        let mut entries = tokio::fs::read_dir(&from).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let destination = to.join(entry.file_name());
+
+            if entry.file_type().await?.is_dir() {
+                dirs.push((entry.path(), destination));
+            } else {
+                tokio::fs::copy(entry.path(), destination).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn queue_position(queue: &[QueuedReplication], path: &str, target: &str) -> Option<usize> {
+    queue
+        .iter()
+        .position(|queued| queued.path == path && queued.target == target)
+}
+
+/// Remove the replication from the queue if it succeeded, or queue it if it
+/// failed.
+fn update_queue(
+    queue: &mut Vec<QueuedReplication>,
+    path: &str,
+    target: &str,
+    attempts: u32,
+    result: Result<(), crate::Error>,
+) -> ReplicationResult {
+    let position = queue_position(queue, path, target);
+
+    let status = match result {
+        Ok(()) => {
+            if let Some(index) = position {
+                queue.remove(index);
+            }
+
+            ReplicationStatus::Replicated
+        }
+        Err(err) => {
+            let queued = QueuedReplication {
+                path: path.to_string(),
+                target: target.to_string(),
+                attempts: attempts + 1,
+                error: err.to_string(),
+                failed: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            };
+            let status = ReplicationStatus::Failed {
+                error: queued.error.clone(),
+                attempts: queued.attempts,
+            };
+
+            match position {
+                Some(index) => queue[index] = queued,
+                None => queue.push(queued),
+            }
+
+            status
+        }
+    };
+
+    ReplicationResult {
+        path: path.to_string(),
+        target: target.to_string(),
+        status,
+    }
+}
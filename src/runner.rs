@@ -4,10 +4,177 @@
 /// stages that have been run.
 pub async fn run<P>(publish: &P) -> Result<crate::Context, crate::Error>
 where
-    P: crate::Publish + Send + Sync,
+    P: crate::Publish + Send + Sync + ?Sized,
 {
-    let context = crate::Context::default();
-    let pre_publish_context = match publish.pre_publish(&context).await {
+    run_with_context(publish, crate::Context::default()).await
+}
+
+/// Run a publish, starting from an initial context.
+///
+/// This behaves the same as [`run`], except the pre-publish stage receives the
+/// given context instead of an empty one.
+pub async fn run_with_context<P>(
+    publish: &P,
+    context: crate::Context,
+) -> Result<crate::Context, crate::Error>
+where
+    P: crate::Publish + Send + Sync + ?Sized,
+{
+    run_stages(publish, context, &RunOptions::default()).await
+}
+
+/// Options for [`run_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    #[cfg(feature = "lock")]
+    lock: Option<(std::sync::Arc<crate::LockManager>, crate::Template)>,
+    #[cfg(feature = "manifest")]
+    replication: Option<(std::sync::Arc<crate::Replicator>, crate::Template)>,
+    #[cfg(feature = "preflight")]
+    preflight: Option<crate::Preflight>,
+}
+
+impl RunOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold a lock for the whole publish.
+    ///
+    /// The lock name is rendered from the initial context, such as
+    /// `{show}/{asset}`, so publishes of different assets do not block each
+    /// other.
+    #[cfg(feature = "lock")]
+    pub fn with_lock(
+        mut self,
+        manager: std::sync::Arc<crate::LockManager>,
+        name: crate::Template,
+    ) -> Self {
+        self.lock = Some((manager, name));
+        self
+    }
+
+    /// Replicate the version after the post-publish stage succeeds.
+    ///
+    /// The version directory is rendered from the final context, such as
+    /// `{show}/{asset}/v{version:03}`. Replications that fail are queued in
+    /// the replicator to be retried, and do not fail the publish.
+    #[cfg(feature = "manifest")]
+    pub fn with_replication(
+        mut self,
+        replicator: std::sync::Arc<crate::Replicator>,
+        path: crate::Template,
+    ) -> Self {
+        self.replication = Some((replicator, path));
+        self
+    }
+
+    /// Check that there is enough space for the publish's estimates after the
+    /// pre-publish stage, before the publish stage begins. If there is not,
+    /// then the pre-publish stage is rolled back.
+    #[cfg(feature = "preflight")]
+    pub fn with_preflight(mut self, preflight: crate::Preflight) -> Self {
+        self.preflight = Some(preflight);
+        self
+    }
+}
+
+/// Run a publish with options, starting from an initial context.
+///
+/// If the options have a lock, then it is acquired before the pre-publish
+/// stage, and released after the publish finishes or is rolled back. If the
+/// publish succeeds but the lock cannot be released, then the release error is
+/// returned.
+///
+/// If the options have replication, then the version is replicated after the
+/// post-publish stage succeeds, while the lock is still held. The publish is
+/// already committed by then, so a replication error does not fail the run.
+/// It is returned in the report instead.
+pub async fn run_with_options<P>(
+    publish: &P,
+    context: crate::Context,
+    options: &RunOptions,
+) -> Result<RunReport, crate::Error>
+where
+    P: crate::Publish + Send + Sync + ?Sized,
+{
+    #[cfg(feature = "lock")]
+    if let Some((manager, name)) = &options.lock {
+        let lock = manager.acquire(&name.render(&context)?).await?;
+        let result = run_stages_and_replicate(publish, context, options).await;
+        let released = lock.release().await;
+
+        return match (result, released) {
+            (Ok(_), Err(err)) => Err(err),
+            (result, _) => result,
+        };
+    }
+
+    run_stages_and_replicate(publish, context, options).await
+}
+
+/// The result of a successful [`run_with_options`].
+#[derive(Debug)]
+pub struct RunReport {
+    /// The final context.
+    pub context: crate::Context,
+    /// Which stage and publish last wrote each key, or `None` if the context
+    /// does not track provenance. See [`crate::Context::with_provenance`].
+    pub provenance: Option<std::collections::BTreeMap<String, crate::Provenance>>,
+    /// The results of replicating the version, or `None` if the options do
+    /// not have replication. This is an error if the version path could not
+    /// be rendered, or the replication queue could not be updated.
+    #[cfg(feature = "manifest")]
+    pub replication: Option<Result<Vec<crate::ReplicationResult>, crate::Error>>,
+}
+
+async fn run_stages_and_replicate<P>(
+    publish: &P,
+    context: crate::Context,
+    options: &RunOptions,
+) -> Result<RunReport, crate::Error>
+where
+    P: crate::Publish + Send + Sync + ?Sized,
+{
+    let context = run_stages(publish, context, options).await?;
+
+    #[cfg(feature = "manifest")]
+    let replication = match &options.replication {
+        Some((replicator, path)) => Some(match path.render(&context) {
+            Ok(path) => replicator.replicate(path).await,
+            Err(err) => Err(err),
+        }),
+        None => None,
+    };
+
+    let provenance = context.is_tracking_provenance().then(|| {
+        context
+            .provenance_iter()
+            .map(|(key, provenance)| (key.clone(), provenance.clone()))
+            .collect()
+    });
+
+    Ok(RunReport {
+        context,
+        provenance,
+        #[cfg(feature = "manifest")]
+        replication,
+    })
+}
+
+async fn run_stages<P>(
+    publish: &P,
+    context: crate::Context,
+    options: &RunOptions,
+) -> Result<crate::Context, crate::Error>
+where
+    P: crate::Publish + Send + Sync + ?Sized,
+{
+    let pre_publish_context = match publish
+        .pre_publish(&context)
+        .await
+        .and_then(|ctx| finish_stage(publish, crate::Stage::PrePublish, &context, ctx))
+    {
         Ok(ctx) => ctx,
         Err(err) => {
             if let Err(rollback_err) = publish.rollback_pre_publish(&context).await {
@@ -22,7 +189,23 @@ where
         }
     };
 
-    let publish_context = match publish.publish(&pre_publish_context).await {
+    if let Err(err) = check_preflight(publish, &pre_publish_context, options).await {
+        if let Err(rollback_err) = publish.rollback_pre_publish(&context).await {
+            return Err(crate::Error::new_rollback(
+                "Error while rolling back pre_publish",
+                Box::new(err),
+                Some(Box::new(rollback_err)),
+            ));
+        }
+
+        return Err(err);
+    }
+
+    let publish_context = match publish
+        .publish(&pre_publish_context)
+        .await
+        .and_then(|ctx| finish_stage(publish, crate::Stage::Publish, &pre_publish_context, ctx))
+    {
         Ok(ctx) => ctx,
         Err(err) => {
             if let Err(rollback_err) = publish.rollback_publish(&pre_publish_context).await {
@@ -45,7 +228,11 @@ where
         }
     };
 
-    let post_publish_context = match publish.post_publish(&publish_context).await {
+    let post_publish_context = match publish
+        .post_publish(&publish_context)
+        .await
+        .and_then(|ctx| finish_stage(publish, crate::Stage::PostPublish, &publish_context, ctx))
+    {
         Ok(ctx) => ctx,
         Err(err) => {
             if let Err(rollback_err) = publish.rollback_post_publish(&publish_context).await {
@@ -77,3 +264,55 @@ where
 
     Ok(post_publish_context.into_owned())
 }
+
+/// Check the publish's estimates, if the options have a preflight.
+#[cfg(feature = "preflight")]
+async fn check_preflight<P>(
+    publish: &P,
+    context: &crate::Context,
+    options: &RunOptions,
+) -> Result<(), crate::Error>
+where
+    P: crate::Publish + Send + Sync + ?Sized,
+{
+    match &options.preflight {
+        Some(preflight) => preflight.check_publish(publish, context).await,
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "preflight"))]
+async fn check_preflight<P>(
+    _publish: &P,
+    _context: &crate::Context,
+    _options: &RunOptions,
+) -> Result<(), crate::Error>
+where
+    P: crate::Publish + Send + Sync + ?Sized,
+{
+    Ok(())
+}
+
+/// Check that the stage did not change any frozen keys, and record the
+/// provenance of the keys that it changed.
+///
+/// Changing a frozen key fails the stage, so it is rolled back like any other
+/// stage error.
+fn finish_stage<'a, P>(
+    publish: &P,
+    stage: crate::Stage,
+    input: &crate::Context,
+    output: std::borrow::Cow<'a, crate::Context>,
+) -> Result<std::borrow::Cow<'a, crate::Context>, crate::Error>
+where
+    P: crate::Publish + ?Sized,
+{
+    match output {
+        std::borrow::Cow::Owned(mut output) => {
+            output.enforce_frozen(input)?;
+            output.record_provenance(input, stage, publish.name());
+            Ok(std::borrow::Cow::Owned(output))
+        }
+        output => Ok(output),
+    }
+}
@@ -0,0 +1,68 @@
+/// A field that a publish expects to find in the context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaField {
+    pub key: String,
+    /// The expected type of the value. If this is `None`, then any type is
+    /// accepted.
+    pub value_type: Option<crate::ValueType>,
+    pub required: bool,
+}
+
+impl SchemaField {
+    pub fn new<T: AsRef<str>>(
+        key: T,
+        value_type: Option<crate::ValueType>,
+        required: bool,
+    ) -> Self {
+        Self {
+            key: key.as_ref().to_string(),
+            value_type,
+            required,
+        }
+    }
+}
+
+/// The context that a publish accepts.
+///
+/// Keys that are not in the schema are allowed, so a publish only needs to
+/// describe the keys that it reads.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextSchema {
+    pub fields: Vec<SchemaField>,
+}
+
+impl ContextSchema {
+    pub fn new<T: IntoIterator<Item = SchemaField>>(fields: T) -> Self {
+        Self {
+            fields: fields.into_iter().collect(),
+        }
+    }
+
+    /// Check that the context has all of the required keys, and that the
+    /// values have the expected types.
+    pub fn validate(&self, context: &crate::Context) -> Result<(), crate::Error> {
+        let mut problems = Vec::new();
+
+        for field in &self.fields {
+            match (context.get(&field.key), field.value_type) {
+                (None, _) if field.required => {
+                    problems.push(format!("missing required key {:?}", field.key))
+                }
+                (Some(value), Some(value_type)) if value.value_type() != value_type => problems
+                    .push(format!(
+                        "expected {:?} to be {}, got {}",
+                        field.key,
+                        value_type,
+                        value.value_type()
+                    )),
+                _ => {}
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::Error::new_invalid_context(problems.join(", ")))
+        }
+    }
+}
@@ -0,0 +1,51 @@
+/// The text that is shown in place of a secret.
+const REDACTED: &str = "[REDACTED]";
+
+/// A sensitive string, such as a password or API token.
+///
+/// The `Debug` and `Display` output of a secret is always redacted, so it is
+/// safe to log a context or an error that contains one. The clear text is only
+/// available through [`Secret::expose`].
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Secret {
+    value: std::sync::Arc<str>,
+}
+
+impl Secret {
+    pub fn new<T: AsRef<str>>(value: T) -> Self {
+        Self {
+            value: std::sync::Arc::from(value.as_ref()),
+        }
+    }
+
+    /// Get the clear text of the secret.
+    ///
+    /// Take care not to log or store the returned text.
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Secret").field(&REDACTED).finish()
+    }
+}
+
+impl std::fmt::Display for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
@@ -0,0 +1,131 @@
+/// A SQLite database connection.
+///
+/// The connection can be cloned cheaply, and the clones share it, including
+/// any transaction that is active. Each call runs on the blocking pool, since
+/// SQLite calls block.
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # async fn example() -> Result<(), publish::Error> {
+/// let database = publish::SqliteDatabase::open("/publish/publishes.db").await?;
+/// let transaction = Arc::new(publish::DatabaseTransaction::new(database.clone()));
+///
+/// transaction.begin().await?;
+/// database
+///     .execute("INSERT INTO assets (name) VALUES (?)", vec!["chair".to_string().into()])
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqliteDatabase {
+    connection: std::sync::Arc<std::sync::Mutex<rusqlite::Connection>>,
+}
+
+impl SqliteDatabase {
+    /// Open the database, creating it if it does not exist.
+    pub async fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self, crate::Error> {
+        let path = path.as_ref().to_path_buf();
+        let connection = spawn_blocking(move || rusqlite::Connection::open(path)).await?;
+
+        Ok(Self::from_connection(connection))
+    }
+
+    /// Open a database that only exists in memory, which is useful for tests.
+    pub async fn open_in_memory() -> Result<Self, crate::Error> {
+        let connection = spawn_blocking(rusqlite::Connection::open_in_memory).await?;
+
+        Ok(Self::from_connection(connection))
+    }
+
+    pub fn from_connection(connection: rusqlite::Connection) -> Self {
+        Self {
+            connection: std::sync::Arc::new(std::sync::Mutex::new(connection)),
+        }
+    }
+
+    /// Execute a statement, returning the number of rows that it changed.
+    pub async fn execute<S: Into<String>>(
+        &self,
+        sql: S,
+        params: Vec<rusqlite::types::Value>,
+    ) -> Result<usize, crate::Error> {
+        let sql = sql.into();
+
+        self.with_connection(move |connection| {
+            connection.execute(&sql, rusqlite::params_from_iter(params))
+        })
+        .await
+    }
+
+    /// Execute statements separated by semicolons, such as a schema.
+    pub async fn execute_batch<S: Into<String>>(&self, sql: S) -> Result<(), crate::Error> {
+        let sql = sql.into();
+
+        self.with_connection(move |connection| connection.execute_batch(&sql))
+            .await
+    }
+
+    /// Run a query, returning the values of each row.
+    pub async fn query<S: Into<String>>(
+        &self,
+        sql: S,
+        params: Vec<rusqlite::types::Value>,
+    ) -> Result<Vec<Vec<rusqlite::types::Value>>, crate::Error> {
+        let sql = sql.into();
+
+        self.with_connection(move |connection| {
+            let mut statement = connection.prepare(&sql)?;
+            let columns = statement.column_count();
+            let rows = statement
+                .query_map(rusqlite::params_from_iter(params), |row| {
+                    (0..columns).map(|index| row.get(index)).collect()
+                })?
+                .collect();
+
+            rows
+        })
+        .await
+    }
+
+    /// Run the function with the connection on the blocking pool.
+    pub async fn with_connection<T, F>(&self, f: F) -> Result<T, crate::Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut rusqlite::Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+
+        spawn_blocking(move || {
+            let mut connection = crate::util::lock(&connection);
+            f(&mut connection)
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::DatabaseBackend for SqliteDatabase {
+    async fn begin(&self) -> Result<(), crate::Error> {
+        self.execute_batch("BEGIN").await
+    }
+
+    async fn commit(&self) -> Result<(), crate::Error> {
+        self.execute_batch("COMMIT").await
+    }
+
+    async fn rollback(&self) -> Result<(), crate::Error> {
+        self.execute_batch("ROLLBACK").await
+    }
+}
+
+async fn spawn_blocking<T, F>(f: F) -> Result<T, crate::Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> rusqlite::Result<T> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result.map_err(|err| crate::Error::new_database(err.to_string())),
+        Err(err) => Err(crate::Error::new_runtime(err.to_string())),
+    }
+}
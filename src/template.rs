@@ -0,0 +1,371 @@
+/// How a template field formats its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateFormat {
+    /// Pad the value with zeros to at least the width, such as `{version:03}`.
+    ZeroPad(usize),
+    /// Convert the value to upper case, such as `{asset:upper}`.
+    Upper,
+    /// Convert the value to lower case, such as `{asset:lower}`.
+    Lower,
+}
+
+impl std::fmt::Display for TemplateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZeroPad(width) => write!(f, "0{}", width),
+            Self::Upper => write!(f, "upper"),
+            Self::Lower => write!(f, "lower"),
+        }
+    }
+}
+
+impl std::str::FromStr for TemplateFormat {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "upper" => Ok(Self::Upper),
+            "lower" => Ok(Self::Lower),
+            _ => match s.strip_prefix('0').map(str::parse::<usize>) {
+                Some(Ok(width)) => Ok(Self::ZeroPad(width)),
+                _ => Err(crate::Error::new_template(format!(
+                    "Unknown format spec {:?}",
+                    s
+                ))),
+            },
+        }
+    }
+}
+
+/// A field in a template, such as `{version:03=1}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateField {
+    /// The context key. Dotted keys such as `asset.name` are looked up as is
+    /// first, then as a path into object values.
+    pub key: String,
+    pub format: Option<TemplateFormat>,
+    /// The text to render if the key is missing or `None`.
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(TemplateField),
+}
+
+/// A string template that is rendered from context values.
+///
+/// Fields are written as `{key}`, with an optional format spec and default,
+/// such as `{version:03}`, `{asset:lower}`, `{step=main}`, or `{version:03=1}`.
+/// Literal braces are escaped as `{{` and `}}`.
+///
+/// ```
+/// let template = publish::Template::new("{show}/{asset}/v{version:03}").unwrap();
+/// let context = publish::Context::new([
+///     ("show".to_string(), publish::Value::String("demo".to_string())),
+///     ("asset".to_string(), publish::Value::String("chair".to_string())),
+///     ("version".to_string(), publish::Value::Integer(7)),
+/// ]);
+///
+/// assert_eq!(template.render(&context).unwrap(), "demo/chair/v007");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn new(source: &str) -> Result<Self, crate::Error> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => {
+                                return Err(crate::Error::new_template(format!(
+                                    "Unclosed field in template {:?}",
+                                    source
+                                )))
+                            }
+                            Some(c) => field.push(c),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    segments.push(Segment::Field(parse_field(source, &field)?));
+                }
+                '}' => {
+                    return Err(crate::Error::new_template(format!(
+                        "Unmatched '}}' in template {:?}",
+                        source
+                    )))
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The fields in the template, in the order they appear.
+    pub fn fields(&self) -> impl Iterator<Item = &TemplateField> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Field(field) => Some(field),
+            Segment::Literal(_) => None,
+        })
+    }
+
+    /// Render the template from the context.
+    ///
+    /// All of the keys that are missing from the context and have no default
+    /// are reported together as an [`crate::Error::MissingTemplateKeys`].
+    /// Secrets are never rendered.
+    pub fn render(&self, context: &crate::Context) -> Result<String, crate::Error> {
+        let mut output = String::new();
+        let mut missing = Vec::new();
+
+        for segment in &self.segments {
+            let field = match segment {
+                Segment::Literal(literal) => {
+                    output.push_str(literal);
+                    continue;
+                }
+                Segment::Field(field) => field,
+            };
+
+            let text = match (lookup(context, &field.key), &field.default) {
+                (Some(value), _) => value_to_string(&field.key, value)?,
+                (None, Some(default)) => default.clone(),
+                (None, None) => {
+                    if !missing.contains(&field.key) {
+                        missing.push(field.key.clone());
+                    }
+
+                    continue;
+                }
+            };
+
+            output.push_str(&apply_format(text, field.format));
+        }
+
+        if missing.is_empty() {
+            Ok(output)
+        } else {
+            Err(crate::Error::new_missing_template_keys(
+                &self.source,
+                missing,
+            ))
+        }
+    }
+
+    /// Parse a rendered string back into the fields of the template.
+    ///
+    /// Zero padded fields are parsed as integers, and all other fields as
+    /// strings. Fields do not match across a `/`, and when a string could be
+    /// split in more than one way, earlier fields match as little as possible.
+    /// A key that appears more than once must have the same value each time.
+    pub fn parse(&self, text: &str) -> Result<crate::Context, crate::Error> {
+        let mut fields = Vec::new();
+
+        if !match_segments(&self.segments, text, &mut fields) {
+            return Err(crate::Error::new_template(format!(
+                "{:?} does not match template {:?}",
+                text, self.source
+            )));
+        }
+
+        let mut context = crate::Context::default();
+
+        for (field, value) in fields {
+            let value = match field.format {
+                Some(TemplateFormat::ZeroPad(_)) => match value.parse::<i64>() {
+                    Ok(value) => crate::Value::Integer(value),
+                    Err(_) => crate::Value::String(value.to_string()),
+                },
+                _ => crate::Value::String(value.to_string()),
+            };
+
+            context.set(&field.key, value);
+        }
+
+        Ok(context)
+    }
+}
+
+impl std::str::FromStr for Template {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl std::fmt::Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn parse_field(source: &str, field: &str) -> Result<TemplateField, crate::Error> {
+    let (field, default) = match field.split_once('=') {
+        Some((field, default)) => (field, Some(default.to_string())),
+        None => (field, None),
+    };
+    let (key, format) = match field.split_once(':') {
+        Some((key, format)) => (key, Some(format.parse()?)),
+        None => (field, None),
+    };
+
+    if key.is_empty() {
+        return Err(crate::Error::new_template(format!(
+            "Empty field in template {:?}",
+            source
+        )));
+    }
+
+    Ok(TemplateField {
+        key: key.to_string(),
+        format,
+        default,
+    })
+}
+
+/// Look up the key in the context, then as a dotted path into object values.
+/// `None` values are treated as missing.
+fn lookup<'a>(context: &'a crate::Context, key: &str) -> Option<&'a crate::Value> {
+    let value = match context.get(key) {
+        Some(value) => Some(value),
+        None => {
+            let mut parts = key.split('.');
+            let mut value = context.get(parts.next()?);
+
+            for part in parts {
+                value = match value {
+                    Some(crate::Value::Object(object)) => object.get(part),
+                    _ => None,
+                };
+            }
+
+            value
+        }
+    };
+
+    match value {
+        Some(crate::Value::None) => None,
+        value => value,
+    }
+}
+
+fn value_to_string(key: &str, value: &crate::Value) -> Result<String, crate::Error> {
+    match value {
+        crate::Value::Boolean(value) => Ok(value.to_string()),
+        crate::Value::Integer(value) => Ok(value.to_string()),
+        crate::Value::Float(value) => Ok(value.to_string()),
+        crate::Value::String(value) => Ok(value.clone()),
+        value => Err(crate::Error::new_template(format!(
+            "Cannot render {} value for key {:?}",
+            value.value_type(),
+            key
+        ))),
+    }
+}
+
+fn apply_format(text: String, format: Option<TemplateFormat>) -> String {
+    match format {
+        None => text,
+        Some(TemplateFormat::Upper) => text.to_uppercase(),
+        Some(TemplateFormat::Lower) => text.to_lowercase(),
+        Some(TemplateFormat::ZeroPad(width)) => {
+            let (sign, digits) = match text.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", text.as_str()),
+            };
+            let padding = width.saturating_sub(sign.len() + digits.chars().count());
+
+            format!("{}{}{}", sign, "0".repeat(padding), digits)
+        }
+    }
+}
+
+/// Match the segments against the text, collecting the value of each field.
+fn match_segments<'a, 't>(
+    segments: &'a [Segment],
+    text: &'t str,
+    fields: &mut Vec<(&'a TemplateField, &'t str)>,
+) -> bool {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return text.is_empty(),
+    };
+
+    match segment {
+        Segment::Literal(literal) => match text.strip_prefix(literal.as_str()) {
+            Some(text) => match_segments(rest, text, fields),
+            None => false,
+        },
+        Segment::Field(field) => {
+            let is_integer = matches!(field.format, Some(TemplateFormat::ZeroPad(_)));
+
+            for (index, c) in text.char_indices() {
+                let valid = if is_integer {
+                    c.is_ascii_digit() || (index == 0 && c == '-')
+                } else {
+                    c != '/'
+                };
+
+                if !valid {
+                    break;
+                }
+
+                let end = index + c.len_utf8();
+                let (value, remainder) = text.split_at(end);
+                let conflicts = fields
+                    .iter()
+                    .any(|(other, other_value)| other.key == field.key && *other_value != value);
+
+                if !conflicts {
+                    fields.push((field, value));
+
+                    if match_segments(rest, remainder, fields) {
+                        return true;
+                    }
+
+                    fields.pop();
+                }
+            }
+
+            false
+        }
+    }
+}
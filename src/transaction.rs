@@ -0,0 +1,15 @@
+/// A set of changes that can be made permanent, or undone.
+///
+/// Publishes use transactions to make the changes of a stage permanent, and
+/// roll them back if a later stage fails. For example, a publish could write
+/// files with a [`crate::FilesystemTransaction`] in the publish stage, roll it
+/// back in `rollback_publish`, and commit it in `post_publish`.
+#[async_trait::async_trait]
+pub trait Transaction: Send + Sync {
+    /// Make the changes permanent, and discard anything that was kept to undo
+    /// them.
+    async fn commit(&self) -> Result<(), crate::Error>;
+
+    /// Undo the changes, in the reverse order that they were made.
+    async fn rollback(&self) -> Result<(), crate::Error>;
+}
@@ -0,0 +1,64 @@
+/// Lock the mutex, ignoring poisoning, since the data behind the crate's
+/// mutexes is always left consistent.
+pub(crate) fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A path beside the given path that no other process or call uses, such as
+/// `chair.usd.1234.0.tmp`.
+#[cfg(feature = "tokio")]
+pub(crate) fn unique_path(path: &std::path::Path, extension: &str) -> std::path::PathBuf {
+    static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let counter = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let mut unique = path.as_os_str().to_os_string();
+    unique.push(format!(".{}.{}.{}", std::process::id(), counter, extension));
+
+    unique.into()
+}
+
+/// The files under the root, including those in subdirectories, sorted.
+#[cfg(feature = "manifest")]
+pub(crate) async fn list_files(
+    root: &std::path::Path,
+) -> Result<Vec<std::path::PathBuf>, crate::Error> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// The name of the current user, or `unknown` if it is not set.
+#[cfg(any(feature = "lock", feature = "sqlite"))]
+pub(crate) fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// The device and inode of the file, which are the same for all of its links,
+/// or `None` if they are not available.
+#[cfg(all(any(feature = "preflight", feature = "sqlite"), unix))]
+pub(crate) fn file_id(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(all(any(feature = "preflight", feature = "sqlite"), not(unix)))]
+pub(crate) fn file_id(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
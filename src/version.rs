@@ -0,0 +1,227 @@
+/// A version directory that was reserved by a [`VersionAllocator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReservation {
+    pub version: i64,
+    pub path: std::path::PathBuf,
+}
+
+/// Allocates version numbers for a versioned path template, such as
+/// `{root}/{asset}/v{version:03}`.
+///
+/// A version is reserved by creating its directory, which fails if the
+/// directory already exists. This makes the reservation atomic, so two
+/// publishes of the same asset that run at once get different versions, even
+/// from different machines on a shared filesystem.
+///
+/// The allocator remembers the versions that it reserved. A publish can
+/// reserve a version in `pre_publish`, keep the reservation, and release it in
+/// `rollback_pre_publish`. Only the reserved version is released, so other
+/// runs that share the allocator keep their versions:
+///
+/// ```no_run
+/// # use std::borrow::Cow;
+/// struct AssetPublish {
+///     versions: std::sync::Arc<publish::VersionAllocator>,
+///     reservation: std::sync::Mutex<Option<publish::VersionReservation>>,
+/// }
+///
+/// #[async_trait::async_trait]
+/// impl publish::Publish for AssetPublish {
+///     async fn pre_publish<'a>(
+///         &self,
+///         context: &'a publish::Context,
+///     ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+///         let reservation = self.versions.reserve(context).await?;
+///         let mut context = context.to_owned();
+///         context.set("version", publish::Value::Integer(reservation.version));
+///         context.freeze("version");
+///         *self.reservation.lock().unwrap() = Some(reservation);
+///
+///         Ok(Cow::Owned(context))
+///     }
+///
+///     async fn rollback_pre_publish(
+///         &self,
+///         _context: &publish::Context,
+///     ) -> Result<(), publish::Error> {
+///         let reservation = self.reservation.lock().unwrap().take();
+///
+///         match reservation {
+///             Some(reservation) => self.versions.release(&reservation).await,
+///             None => Ok(()),
+///         }
+///     }
+/// #
+/// #   async fn publish<'a>(
+/// #       &self,
+/// #       context: &'a publish::Context,
+/// #   ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+/// #       Ok(Cow::Borrowed(context))
+/// #   }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct VersionAllocator {
+    template: crate::Template,
+    key: String,
+    reservations: std::sync::Mutex<Vec<VersionReservation>>,
+}
+
+impl VersionAllocator {
+    /// Create an allocator for the template, which stores the version in the
+    /// `version` key.
+    ///
+    /// The version must be in the last component of the path.
+    pub fn new(template: crate::Template) -> Self {
+        Self {
+            template,
+            key: "version".to_string(),
+            reservations: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Use a different key for the version.
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key = key.to_string();
+        self
+    }
+
+    pub fn template(&self) -> &crate::Template {
+        &self.template
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Render the path of the version.
+    ///
+    /// Any version already in the context is replaced with the given version.
+    pub fn path(
+        &self,
+        context: &crate::Context,
+        version: i64,
+    ) -> Result<std::path::PathBuf, crate::Error> {
+        let mut context = context.clone();
+        context.set(&self.key, crate::Value::Integer(version));
+
+        Ok(self.template.render(&context)?.into())
+    }
+
+    /// Find the versions that already exist on disk, in ascending order.
+    pub async fn existing_versions(
+        &self,
+        context: &crate::Context,
+    ) -> Result<Vec<i64>, crate::Error> {
+        let parent = self.parent(context)?;
+        let mut entries = match tokio::fs::read_dir(&parent).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut versions = Vec::new();
+
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+            let name = match file_name.to_str() {
+                Some(name) => name,
+                None => continue,
+            };
+
+            // The entry is a version if rendering the template with one of the
+            // numbers in its name gives the entry back.
+            for number in name
+                .split(|c: char| !c.is_ascii_digit())
+                .filter_map(|digits| digits.parse::<i64>().ok())
+            {
+                if self.path(context, number)?.file_name() == Some(file_name.as_os_str()) {
+                    versions.push(number);
+                    break;
+                }
+            }
+        }
+
+        versions.sort_unstable();
+
+        Ok(versions)
+    }
+
+    /// Reserve the next version after the latest existing version, starting
+    /// at 1.
+    ///
+    /// If another publish reserves the same version first, then the following
+    /// versions are tried until one is reserved.
+    pub async fn reserve(
+        &self,
+        context: &crate::Context,
+    ) -> Result<VersionReservation, crate::Error> {
+        tokio::fs::create_dir_all(self.parent(context)?).await?;
+
+        let mut version = match self.existing_versions(context).await?.last() {
+            Some(latest) => latest + 1,
+            None => 1,
+        };
+
+        loop {
+            let path = self.path(context, version)?;
+
+            match tokio::fs::create_dir(&path).await {
+                Ok(()) => {
+                    let reservation = VersionReservation { version, path };
+                    crate::util::lock(&self.reservations).push(reservation.clone());
+
+                    return Ok(reservation);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => version += 1,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Release a version that was reserved by [`VersionAllocator::reserve`].
+    ///
+    /// Reservations that were already released are ignored. The version
+    /// directory is only removed if it is empty, so anything that was
+    /// published into it must be rolled back first.
+    pub async fn release(&self, reservation: &VersionReservation) -> Result<(), crate::Error> {
+        {
+            let mut reservations = crate::util::lock(&self.reservations);
+
+            match reservations.iter().position(|other| other == reservation) {
+                Some(index) => reservations.remove(index),
+                None => return Ok(()),
+            };
+        }
+
+        match tokio::fs::remove_dir(&reservation.path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The versions that are currently reserved.
+    pub fn reservations(&self) -> Vec<VersionReservation> {
+        crate::util::lock(&self.reservations).clone()
+    }
+
+    fn parent(&self, context: &crate::Context) -> Result<std::path::PathBuf, crate::Error> {
+        let first = self.path(context, 0)?;
+        let second = self.path(context, 1)?;
+
+        match (first.parent(), second.parent()) {
+            (Some(parent), Some(other)) if first != second && parent == other => {
+                if parent.as_os_str().is_empty() {
+                    Ok(".".into())
+                } else {
+                    Ok(parent.to_path_buf())
+                }
+            }
+            _ => Err(crate::Error::new_template(format!(
+                "The {:?} key must be in the last path component of template {:?}",
+                self.key,
+                self.template.source()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "archive")]
+
+use std::io::Read;
+
+use publish::Transaction;
+
+async fn write_publish(root: &std::path::Path) -> publish::Manifest {
+    let transaction = publish::FilesystemTransaction::new();
+    transaction
+        .write(root.join("textures/wood.png"), "wood")
+        .await
+        .unwrap();
+    transaction
+        .write(root.join("model.usd"), "model")
+        .await
+        .unwrap();
+    let manifest = publish::ManifestBuilder::new(root)
+        .add_files(transaction.written_files())
+        .build()
+        .await
+        .unwrap();
+    manifest.save(&transaction, root).await.unwrap();
+    transaction
+        .write(root.join("notes.txt"), "notes")
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+
+    manifest
+}
+
+fn tar_entries(reader: impl Read) -> Vec<(String, u64, String)> {
+    tar::Archive::new(reader)
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            let mtime = entry.header().mtime().unwrap();
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+
+            (path, mtime, contents)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_archive_formats_are_deterministic() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("v001");
+    write_publish(&root).await;
+    let transaction = publish::ArchiveTransaction::new();
+
+    for format in [
+        publish::ArchiveFormat::Tar,
+        publish::ArchiveFormat::TarGz,
+        publish::ArchiveFormat::Zip,
+    ] {
+        let first = dir.path().join(format!("first.{}", format));
+        let second = dir.path().join(format!("second.{}", format));
+        transaction
+            .package_dir(&root, &first, format)
+            .await
+            .unwrap();
+
+        // Changing the modification times does not change the archive.
+        let file = std::fs::File::options()
+            .write(true)
+            .open(root.join("model.usd"))
+            .unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+
+        transaction
+            .package_dir(&root, &second, format)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read(&first).unwrap(),
+            std::fs::read(&second).unwrap()
+        );
+    }
+
+    transaction.commit().await.unwrap();
+
+    let entries = tar_entries(std::fs::File::open(dir.path().join("first.tar")).unwrap());
+
+    assert_eq!(
+        entries,
+        vec![
+            ("manifest.json".into(), 315_532_800, entries[0].2.clone()),
+            ("model.usd".into(), 315_532_800, "model".into()),
+            ("notes.txt".into(), 315_532_800, "notes".into()),
+            ("textures/wood.png".into(), 315_532_800, "wood".into()),
+        ]
+    );
+    assert_eq!(
+        tar_entries(flate2::read::GzDecoder::new(
+            std::fs::File::open(dir.path().join("first.tar.gz")).unwrap()
+        )),
+        entries
+    );
+
+    let mut zip =
+        zip::ZipArchive::new(std::fs::File::open(dir.path().join("first.zip")).unwrap()).unwrap();
+    let names = zip.file_names().map(String::from).collect::<Vec<_>>();
+    let mut contents = String::new();
+    zip.by_name("textures/wood.png")
+        .unwrap()
+        .read_to_string(&mut contents)
+        .unwrap();
+
+    assert_eq!(names.len(), 4);
+    assert_eq!(contents, "wood");
+}
+
+#[tokio::test]
+async fn test_archive_manifest_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("v001");
+    let manifest = write_publish(&root).await;
+    let archive = dir.path().join("chair.tar");
+    let transaction = publish::ArchiveTransaction::new();
+
+    transaction
+        .package_manifest(&root, &manifest, &archive, publish::ArchiveFormat::Tar)
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+
+    let names = tar_entries(std::fs::File::open(&archive).unwrap())
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        names,
+        vec!["manifest.json", "model.usd", "textures/wood.png"]
+    );
+}
+
+#[tokio::test]
+async fn test_archive_rollback() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("v001");
+    write_publish(&root).await;
+    let deliveries = dir.path().join("deliveries");
+    std::fs::create_dir(&deliveries).unwrap();
+    std::fs::write(deliveries.join("chair.zip"), "previous").unwrap();
+    let transaction = publish::ArchiveTransaction::new();
+
+    transaction
+        .package_dir(
+            &root,
+            deliveries.join("chair.zip"),
+            publish::ArchiveFormat::Zip,
+        )
+        .await
+        .unwrap();
+    transaction
+        .package_dir(
+            &root,
+            deliveries.join("new/chair.tgz"),
+            publish::ArchiveFormat::TarGz,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        transaction.written_files(),
+        vec![
+            deliveries.join("chair.zip"),
+            deliveries.join("new/chair.tgz")
+        ]
+    );
+
+    transaction.rollback().await.unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(deliveries.join("chair.zip")).unwrap(),
+        "previous"
+    );
+    assert_eq!(std::fs::read_dir(&deliveries).unwrap().count(), 1);
+}
+
+#[test]
+fn test_archive_format() {
+    assert_eq!(
+        publish::ArchiveFormat::from_path("chair.tar.gz"),
+        Some(publish::ArchiveFormat::TarGz)
+    );
+    assert_eq!(
+        publish::ArchiveFormat::from_path("/deliveries/chair.zip"),
+        Some(publish::ArchiveFormat::Zip)
+    );
+    assert_eq!(publish::ArchiveFormat::from_path("chair.usd"), None);
+    assert_eq!(
+        "tgz".parse::<publish::ArchiveFormat>().unwrap(),
+        publish::ArchiveFormat::TarGz
+    );
+    assert!(matches!(
+        "rar".parse::<publish::ArchiveFormat>(),
+        Err(publish::Error::Archive(_))
+    ));
+}
@@ -0,0 +1,159 @@
+#![cfg(feature = "tokio")]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[derive(Default)]
+struct Counter {
+    running: AtomicUsize,
+    max_running: AtomicUsize,
+}
+
+struct TestPublish {
+    counter: Arc<Counter>,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for TestPublish {
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<std::borrow::Cow<'a, publish::Context>, publish::Error> {
+        let running = self.counter.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.counter
+            .max_running
+            .fetch_max(running, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        self.counter.running.fetch_sub(1, Ordering::SeqCst);
+
+        if context.get("panic") == Some(&publish::Value::Boolean(true)) {
+            panic!("Publish panicked");
+        }
+
+        match context.get("fail") {
+            Some(publish::Value::Boolean(true)) => {
+                Err(publish::Error::new_publish("Publish failed", None))
+            }
+            _ => {
+                let mut context = context.to_owned();
+                context.set("published", publish::Value::Boolean(true));
+
+                Ok(std::borrow::Cow::Owned(context))
+            }
+        }
+    }
+
+    async fn rollback_publish(&self, context: &publish::Context) -> Result<(), publish::Error> {
+        match context.get("fail_rollback") {
+            Some(publish::Value::Boolean(true)) => {
+                Err(publish::Error::new_publish("Rollback failed", None))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn job(counter: &Arc<Counter>, values: &[(&str, bool)]) -> publish::Job<TestPublish> {
+    publish::Job::new(
+        TestPublish {
+            counter: counter.clone(),
+        },
+        publish::Context::new(
+            values
+                .iter()
+                .map(|(key, value)| (key.to_string(), publish::Value::Boolean(*value))),
+        ),
+    )
+}
+
+#[tokio::test]
+async fn test_run_many_success() {
+    let counter = Arc::new(Counter::default());
+    let jobs = (0..10).map(|_| job(&counter, &[]));
+
+    let report = publish::run_many(jobs, 3).await;
+
+    assert!(report.is_success());
+    assert_eq!(report.len(), 10);
+    assert_eq!(report.succeeded().count(), 10);
+    assert!(counter.max_running.load(Ordering::SeqCst) <= 3);
+
+    for (index, (job_index, context)) in report.succeeded().enumerate() {
+        assert_eq!(index, job_index);
+        assert_eq!(
+            context.get("published").unwrap(),
+            &publish::Value::Boolean(true)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_run_many_failure_is_isolated() {
+    let counter = Arc::new(Counter::default());
+    let jobs = vec![
+        job(&counter, &[]),
+        job(&counter, &[("fail", true)]),
+        job(&counter, &[("fail", true), ("fail_rollback", true)]),
+        job(&counter, &[]),
+    ];
+
+    let report = publish::run_many(jobs, 2).await;
+
+    assert!(!report.is_success());
+    assert_eq!(
+        report
+            .succeeded()
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>(),
+        vec![0, 3]
+    );
+    assert_eq!(
+        report.failed().map(|(index, _)| index).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert_eq!(
+        report
+            .rollback_failed()
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>(),
+        vec![2]
+    );
+    assert!(matches!(
+        report.jobs[1].outcome,
+        publish::JobOutcome::RolledBack(publish::Error::Publish { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_run_many_panic_is_isolated() {
+    let counter = Arc::new(Counter::default());
+    let jobs = vec![
+        job(&counter, &[]),
+        job(&counter, &[("panic", true)]),
+        job(&counter, &[]),
+    ];
+
+    let report = publish::run_many(jobs, 3).await;
+
+    assert_eq!(
+        report
+            .succeeded()
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>(),
+        vec![0, 2]
+    );
+    assert!(matches!(
+        report.jobs[1].outcome,
+        publish::JobOutcome::RollbackFailed(publish::Error::Runtime(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_run_many_empty_success() {
+    let report = publish::run_many(Vec::<publish::Job<TestPublish>>::new(), 0).await;
+
+    assert!(report.is_empty());
+    assert!(report.is_success());
+}
@@ -0,0 +1,147 @@
+#![cfg(all(feature = "manifest", unix))]
+
+use publish::Transaction;
+
+const MODEL_CHECKSUM: &str = "9372c470eeadd5ecd9c3c74c2b3cb633f8e2f2fad799250a0f70d652b6b825e4";
+
+fn setup() -> (tempfile::TempDir, publish::BlobStore, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = publish::BlobStore::new(dir.path().join("store"));
+    let source = dir.path().join("model.abc");
+    std::fs::write(&source, "model").unwrap();
+
+    (dir, store, source)
+}
+
+#[tokio::test]
+async fn test_blob_transaction_deduplicates() {
+    let (dir, store, source) = setup();
+    let first = publish::BlobTransaction::new(store.clone());
+    let second = publish::BlobTransaction::new(store.clone());
+
+    let checksum = first
+        .add(&source, dir.path().join("v001/model.abc"))
+        .await
+        .unwrap();
+    second
+        .add(&source, dir.path().join("v002/model.abc"))
+        .await
+        .unwrap();
+    first.commit().await.unwrap();
+    second.commit().await.unwrap();
+
+    assert_eq!(checksum, MODEL_CHECKSUM);
+    assert_eq!(
+        store.object_path(&checksum),
+        dir.path().join("store/objects/93").join(MODEL_CHECKSUM)
+    );
+    assert_eq!(store.checksums().await.unwrap(), vec![checksum.clone()]);
+    assert_eq!(store.ref_count(&checksum).await.unwrap(), Some(2));
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("v002/model.abc")).unwrap(),
+        "model"
+    );
+    assert!(std::fs::metadata(dir.path().join("v001/model.abc"))
+        .unwrap()
+        .permissions()
+        .readonly());
+    assert_eq!(
+        second.written_files(),
+        vec![dir.path().join("v002/model.abc")]
+    );
+}
+
+#[tokio::test]
+async fn test_blob_transaction_rollback() {
+    let (dir, store, source) = setup();
+    let first = publish::BlobTransaction::new(store.clone());
+    let checksum = first
+        .add(&source, dir.path().join("v001/model.abc"))
+        .await
+        .unwrap();
+    first.commit().await.unwrap();
+
+    // Rolling back a transaction that reused an object keeps it.
+    let second = publish::BlobTransaction::new(store.clone());
+    second
+        .add(&source, dir.path().join("v002/model.abc"))
+        .await
+        .unwrap();
+    second.rollback().await.unwrap();
+
+    assert!(!dir.path().join("v002").exists());
+    assert_eq!(store.ref_count(&checksum).await.unwrap(), Some(1));
+
+    // Rolling back a transaction that created an object removes it.
+    std::fs::write(&source, "other").unwrap();
+    let third = publish::BlobTransaction::new(store.clone());
+    let other = third
+        .add(&source, dir.path().join("v003/model.abc"))
+        .await
+        .unwrap();
+    third.rollback().await.unwrap();
+
+    assert!(!store.contains(&other).await.unwrap());
+    assert_eq!(store.checksums().await.unwrap(), vec![checksum]);
+}
+
+#[tokio::test]
+async fn test_blob_transaction_remove() {
+    let (dir, store, source) = setup();
+    let first = publish::BlobTransaction::new(store.clone());
+    let checksum = first
+        .add(&source, dir.path().join("v001/model.abc"))
+        .await
+        .unwrap();
+    first
+        .add(&source, dir.path().join("v002/model.abc"))
+        .await
+        .unwrap();
+    first.commit().await.unwrap();
+
+    let remove = publish::BlobTransaction::new(store.clone());
+    remove
+        .remove(dir.path().join("v001/model.abc"))
+        .await
+        .unwrap();
+    remove.rollback().await.unwrap();
+
+    assert_eq!(store.ref_count(&checksum).await.unwrap(), Some(2));
+
+    remove
+        .remove(dir.path().join("v001/model.abc"))
+        .await
+        .unwrap();
+    remove.commit().await.unwrap();
+
+    assert_eq!(store.ref_count(&checksum).await.unwrap(), Some(1));
+
+    remove
+        .remove(dir.path().join("v002/model.abc"))
+        .await
+        .unwrap();
+    remove.commit().await.unwrap();
+
+    assert_eq!(store.ref_count(&checksum).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_remove_all_unreferenced() {
+    let (dir, store, source) = setup();
+    let transaction = publish::BlobTransaction::new(store.clone());
+    let checksum = transaction
+        .add(&source, dir.path().join("v001/model.abc"))
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+
+    assert!(store.remove_all_unreferenced().await.unwrap().is_empty());
+
+    std::fs::remove_file(dir.path().join("v001/model.abc")).unwrap();
+
+    assert_eq!(
+        store.remove_all_unreferenced().await.unwrap(),
+        vec![checksum.clone()]
+    );
+    assert!(!store.contains(&checksum).await.unwrap());
+}
@@ -0,0 +1,209 @@
+#![cfg(feature = "sqlite")]
+
+use std::{borrow::Cow, time::Duration};
+
+use publish::Transaction;
+
+struct CatalogPublish {
+    transaction: publish::CatalogTransaction,
+    fail: bool,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for CatalogPublish {
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        let record = publish::CatalogRecord::new("model", context.clone())
+            .with_version(1)
+            .with_files(["/publish/chair/v001/chair.abc"]);
+        self.transaction.insert(&record).await?;
+
+        Ok(Cow::Borrowed(context))
+    }
+
+    async fn rollback_publish(&self, _context: &publish::Context) -> Result<(), publish::Error> {
+        self.transaction.rollback().await
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        if self.fail {
+            return Err(publish::Error::new_publish("post publish failed", None));
+        }
+
+        self.transaction.commit().await?;
+
+        Ok(Cow::Borrowed(context))
+    }
+}
+
+fn asset_context(asset: &str) -> publish::Context {
+    publish::Context::new([
+        (
+            "asset".to_string(),
+            publish::Value::String(asset.to_string()),
+        ),
+        (
+            "password".to_string(),
+            publish::Value::Secret(publish::Secret::new("hunter2")),
+        ),
+    ])
+}
+
+async fn insert(catalog: &publish::Catalog, record: publish::CatalogRecord) -> i64 {
+    let transaction = publish::CatalogTransaction::new(catalog.clone());
+    let id = transaction.insert(&record).await.unwrap();
+    transaction.commit().await.unwrap();
+
+    id
+}
+
+#[tokio::test]
+async fn test_catalog_transaction_with_runner() {
+    let catalog = publish::Catalog::open_in_memory().await.unwrap();
+
+    for fail in [true, false] {
+        let publish = CatalogPublish {
+            transaction: publish::CatalogTransaction::new(catalog.clone()),
+            fail,
+        };
+
+        let result = publish::run_with_context(&publish, asset_context("chair")).await;
+
+        assert_eq!(result.is_ok(), !fail);
+    }
+
+    let entries = catalog.find(&publish::CatalogQuery::new()).await.unwrap();
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].record.name, "model");
+    assert_eq!(entries[0].record.version, Some(1));
+    assert_eq!(
+        entries[0].record.files,
+        vec![std::path::PathBuf::from("/publish/chair/v001/chair.abc")]
+    );
+    assert_eq!(
+        entries[0].record.context.get("asset"),
+        Some(&publish::Value::String("chair".to_string()))
+    );
+    assert!(!entries[0].record.context.contains_key("password"));
+    assert!(catalog.get(entries[0].id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_catalog_uncommitted_entries_are_hidden() {
+    let catalog = publish::Catalog::open_in_memory().await.unwrap();
+    let transaction = publish::CatalogTransaction::new(catalog.clone());
+
+    let id = transaction
+        .insert(&publish::CatalogRecord::new(
+            "model",
+            asset_context("chair"),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(transaction.inserted(), vec![id]);
+    assert!(catalog.get(id).await.unwrap().is_none());
+
+    transaction.commit().await.unwrap();
+
+    assert!(transaction.inserted().is_empty());
+    assert!(catalog.get(id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_catalog_latest() {
+    let catalog = publish::Catalog::open_in_memory().await.unwrap();
+
+    for (asset, version) in [("chair", 2), ("chair", 3), ("table", 7), ("chair", 1)] {
+        insert(
+            &catalog,
+            publish::CatalogRecord::new("model", asset_context(asset)).with_version(version),
+        )
+        .await;
+    }
+
+    let query = publish::CatalogQuery::new()
+        .with_name("model")
+        .with_context("asset", publish::Value::String("chair".to_string()));
+    let latest = catalog.latest(&query).await.unwrap().unwrap();
+    let versions = catalog
+        .find(&query)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.record.version)
+        .collect::<Vec<_>>();
+
+    assert_eq!(latest.record.version, Some(3));
+    assert_eq!(versions, vec![Some(1), Some(2), Some(3)]);
+    assert!(catalog
+        .latest(&publish::CatalogQuery::new().with_name("rig"))
+        .await
+        .unwrap()
+        .is_none());
+    assert!(matches!(
+        catalog
+            .find(&publish::CatalogQuery::new().with_context(
+                "password",
+                publish::Value::Secret(publish::Secret::new("hunter2"))
+            ))
+            .await,
+        Err(publish::Error::Database(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_catalog_by_tag_and_date() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("catalog.db");
+    let catalog = publish::Catalog::open(&path).await.unwrap();
+    let start = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+    for (version, tags) in [
+        (1, vec!["approved"]),
+        (2, vec![]),
+        (3, vec!["approved", "wip"]),
+    ] {
+        let finished = start + Duration::from_secs(version as u64 * 60);
+        insert(
+            &catalog,
+            publish::CatalogRecord::new("model", asset_context("chair"))
+                .with_version(version)
+                .with_tags(tags)
+                .with_user("artist")
+                .with_started(finished - Duration::from_secs(30))
+                .with_finished(finished),
+        )
+        .await;
+    }
+
+    // Reopen the catalog, to check that the entries were saved.
+    drop(catalog);
+    let catalog = publish::Catalog::open(&path).await.unwrap();
+
+    let approved = catalog
+        .find(&publish::CatalogQuery::new().with_tag("approved"))
+        .await
+        .unwrap();
+    let between = catalog
+        .find(
+            &publish::CatalogQuery::new()
+                .with_finished_after(start + Duration::from_secs(120))
+                .with_finished_before(start + Duration::from_secs(180)),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(approved.len(), 2);
+    assert_eq!(approved[1].record.tags, vec!["approved", "wip"]);
+    assert_eq!(between.len(), 1);
+    assert_eq!(between[0].record.version, Some(2));
+    assert_eq!(between[0].record.user, "artist");
+    assert_eq!(between[0].record.finished, start + Duration::from_secs(120));
+}
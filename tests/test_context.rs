@@ -0,0 +1,225 @@
+fn object<const N: usize>(entries: [(&str, publish::Value); N]) -> publish::Value {
+    publish::Value::Object(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_context_map_api() {
+    let mut context: publish::Context = [
+        ("a".to_string(), publish::Value::Integer(1)),
+        ("b".to_string(), publish::Value::Integer(2)),
+    ]
+    .into_iter()
+    .collect();
+
+    assert!(context.contains_key("a"));
+    assert_eq!(context.remove("a"), Some(publish::Value::Integer(1)));
+    assert_eq!(context.remove("a"), None);
+
+    *context.get_mut("b").unwrap() = publish::Value::Integer(3);
+    assert_eq!(context.get("b"), Some(&publish::Value::Integer(3)));
+
+    context.extend([
+        ("c".to_string(), publish::Value::Integer(4)),
+        ("d".to_string(), publish::Value::Integer(5)),
+    ]);
+    context.retain(|key, _| key != "d");
+
+    let mut keys = context.keys().cloned().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, vec!["b", "c"]);
+    assert_eq!(
+        context
+            .values()
+            .filter_map(|value| match value {
+                publish::Value::Integer(value) => Some(*value),
+                _ => None,
+            })
+            .sum::<i64>(),
+        7
+    );
+}
+
+#[test]
+fn test_context_entry() {
+    let mut context = publish::Context::default();
+
+    context.entry("count").or_insert(publish::Value::Integer(0));
+    context
+        .entry("count")
+        .and_modify(|value| {
+            if let publish::Value::Integer(count) = value {
+                *count += 1;
+            }
+        })
+        .or_insert(publish::Value::Integer(0));
+
+    assert_eq!(context.entry("count").key(), "count");
+    assert_eq!(context.get("count"), Some(&publish::Value::Integer(1)));
+}
+
+#[test]
+fn test_context_merge_deep() {
+    let mut context = publish::Context::new([(
+        "asset".to_string(),
+        object([
+            ("name", publish::Value::String("chair".to_string())),
+            ("version", publish::Value::Integer(1)),
+        ]),
+    )]);
+    let other = publish::Context::new([
+        (
+            "asset".to_string(),
+            object([
+                ("version", publish::Value::Integer(2)),
+                ("path", publish::Value::String("/chair".to_string())),
+            ]),
+        ),
+        ("status".to_string(), publish::Value::Boolean(true)),
+    ]);
+
+    let mut keep = context.clone();
+    keep.merge(other.clone(), publish::MergeStrategy::Keep)
+        .unwrap();
+    context
+        .merge(other, publish::MergeStrategy::Overwrite)
+        .unwrap();
+
+    assert_eq!(
+        context.get("asset"),
+        Some(&object([
+            ("name", publish::Value::String("chair".to_string())),
+            ("version", publish::Value::Integer(2)),
+            ("path", publish::Value::String("/chair".to_string())),
+        ]))
+    );
+    assert_eq!(context.get("status"), Some(&publish::Value::Boolean(true)));
+    assert_eq!(
+        keep.get("asset"),
+        Some(&object([
+            ("name", publish::Value::String("chair".to_string())),
+            ("version", publish::Value::Integer(1)),
+            ("path", publish::Value::String("/chair".to_string())),
+        ]))
+    );
+}
+
+#[test]
+fn test_context_merge_error_strategy() {
+    let mut context = publish::Context::new([
+        ("a".to_string(), object([("b", publish::Value::Integer(1))])),
+        ("same".to_string(), publish::Value::Integer(1)),
+    ]);
+    let other = publish::Context::new([
+        ("a".to_string(), object([("b", publish::Value::Integer(2))])),
+        ("same".to_string(), publish::Value::Integer(1)),
+        ("new".to_string(), publish::Value::Integer(1)),
+    ]);
+
+    let result = context.merge(other, publish::MergeStrategy::Error);
+
+    match result {
+        Err(publish::Error::InvalidContext(message)) => assert!(message.contains("\"a.b\"")),
+        _ => panic!("Expected an invalid context error"),
+    }
+    assert!(!context.contains_key("new"));
+
+    let other = publish::Context::new([("same".to_string(), publish::Value::Integer(1))]);
+    context.merge(other, publish::MergeStrategy::Error).unwrap();
+}
+
+#[test]
+fn test_merge_strategy_from_str() {
+    for strategy in [
+        publish::MergeStrategy::Overwrite,
+        publish::MergeStrategy::Keep,
+        publish::MergeStrategy::Error,
+    ] {
+        assert_eq!(
+            strategy
+                .to_string()
+                .parse::<publish::MergeStrategy>()
+                .unwrap(),
+            strategy
+        );
+    }
+
+    assert!("replace".parse::<publish::MergeStrategy>().is_err());
+}
+
+#[test]
+fn test_context_clone_shares_unchanged_values() {
+    let context = publish::Context::new([
+        (
+            "files".to_string(),
+            publish::Value::Array((0..100).map(publish::Value::Integer).collect()),
+        ),
+        ("status".to_string(), publish::Value::Boolean(false)),
+    ]);
+
+    let mut modified = context.clone();
+    modified.set("status", publish::Value::Boolean(true));
+    if let Some(publish::Value::Array(files)) = modified.get_mut("files") {
+        files.push_back(publish::Value::Integer(100));
+    }
+
+    assert_eq!(context.get("status"), Some(&publish::Value::Boolean(false)));
+    assert_eq!(modified.get("status"), Some(&publish::Value::Boolean(true)));
+    match (context.get("files"), modified.get("files")) {
+        (Some(publish::Value::Array(files)), Some(publish::Value::Array(modified_files))) => {
+            assert_eq!(files.len(), 100);
+            assert_eq!(modified_files.len(), 101);
+        }
+        _ => panic!("Expected arrays"),
+    }
+
+    let shared = context.clone();
+    assert!(std::ptr::eq(
+        context.get("status").unwrap(),
+        shared.get("status").unwrap()
+    ));
+}
+
+#[test]
+fn test_context_order_is_sorted() {
+    let mut context = publish::Context::default();
+
+    for key in ["b", "c", "a"] {
+        context.set(
+            key,
+            publish::Value::Object(
+                [
+                    ("z".to_string(), publish::Value::None),
+                    ("y".to_string(), publish::Value::None),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        );
+    }
+
+    assert_eq!(context.keys().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    assert_eq!(
+        context.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+        vec!["a", "b", "c"]
+    );
+    assert_eq!(
+        context
+            .clone()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>(),
+        vec!["a", "b", "c"]
+    );
+
+    match context.get("a") {
+        Some(publish::Value::Object(object)) => {
+            assert_eq!(object.keys().collect::<Vec<_>>(), vec!["y", "z"])
+        }
+        _ => panic!("Expected an object"),
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+use publish::Transaction;
+
+#[derive(Default)]
+struct RecordingBackend {
+    calls: Mutex<Vec<&'static str>>,
+}
+
+#[async_trait::async_trait]
+impl publish::DatabaseBackend for RecordingBackend {
+    async fn begin(&self) -> Result<(), publish::Error> {
+        self.calls.lock().unwrap().push("begin");
+        Ok(())
+    }
+
+    async fn commit(&self) -> Result<(), publish::Error> {
+        self.calls.lock().unwrap().push("commit");
+        Ok(())
+    }
+
+    async fn rollback(&self) -> Result<(), publish::Error> {
+        self.calls.lock().unwrap().push("rollback");
+        Ok(())
+    }
+}
+
+struct FailingPublish {
+    stage: Option<publish::Stage>,
+}
+
+impl FailingPublish {
+    fn result<'a>(
+        &self,
+        stage: publish::Stage,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        if self.stage == Some(stage) {
+            Err(publish::Error::new_publish("stage failed", None))
+        } else {
+            Ok(Cow::Borrowed(context))
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for FailingPublish {
+    async fn pre_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        self.result(publish::Stage::PrePublish, context)
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        self.result(publish::Stage::Publish, context)
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        self.result(publish::Stage::PostPublish, context)
+    }
+}
+
+#[tokio::test]
+async fn test_database_transaction_lifecycle() {
+    let transaction = publish::DatabaseTransaction::new(RecordingBackend::default());
+
+    assert!(matches!(
+        transaction.commit().await,
+        Err(publish::Error::Database(_))
+    ));
+    transaction.rollback().await.unwrap();
+
+    transaction.begin().await.unwrap();
+    assert!(transaction.is_active());
+    assert!(matches!(
+        transaction.begin().await,
+        Err(publish::Error::Database(_))
+    ));
+    transaction.commit().await.unwrap();
+    assert!(!transaction.is_active());
+
+    transaction.begin().await.unwrap();
+    transaction.rollback().await.unwrap();
+    transaction.rollback().await.unwrap();
+
+    assert_eq!(
+        *transaction.backend().calls.lock().unwrap(),
+        vec!["begin", "commit", "begin", "rollback"]
+    );
+}
+
+#[tokio::test]
+async fn test_database_publish() {
+    let stages = [
+        None,
+        Some(publish::Stage::PrePublish),
+        Some(publish::Stage::Publish),
+        Some(publish::Stage::PostPublish),
+    ];
+
+    for stage in stages {
+        let transaction = Arc::new(publish::DatabaseTransaction::new(
+            RecordingBackend::default(),
+        ));
+        let publish = publish::DatabasePublish::new(transaction.clone(), FailingPublish { stage });
+
+        let result = publish::run(&publish).await;
+        let expected = match stage {
+            None => vec!["begin", "commit"],
+            Some(_) => vec!["begin", "rollback"],
+        };
+
+        assert_eq!(result.is_ok(), stage.is_none());
+        assert!(!transaction.is_active());
+        assert_eq!(*transaction.backend().calls.lock().unwrap(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_database_publish_does_not_roll_back_another_run() {
+    let transaction = Arc::new(publish::DatabaseTransaction::new(
+        RecordingBackend::default(),
+    ));
+    let first = publish::DatabasePublish::new(transaction.clone(), FailingPublish { stage: None });
+    let second = publish::DatabasePublish::new(transaction.clone(), FailingPublish { stage: None });
+
+    let context = publish::Context::default();
+    publish::Publish::pre_publish(&first, &context)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        publish::run(&second).await,
+        Err(publish::Error::Database(_))
+    ));
+    assert!(transaction.is_active());
+    assert_eq!(*transaction.backend().calls.lock().unwrap(), vec!["begin"]);
+
+    publish::Publish::rollback_pre_publish(&first, &context)
+        .await
+        .unwrap();
+
+    assert!(!transaction.is_active());
+    assert_eq!(
+        *transaction.backend().calls.lock().unwrap(),
+        vec!["begin", "rollback"]
+    );
+}
+
+#[tokio::test]
+async fn test_database_publish_overlapping_runs() {
+    let transaction = Arc::new(publish::DatabaseTransaction::new(
+        RecordingBackend::default(),
+    ));
+    let publish = Arc::new(publish::DatabasePublish::new(
+        transaction.clone(),
+        FailingPublish {
+            stage: Some(publish::Stage::PostPublish),
+        },
+    ));
+
+    let context = publish::Context::default();
+    publish::Publish::pre_publish(&*publish, &context)
+        .await
+        .unwrap();
+
+    // The second run waits for the first one instead of failing, so that its
+    // rollback can't roll back the transaction of the first run.
+    let second = tokio::spawn({
+        let publish = publish.clone();
+        async move { publish::run(&*publish).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    assert!(!second.is_finished());
+    assert!(transaction.is_active());
+    assert_eq!(*transaction.backend().calls.lock().unwrap(), vec!["begin"]);
+
+    publish::Publish::rollback_pre_publish(&*publish, &context)
+        .await
+        .unwrap();
+
+    assert!(second.await.unwrap().is_err());
+    assert!(!transaction.is_active());
+    assert_eq!(
+        *transaction.backend().calls.lock().unwrap(),
+        vec!["begin", "rollback", "begin", "rollback"]
+    );
+}
+
+#[tokio::test]
+async fn test_database_publish_checks_frozen_keys_before_commit() {
+    struct FreezePublish;
+
+    #[async_trait::async_trait]
+    impl publish::Publish for FreezePublish {
+        async fn pre_publish<'a>(
+            &self,
+            context: &'a publish::Context,
+        ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+            let mut context = context.to_owned();
+            context.set("version", publish::Value::Integer(1));
+            context.freeze("version");
+
+            Ok(Cow::Owned(context))
+        }
+
+        async fn publish<'a>(
+            &self,
+            context: &'a publish::Context,
+        ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+            Ok(Cow::Borrowed(context))
+        }
+
+        async fn post_publish<'a>(
+            &self,
+            context: &'a publish::Context,
+        ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+            let mut context = context.to_owned();
+            context.set("version", publish::Value::Integer(2));
+
+            Ok(Cow::Owned(context))
+        }
+    }
+
+    let transaction = Arc::new(publish::DatabaseTransaction::new(
+        RecordingBackend::default(),
+    ));
+    let publish = publish::DatabasePublish::new(transaction.clone(), FreezePublish);
+
+    assert!(matches!(
+        publish::run(&publish).await,
+        Err(publish::Error::FrozenKey(key)) if key == "version"
+    ));
+    assert!(!transaction.is_active());
+    assert_eq!(
+        *transaction.backend().calls.lock().unwrap(),
+        vec!["begin", "rollback"]
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_database_publish() {
+    struct InsertPublish {
+        database: publish::SqliteDatabase,
+        catalog: publish::Catalog,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl publish::Publish for InsertPublish {
+        async fn publish<'a>(
+            &self,
+            context: &'a publish::Context,
+        ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+            self.database
+                .execute(
+                    "INSERT INTO assets (name) VALUES (?)",
+                    vec!["chair".to_string().into()],
+                )
+                .await?;
+
+            let catalog = publish::CatalogTransaction::new(self.catalog.clone());
+            catalog
+                .insert(&publish::CatalogRecord::new("model", context.clone()))
+                .await?;
+            catalog.commit().await?;
+
+            Ok(Cow::Borrowed(context))
+        }
+
+        async fn post_publish<'a>(
+            &self,
+            context: &'a publish::Context,
+        ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+            if self.fail {
+                Err(publish::Error::new_publish("post publish failed", None))
+            } else {
+                Ok(Cow::Borrowed(context))
+            }
+        }
+    }
+
+    let database = publish::SqliteDatabase::open_in_memory().await.unwrap();
+    database
+        .execute_batch("CREATE TABLE assets (name TEXT NOT NULL)")
+        .await
+        .unwrap();
+    let catalog = publish::Catalog::new(database.clone()).await.unwrap();
+
+    for fail in [true, false] {
+        let transaction = Arc::new(publish::DatabaseTransaction::new(database.clone()));
+        let publish = publish::DatabasePublish::new(
+            transaction,
+            InsertPublish {
+                database: database.clone(),
+                catalog: catalog.clone(),
+                fail,
+            },
+        );
+
+        let result = publish::run(&publish).await;
+
+        assert_eq!(result.is_ok(), !fail);
+    }
+
+    let rows = database
+        .query("SELECT name FROM assets", Vec::new())
+        .await
+        .unwrap();
+
+    assert_eq!(rows, vec![vec!["chair".to_string().into()]]);
+    assert_eq!(
+        catalog
+            .find(&publish::CatalogQuery::new())
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
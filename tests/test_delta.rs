@@ -0,0 +1,194 @@
+#![cfg(feature = "manifest")]
+
+use publish::Transaction;
+
+async fn publish_version(
+    work: &std::path::Path,
+    root: &std::path::Path,
+    previous: Option<&std::path::Path>,
+) -> publish::DeltaTransaction {
+    let mut transaction = publish::DeltaTransaction::new(root);
+
+    if let Some(previous) = previous {
+        transaction = transaction.load_previous(previous).await.unwrap();
+    }
+
+    for file in ["model.usd", "textures/wood.png"] {
+        transaction.copy(work.join(file), file).await.unwrap();
+    }
+
+    transaction
+}
+
+fn write_work(work: &std::path::Path, model: &str) {
+    std::fs::create_dir_all(work.join("textures")).unwrap();
+    std::fs::write(work.join("model.usd"), model).unwrap();
+    std::fs::write(work.join("textures/wood.png"), "wood").unwrap();
+}
+
+#[tokio::test]
+async fn test_delta_links_unchanged_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let work = dir.path().join("work");
+    let v001 = dir.path().join("v001");
+    let v002 = dir.path().join("v002");
+
+    write_work(&work, "model");
+    let transaction = publish_version(&work, &v001, None).await;
+    transaction.save_manifest().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    write_work(&work, "model with more detail");
+    let transaction = publish_version(&work, &v002, Some(&v001)).await;
+
+    assert_eq!(transaction.carried_over_files(), vec!["textures/wood.png"]);
+
+    transaction.save_manifest().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    let manifest = publish::Manifest::load(&v002).await.unwrap();
+
+    assert_eq!(manifest.get("model.usd").unwrap().carried_over, None);
+    assert_eq!(
+        manifest.get("textures/wood.png").unwrap().carried_over,
+        Some(v001.to_string_lossy().into_owned())
+    );
+    assert!(manifest.verify(&v002).await.unwrap().is_empty());
+    assert_eq!(
+        std::fs::read_to_string(v002.join("model.usd")).unwrap(),
+        "model with more detail"
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = std::fs::metadata(v002.join("textures/wood.png")).unwrap();
+        assert_eq!(
+            metadata.ino(),
+            std::fs::metadata(v001.join("textures/wood.png"))
+                .unwrap()
+                .ino()
+        );
+    }
+}
+
+/// Makes a file immutable, so that it cannot be hard linked to, until dropped.
+#[cfg(target_os = "linux")]
+struct Immutable(std::path::PathBuf);
+
+#[cfg(target_os = "linux")]
+impl Immutable {
+    fn new(path: std::path::PathBuf) -> Option<Self> {
+        let status = std::process::Command::new("chattr")
+            .arg("+i")
+            .arg(&path)
+            .stderr(std::process::Stdio::null())
+            .status();
+
+        status
+            .is_ok_and(|status| status.success())
+            .then_some(Self(path))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Immutable {
+    fn drop(&mut self) {
+        let _ = std::process::Command::new("chattr")
+            .arg("-i")
+            .arg(&self.0)
+            .status();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_delta_copies_when_linking_is_not_permitted() {
+    let dir = tempfile::tempdir().unwrap();
+    let work = dir.path().join("work");
+    let v001 = dir.path().join("v001");
+    let v002 = dir.path().join("v002");
+
+    write_work(&work, "model");
+    let transaction = publish_version(&work, &v001, None).await;
+    transaction.save_manifest().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    // Changing file attributes needs privileges that the test may not have.
+    let Some(_immutable) = Immutable::new(v001.join("textures/wood.png")) else {
+        return;
+    };
+
+    let transaction = publish_version(&work, &v002, Some(&v001)).await;
+    transaction.save_manifest().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    assert!(transaction.carried_over_files().is_empty());
+    assert_eq!(
+        std::fs::read_to_string(v002.join("textures/wood.png")).unwrap(),
+        "wood"
+    );
+    assert!(publish::Manifest::load(&v002)
+        .await
+        .unwrap()
+        .verify(&v002)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_delta_copies_changed_previous_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let work = dir.path().join("work");
+    let v001 = dir.path().join("v001");
+    let v002 = dir.path().join("v002");
+
+    write_work(&work, "model");
+    let transaction = publish_version(&work, &v001, None).await;
+    transaction.save_manifest().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    // The previous version no longer matches its manifest, so it cannot be
+    // reused, even though the texture is still the same size.
+    std::fs::write(v001.join("textures/wood.png"), "pine").unwrap();
+    std::fs::remove_file(v001.join("model.usd")).unwrap();
+
+    let transaction = publish_version(&work, &v002, Some(&v001)).await;
+
+    assert!(transaction.carried_over_files().is_empty());
+    assert_eq!(
+        std::fs::read_to_string(v002.join("textures/wood.png")).unwrap(),
+        "wood"
+    );
+}
+
+#[tokio::test]
+async fn test_delta_rollback() {
+    let dir = tempfile::tempdir().unwrap();
+    let work = dir.path().join("work");
+    let v001 = dir.path().join("v001");
+    let v002 = dir.path().join("v002");
+
+    write_work(&work, "model");
+    let transaction = publish_version(&work, &v001, None).await;
+    transaction.save_manifest().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    let transaction = publish_version(&work, &v002, Some(&v001)).await;
+
+    assert_eq!(transaction.carried_over_files().len(), 2);
+
+    transaction.rollback().await.unwrap();
+
+    assert!(transaction.carried_over_files().is_empty());
+    assert!(!v002.exists());
+    assert!(publish::Manifest::load(&v001)
+        .await
+        .unwrap()
+        .verify(&v001)
+        .await
+        .unwrap()
+        .is_empty());
+}
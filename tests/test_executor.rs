@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+struct TestPublish;
+
+#[async_trait::async_trait]
+impl publish::Publish for TestPublish {
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<std::borrow::Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set("test", publish::Value::Integer(1));
+
+        Ok(std::borrow::Cow::Owned(context))
+    }
+}
+
+/// An executor that does not depend on any async runtime.
+struct ThreadExecutor;
+
+impl publish::Executor for ThreadExecutor {
+    fn spawn(&self, future: publish::BoxFuture<'static, ()>) -> publish::BoxFuture<'static, ()> {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+
+        std::thread::spawn(move || {
+            futures::executor::block_on(future);
+            let _ = sender.send(());
+        });
+
+        Box::pin(async move {
+            let _ = receiver.await;
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> publish::BoxFuture<'static, ()> {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            let _ = sender.send(());
+        });
+
+        Box::pin(async move {
+            let _ = receiver.await;
+        })
+    }
+}
+
+#[test]
+fn test_run_without_runtime_success() {
+    let result = futures::executor::block_on(publish::run(&TestPublish)).unwrap();
+
+    assert_eq!(result.get("test").unwrap(), &publish::Value::Integer(1));
+}
+
+#[test]
+fn test_custom_executor_spawn_success() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    futures::executor::block_on(publish::Executor::spawn(
+        &ThreadExecutor,
+        Box::pin(async move {
+            sender.send(1).unwrap();
+        }),
+    ));
+
+    assert_eq!(receiver.recv().unwrap(), 1);
+}
+
+#[test]
+fn test_custom_executor_timeout_failure() {
+    let result = futures::executor::block_on(publish::timeout(
+        &ThreadExecutor,
+        Duration::from_millis(10),
+        futures::future::pending::<()>(),
+    ));
+
+    assert!(matches!(result, Err(publish::Error::Timeout(_))));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_tokio_executor_timeout_success() {
+    let result = publish::timeout(
+        &publish::TokioExecutor,
+        Duration::from_secs(10),
+        publish::run(&TestPublish),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(result.get("test").unwrap(), &publish::Value::Integer(1));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_tokio_executor_timeout_failure() {
+    let result = publish::timeout(
+        &publish::TokioExecutor,
+        Duration::from_millis(10),
+        futures::future::pending::<()>(),
+    )
+    .await;
+
+    assert!(matches!(result, Err(publish::Error::Timeout(_))));
+}
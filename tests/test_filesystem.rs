@@ -0,0 +1,115 @@
+#![cfg(feature = "tokio")]
+
+use publish::Transaction;
+
+#[tokio::test]
+async fn test_filesystem_transaction_rollback() {
+    let dir = tempfile::tempdir().unwrap();
+    let existing = dir.path().join("existing.txt");
+    std::fs::write(&existing, "before").unwrap();
+    let source = dir.path().join("source.txt");
+    std::fs::write(&source, "source").unwrap();
+    let transaction = publish::FilesystemTransaction::new();
+
+    transaction
+        .write(dir.path().join("v001/a/b.txt"), "b")
+        .await
+        .unwrap();
+    transaction.write(&existing, "after").await.unwrap();
+    transaction
+        .copy(&source, dir.path().join("v001/copy.txt"))
+        .await
+        .unwrap();
+    transaction
+        .hard_link(&source, dir.path().join("v001/link.txt"))
+        .await
+        .unwrap();
+    transaction
+        .write(dir.path().join("v001/a/b.txt"), "b2")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        transaction.written_files(),
+        vec![
+            dir.path().join("v001/a/b.txt"),
+            existing.clone(),
+            dir.path().join("v001/copy.txt"),
+            dir.path().join("v001/link.txt"),
+        ]
+    );
+    assert_eq!(std::fs::read_to_string(&existing).unwrap(), "after");
+
+    transaction.rollback().await.unwrap();
+
+    assert!(!dir.path().join("v001").exists());
+    assert_eq!(std::fs::read_to_string(&existing).unwrap(), "before");
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    assert!(transaction.written_files().is_empty());
+}
+
+#[tokio::test]
+async fn test_filesystem_transaction_commit() {
+    let dir = tempfile::tempdir().unwrap();
+    let existing = dir.path().join("existing.txt");
+    std::fs::write(&existing, "before").unwrap();
+    let transaction = publish::FilesystemTransaction::new();
+
+    transaction.write(&existing, "after").await.unwrap();
+    transaction
+        .write(dir.path().join("v001/a.txt"), "a")
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+    transaction.rollback().await.unwrap();
+
+    assert_eq!(std::fs::read_to_string(&existing).unwrap(), "after");
+    assert!(dir.path().join("v001/a.txt").exists());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+}
+
+#[tokio::test]
+async fn test_filesystem_transaction_remove_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a.txt");
+    std::fs::write(&path, "a").unwrap();
+    let transaction = publish::FilesystemTransaction::new();
+
+    transaction.remove_file(&path).await.unwrap();
+    assert!(!path.exists());
+    transaction.rollback().await.unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "a");
+
+    transaction.remove_file(&path).await.unwrap();
+    transaction.commit().await.unwrap();
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_filesystem_transaction_rollback_retry() {
+    let dir = tempfile::tempdir().unwrap();
+    let existing = dir.path().join("existing.txt");
+    std::fs::write(&existing, "before").unwrap();
+    let transaction = publish::FilesystemTransaction::new();
+
+    transaction
+        .write(dir.path().join("new.txt"), "new")
+        .await
+        .unwrap();
+    transaction.write(&existing, "after").await.unwrap();
+
+    // A directory in the way stops the existing file from being restored.
+    std::fs::remove_file(&existing).unwrap();
+    std::fs::create_dir(&existing).unwrap();
+    std::fs::write(existing.join("blocker.txt"), "").unwrap();
+
+    assert!(transaction.rollback().await.is_err());
+    assert!(dir.path().join("new.txt").exists());
+
+    std::fs::remove_dir_all(&existing).unwrap();
+    transaction.rollback().await.unwrap();
+
+    assert_eq!(std::fs::read_to_string(&existing).unwrap(), "before");
+    assert!(!dir.path().join("new.txt").exists());
+    assert!(transaction.written_files().is_empty());
+}
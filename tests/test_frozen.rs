@@ -0,0 +1,208 @@
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A publish that freezes the version in pre-publish, then sets a key in
+/// publish.
+struct SetKeyPublish {
+    key: &'static str,
+    rollbacks: AtomicUsize,
+}
+
+impl SetKeyPublish {
+    fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            rollbacks: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for SetKeyPublish {
+    async fn pre_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set("version", publish::Value::Integer(1));
+        context.freeze("version");
+
+        Ok(Cow::Owned(context))
+    }
+
+    async fn rollback_pre_publish(
+        &self,
+        _context: &publish::Context,
+    ) -> Result<(), publish::Error> {
+        self.rollbacks.fetch_add(1, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set(self.key, publish::Value::Integer(2));
+
+        Ok(Cow::Owned(context))
+    }
+
+    async fn rollback_publish(&self, _context: &publish::Context) -> Result<(), publish::Error> {
+        self.rollbacks.fetch_add(1, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        // Rebuild the context from scratch, which must not unfreeze any keys.
+        Ok(Cow::Owned(
+            context
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        ))
+    }
+}
+
+#[test]
+fn test_freeze_key_and_namespace() {
+    let mut context = publish::Context::default();
+    context.freeze("version");
+    context.freeze_namespace("asset");
+
+    assert!(context.is_frozen("version"));
+    assert!(!context.is_frozen("version.major"));
+    assert!(context.is_frozen("asset"));
+    assert!(context.is_frozen("asset.name"));
+    assert!(!context.is_frozen("assets"));
+    assert!(!context.is_frozen("directory"));
+
+    assert!(matches!(
+        context.try_set("asset.name", publish::Value::Integer(1)),
+        Err(publish::Error::FrozenKey(key)) if key == "asset.name"
+    ));
+    assert!(context
+        .try_set("directory", publish::Value::Integer(1))
+        .is_ok());
+    assert_eq!(context.get("asset.name"), None);
+}
+
+#[test]
+fn test_merge_into_frozen_key() {
+    let mut context = publish::Context::new([("version".to_string(), publish::Value::Integer(1))]);
+    context.freeze("version");
+
+    let same = publish::Context::new([("version".to_string(), publish::Value::Integer(1))]);
+    assert!(context
+        .merge(same, publish::MergeStrategy::Overwrite)
+        .is_ok());
+
+    let changed = publish::Context::new([
+        ("directory".to_string(), publish::Value::Integer(1)),
+        ("version".to_string(), publish::Value::Integer(2)),
+    ]);
+    assert!(matches!(
+        context.merge(changed.clone(), publish::MergeStrategy::Overwrite),
+        Err(publish::Error::FrozenKey(key)) if key == "version"
+    ));
+    assert_eq!(context.get("directory"), None);
+
+    assert!(context.merge(changed, publish::MergeStrategy::Keep).is_ok());
+    assert_eq!(context.get("version"), Some(&publish::Value::Integer(1)));
+}
+
+#[test]
+fn test_keep_merge_into_frozen_key() {
+    let asset = publish::Value::Object(
+        [("name".to_string(), publish::Value::String("a".to_string()))]
+            .into_iter()
+            .collect(),
+    );
+    let mut context = publish::Context::new([("asset".to_string(), asset.clone())]);
+    context.freeze("asset");
+    context.freeze("version");
+    context.freeze_namespace("task");
+
+    let extra = publish::Context::new([(
+        "asset".to_string(),
+        publish::Value::Object(
+            [("extra".to_string(), publish::Value::Integer(1))]
+                .into_iter()
+                .collect(),
+        ),
+    )]);
+    assert!(matches!(
+        context.merge(extra, publish::MergeStrategy::Keep),
+        Err(publish::Error::FrozenKey(key)) if key == "asset"
+    ));
+    assert_eq!(context.get("asset"), Some(&asset));
+
+    let missing = publish::Context::new([("version".to_string(), publish::Value::Integer(9))]);
+    assert!(matches!(
+        context.merge(missing, publish::MergeStrategy::Keep),
+        Err(publish::Error::FrozenKey(key)) if key == "version"
+    ));
+    assert_eq!(context.get("version"), None);
+
+    let namespaced = publish::Context::new([("task.name".to_string(), publish::Value::Integer(1))]);
+    assert!(matches!(
+        context.merge(namespaced, publish::MergeStrategy::Keep),
+        Err(publish::Error::FrozenKey(key)) if key == "task.name"
+    ));
+
+    let renamed = publish::Context::new([(
+        "asset".to_string(),
+        publish::Value::Object(
+            [("name".to_string(), publish::Value::String("b".to_string()))]
+                .into_iter()
+                .collect(),
+        ),
+    )]);
+    assert!(context.merge(renamed, publish::MergeStrategy::Keep).is_ok());
+    assert_eq!(context.get("asset"), Some(&asset));
+}
+
+#[tokio::test]
+async fn test_runner_allows_unfrozen_writes() {
+    let publish = SetKeyPublish::new("directory");
+
+    let result = publish::run(&publish).await.unwrap();
+
+    assert_eq!(result.get("directory"), Some(&publish::Value::Integer(2)));
+    assert!(result.is_frozen("version"));
+    assert_eq!(publish.rollbacks.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_runner_rejects_frozen_key_write() {
+    let publish = SetKeyPublish::new("version");
+
+    let result = publish::run(&publish).await;
+
+    assert!(matches!(
+        result,
+        Err(publish::Error::FrozenKey(key)) if key == "version"
+    ));
+    assert_eq!(publish.rollbacks.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_runner_rejects_frozen_namespace_write() {
+    let publish = SetKeyPublish::new("asset.name");
+    let mut context = publish::Context::default();
+    context.freeze_namespace("asset");
+
+    let result = publish::run_with_context(&publish, context).await;
+
+    assert!(matches!(
+        result,
+        Err(publish::Error::FrozenKey(key)) if key == "asset.name"
+    ));
+}
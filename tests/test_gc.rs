@@ -0,0 +1,347 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::{Duration, SystemTime};
+
+use publish::Transaction;
+
+struct Fixture {
+    dir: tempfile::TempDir,
+    catalog: publish::Catalog,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        Self {
+            dir: tempfile::tempdir().unwrap(),
+            catalog: publish::Catalog::open_in_memory().await.unwrap(),
+        }
+    }
+
+    fn root(&self) -> std::path::PathBuf {
+        self.dir.path().join("publish")
+    }
+
+    /// Publish a version with one file, and record it in the catalog.
+    async fn publish(
+        &self,
+        asset: &str,
+        version: i64,
+        tags: &[&str],
+        inputs: &[i64],
+        age: Duration,
+    ) -> i64 {
+        let path = self
+            .root()
+            .join(asset)
+            .join(format!("v{:03}", version))
+            .join("model.abc");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, asset).unwrap();
+
+        let transaction = publish::CatalogTransaction::new(self.catalog.clone());
+        let context = publish::Context::new([(
+            "asset".to_string(),
+            publish::Value::String(asset.to_string()),
+        )]);
+        let id = transaction
+            .insert(
+                &publish::CatalogRecord::new("model", context)
+                    .with_version(version)
+                    .with_files([path])
+                    .with_tags(tags)
+                    .with_inputs(inputs.iter().copied())
+                    .with_finished(SystemTime::now() - age),
+            )
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+
+        id
+    }
+
+    async fn versions(&self, asset: &str) -> Vec<i64> {
+        self.catalog
+            .find(
+                &publish::CatalogQuery::new()
+                    .with_context("asset", publish::Value::String(asset.to_string())),
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .filter_map(|entry| entry.record.version)
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn test_gc_keep_last_and_tags() {
+    let fixture = Fixture::new().await;
+
+    for version in 1..=4 {
+        let tags: &[&str] = if version == 1 { &["approved"] } else { &[] };
+        fixture
+            .publish("chair", version, tags, &[], Duration::ZERO)
+            .await;
+    }
+
+    for version in 1..=2 {
+        fixture
+            .publish("table", version, &[], &[], Duration::ZERO)
+            .await;
+    }
+
+    let policy = publish::RetentionPolicy::new()
+        .with_keep_last(2)
+        .with_group_by(["asset"])
+        .with_keep_tags(["approved"]);
+    let collector =
+        publish::GarbageCollector::new(fixture.catalog.clone(), policy).with_root(fixture.root());
+
+    let dry_run = collector
+        .clone()
+        .with_dry_run(true)
+        .collect()
+        .await
+        .unwrap();
+
+    assert!(dry_run.dry_run);
+    assert_eq!(dry_run.entries.len(), 1);
+    assert_eq!(dry_run.reclaimed_bytes, 5);
+    assert_eq!(fixture.versions("chair").await, vec![1, 2, 3, 4]);
+    assert!(fixture.root().join("chair/v002/model.abc").exists());
+
+    let report = collector.collect().await.unwrap();
+
+    assert!(!report.dry_run);
+    assert_eq!(report.entries, dry_run.entries);
+    assert_eq!(
+        report.files,
+        vec![fixture.root().join("chair/v002/model.abc")]
+    );
+    assert_eq!(report.reclaimed_bytes, 5);
+    assert_eq!(fixture.versions("chair").await, vec![1, 3, 4]);
+    assert_eq!(fixture.versions("table").await, vec![1, 2]);
+    assert!(!fixture.root().join("chair/v002").exists());
+    assert!(fixture.root().join("chair/v003").exists());
+    assert!(collector.plan().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_gc_keeps_inputs_of_kept_entries() {
+    let fixture = Fixture::new().await;
+    let rig_1 = fixture.publish("rig", 1, &[], &[], Duration::ZERO).await;
+    let rig_2 = fixture.publish("rig", 2, &[], &[], Duration::ZERO).await;
+    let rig_3 = fixture.publish("rig", 3, &[], &[], Duration::ZERO).await;
+    let cache_1 = fixture
+        .publish("cache", 1, &[], &[rig_1], Duration::ZERO)
+        .await;
+    let cache_2 = fixture
+        .publish("cache", 2, &[], &[rig_2], Duration::ZERO)
+        .await;
+    let cache_3 = fixture
+        .publish("cache", 3, &[], &[rig_3], Duration::ZERO)
+        .await;
+
+    let policy = publish::RetentionPolicy::new()
+        .with_keep_last(2)
+        .with_group_by(["asset"]);
+    let collector = publish::GarbageCollector::new(fixture.catalog.clone(), policy);
+    let plan = collector.plan().await.unwrap();
+
+    // The first rig is only deleted along with the cache that uses it, and
+    // the cache is deleted first.
+    assert_eq!(
+        plan.deletions
+            .iter()
+            .map(|deletion| deletion.entry.id)
+            .collect::<Vec<_>>(),
+        vec![cache_1, rig_1]
+    );
+
+    collector.execute(&plan).await.unwrap();
+
+    assert_eq!(fixture.versions("rig").await, vec![2, 3]);
+    assert_eq!(fixture.versions("cache").await, vec![2, 3]);
+    assert_eq!(
+        fixture.catalog.dependents(rig_2).await.unwrap(),
+        vec![cache_2]
+    );
+    assert_eq!(
+        fixture.catalog.dependents(rig_3).await.unwrap(),
+        vec![cache_3]
+    );
+}
+
+#[tokio::test]
+async fn test_gc_age_limits() {
+    let fixture = Fixture::new().await;
+    let day = Duration::from_secs(24 * 60 * 60);
+
+    for (version, age) in [(1, day * 60), (2, day * 40), (3, day * 10), (4, day)] {
+        fixture.publish("chair", version, &[], &[], age).await;
+    }
+
+    // Old versions are only kept by the count, until they are too old.
+    let policy = publish::RetentionPolicy::new()
+        .with_keep_last(3)
+        .with_max_age(day * 30);
+    publish::GarbageCollector::new(fixture.catalog.clone(), policy)
+        .collect()
+        .await
+        .unwrap();
+
+    assert_eq!(fixture.versions("chair").await, vec![3, 4]);
+
+    // Recent versions are kept, even if they are not in the count.
+    let policy = publish::RetentionPolicy::new()
+        .with_keep_last(0)
+        .with_min_age(day * 5);
+    publish::GarbageCollector::new(fixture.catalog.clone(), policy)
+        .collect()
+        .await
+        .unwrap();
+
+    assert_eq!(fixture.versions("chair").await, vec![4]);
+}
+
+#[tokio::test]
+async fn test_gc_rolls_back_on_failure() {
+    let fixture = Fixture::new().await;
+    let chair = fixture.publish("chair", 1, &[], &[], Duration::ZERO).await;
+    fixture.publish("chair", 2, &[], &[], Duration::ZERO).await;
+    fixture.publish("chair", 3, &[], &[], Duration::ZERO).await;
+    fixture.publish("chair", 4, &[], &[], Duration::ZERO).await;
+
+    let policy = publish::RetentionPolicy::new().with_keep_last(2);
+    let collector =
+        publish::GarbageCollector::new(fixture.catalog.clone(), policy).with_root(fixture.root());
+    let plan = collector.plan().await.unwrap();
+
+    // Another publish starts using the first version after the plan was made.
+    fixture
+        .publish("cache", 1, &[], &[chair], Duration::ZERO)
+        .await;
+
+    let result = collector.execute(&plan).await;
+
+    assert!(matches!(result, Err(publish::Error::InUse { .. })));
+    assert_eq!(fixture.versions("chair").await, vec![1, 2, 3, 4]);
+    assert!(fixture.root().join("chair/v001/model.abc").exists());
+    assert!(fixture.root().join("chair/v002/model.abc").exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_gc_removes_unreferenced_blobs() {
+    let fixture = Fixture::new().await;
+    let store = publish::BlobStore::new(fixture.dir.path().join("store"));
+    let source = fixture.dir.path().join("texture.exr");
+    std::fs::write(&source, "texture").unwrap();
+
+    for version in 1..=2 {
+        let path = fixture
+            .root()
+            .join(format!("chair/v{:03}/texture.exr", version));
+        let blobs = publish::BlobTransaction::new(store.clone());
+        blobs.add(&source, &path).await.unwrap();
+        blobs.commit().await.unwrap();
+
+        let transaction = publish::CatalogTransaction::new(fixture.catalog.clone());
+        transaction
+            .insert(
+                &publish::CatalogRecord::new("texture", publish::Context::default())
+                    .with_version(version)
+                    .with_files([path]),
+            )
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+    }
+
+    let collector = publish::GarbageCollector::new(
+        fixture.catalog.clone(),
+        publish::RetentionPolicy::new().with_keep_last(1),
+    )
+    .with_blob_store(store.clone());
+    let report = collector.collect().await.unwrap();
+
+    // The object is still used by the last version.
+    assert_eq!(report.reclaimed_bytes, 0);
+    assert!(report.blobs.is_empty());
+
+    let collector = publish::GarbageCollector::new(
+        fixture.catalog.clone(),
+        publish::RetentionPolicy::new().with_keep_last(0),
+    )
+    .with_blob_store(store.clone());
+    let report = collector.collect().await.unwrap();
+
+    assert_eq!(report.blobs.len(), 1);
+    assert_eq!(report.reclaimed_bytes, 7);
+    assert!(store.checksums().await.unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_gc_dry_run_skips_hard_linked_files() {
+    let fixture = Fixture::new().await;
+    fixture.publish("chair", 1, &[], &[], Duration::ZERO).await;
+    fixture.publish("chair", 2, &[], &[], Duration::ZERO).await;
+
+    // The second version carried the first version's file over.
+    let first = fixture.root().join("chair/v001/model.abc");
+    let second = fixture.root().join("chair/v002/model.abc");
+    std::fs::remove_file(&second).unwrap();
+    std::fs::hard_link(&first, &second).unwrap();
+
+    let collector = publish::GarbageCollector::new(
+        fixture.catalog.clone(),
+        publish::RetentionPolicy::new().with_keep_last(1),
+    );
+    let dry_run = collector
+        .clone()
+        .with_dry_run(true)
+        .collect()
+        .await
+        .unwrap();
+    let report = collector.collect().await.unwrap();
+
+    assert_eq!(dry_run.entries, report.entries);
+    assert_eq!(dry_run.reclaimed_bytes, 0);
+    assert_eq!(report.reclaimed_bytes, 0);
+    assert!(second.exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_gc_counts_hard_linked_files_once() {
+    let fixture = Fixture::new().await;
+    fixture.publish("chair", 1, &[], &[], Duration::ZERO).await;
+    fixture.publish("chair", 2, &[], &[], Duration::ZERO).await;
+
+    let first = fixture.root().join("chair/v001/model.abc");
+    let second = fixture.root().join("chair/v002/model.abc");
+    std::fs::remove_file(&second).unwrap();
+    std::fs::hard_link(&first, &second).unwrap();
+
+    // Both links are deleted, so the file is counted once.
+    let collector = publish::GarbageCollector::new(
+        fixture.catalog.clone(),
+        publish::RetentionPolicy::new().with_keep_last(0),
+    );
+    let plan = collector.plan().await.unwrap();
+    let dry_run = collector
+        .clone()
+        .with_dry_run(true)
+        .execute(&plan)
+        .await
+        .unwrap();
+    let report = collector.execute(&plan).await.unwrap();
+
+    assert_eq!(plan.deletions.len(), 2);
+    assert_eq!(plan.bytes(), 5);
+    assert_eq!(dry_run.reclaimed_bytes, 5);
+    assert_eq!(report.reclaimed_bytes, 5);
+    assert!(!first.exists());
+    assert!(!second.exists());
+}
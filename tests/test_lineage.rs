@@ -0,0 +1,155 @@
+#![cfg(feature = "sqlite")]
+
+use publish::Transaction;
+
+async fn insert(
+    catalog: &publish::Catalog,
+    name: &str,
+    inputs: impl IntoIterator<Item = i64>,
+) -> i64 {
+    let transaction = publish::CatalogTransaction::new(catalog.clone());
+    let id = transaction
+        .insert(
+            &publish::CatalogRecord::new(name, publish::Context::default())
+                .with_version(1)
+                .with_inputs(inputs),
+        )
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+
+    id
+}
+
+fn names(entries: Vec<publish::CatalogEntry>) -> Vec<String> {
+    entries.into_iter().map(|entry| entry.record.name).collect()
+}
+
+#[tokio::test]
+async fn test_upstream_and_downstream() {
+    let catalog = publish::Catalog::open_in_memory().await.unwrap();
+    let model = insert(&catalog, "model", []).await;
+    let rig = insert(&catalog, "rig", [model]).await;
+    let animation = insert(&catalog, "animation", []).await;
+    let cache = insert(&catalog, "cache", [rig, animation]).await;
+    let render = insert(&catalog, "render", [cache]).await;
+
+    assert_eq!(
+        catalog.get(cache).await.unwrap().unwrap().record.inputs,
+        vec![rig, animation]
+    );
+    assert_eq!(
+        names(catalog.upstream(render).await.unwrap()),
+        vec!["model", "rig", "animation", "cache"]
+    );
+    assert_eq!(
+        names(catalog.downstream(model).await.unwrap()),
+        vec!["rig", "cache", "render"]
+    );
+    assert!(catalog.upstream(model).await.unwrap().is_empty());
+    assert!(catalog.downstream(render).await.unwrap().is_empty());
+    assert_eq!(catalog.dependents(rig).await.unwrap(), vec![cache]);
+}
+
+#[tokio::test]
+async fn test_remove_refuses_used_entries() {
+    let catalog = publish::Catalog::open_in_memory().await.unwrap();
+    let rig = insert(&catalog, "rig", []).await;
+    let cache = insert(&catalog, "cache", [rig]).await;
+    let transaction = publish::CatalogTransaction::new(catalog.clone());
+
+    match transaction.remove(rig).await {
+        Err(publish::Error::InUse { id, dependents }) => {
+            assert_eq!(id, rig);
+            assert_eq!(dependents, vec![cache]);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    transaction.remove(cache).await.unwrap();
+    transaction.rollback().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    assert!(catalog.get(cache).await.unwrap().is_some());
+
+    transaction.remove(cache).await.unwrap();
+    transaction.remove(rig).await.unwrap_err();
+    transaction.commit().await.unwrap();
+
+    assert!(catalog.get(cache).await.unwrap().is_none());
+
+    transaction.remove(rig).await.unwrap();
+    transaction.commit().await.unwrap();
+
+    assert!(catalog
+        .find(&publish::CatalogQuery::new())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_remove_checks_again_on_commit() {
+    let catalog = publish::Catalog::open_in_memory().await.unwrap();
+    let rig = insert(&catalog, "rig", []).await;
+    let transaction = publish::CatalogTransaction::new(catalog.clone());
+
+    transaction.remove(rig).await.unwrap();
+    let cache = insert(&catalog, "cache", [rig]).await;
+
+    assert!(matches!(
+        transaction.commit().await,
+        Err(publish::Error::InUse { .. })
+    ));
+    assert!(catalog.get(rig).await.unwrap().is_some());
+    assert_eq!(catalog.dependents(rig).await.unwrap(), vec![cache]);
+}
+
+#[tokio::test]
+async fn test_rollback_removes_dependent_entries_first() {
+    let catalog = publish::Catalog::open_in_memory().await.unwrap();
+    let transaction = publish::CatalogTransaction::new(catalog.clone());
+    let record = publish::CatalogRecord::new("rig", publish::Context::default());
+    let rig = transaction.insert(&record).await.unwrap();
+    transaction
+        .insert(
+            &publish::CatalogRecord::new("cache", publish::Context::default()).with_inputs([rig]),
+        )
+        .await
+        .unwrap();
+
+    transaction.rollback().await.unwrap();
+
+    assert!(transaction.inserted().is_empty());
+    assert!(catalog.dependents(rig).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_commit_keeps_inserted_entries_hidden() {
+    let catalog = publish::Catalog::open_in_memory().await.unwrap();
+    let rig = insert(&catalog, "rig", []).await;
+    let transaction = publish::CatalogTransaction::new(catalog.clone());
+    let model = transaction
+        .insert(&publish::CatalogRecord::new(
+            "model",
+            publish::Context::default(),
+        ))
+        .await
+        .unwrap();
+
+    transaction.remove(rig).await.unwrap();
+    insert(&catalog, "cache", [rig]).await;
+
+    assert!(matches!(
+        transaction.commit().await,
+        Err(publish::Error::InUse { .. })
+    ));
+    assert!(catalog.get(model).await.unwrap().is_none());
+    assert_eq!(transaction.inserted(), vec![model]);
+    assert_eq!(transaction.removed(), vec![rig]);
+
+    transaction.rollback().await.unwrap();
+
+    assert!(transaction.inserted().is_empty());
+    assert!(catalog.get(rig).await.unwrap().is_some());
+}
@@ -0,0 +1,241 @@
+#![cfg(feature = "lock")]
+
+use std::{borrow::Cow, sync::Arc, time::Duration};
+
+struct LockedPublish {
+    lock_path: std::path::PathBuf,
+    fail: bool,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for LockedPublish {
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        assert!(self.lock_path.exists());
+
+        if self.fail {
+            Err(publish::Error::new_publish("publish failed", None))
+        } else {
+            Ok(Cow::Borrowed(context))
+        }
+    }
+}
+
+fn asset_context() -> publish::Context {
+    publish::Context::new([
+        (
+            "show".to_string(),
+            publish::Value::String("demo".to_string()),
+        ),
+        (
+            "asset".to_string(),
+            publish::Value::String("chair".to_string()),
+        ),
+    ])
+}
+
+#[tokio::test]
+async fn test_acquire_and_release() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = publish::LockManager::new(dir.path());
+
+    let lock = manager.acquire("demo/chair").await.unwrap();
+
+    assert_eq!(lock.path(), dir.path().join("demo%2Fchair.lock"));
+    assert_eq!(lock.info().pid, std::process::id());
+    assert_eq!(
+        manager.info("demo/chair").await.unwrap().as_ref(),
+        Some(lock.info())
+    );
+    assert!(manager.try_acquire("demo/chair").await.unwrap().is_none());
+    assert!(manager.try_acquire("demo/table").await.unwrap().is_some());
+
+    lock.release().await.unwrap();
+
+    assert_eq!(manager.info("demo/chair").await.unwrap(), None);
+    assert!(manager.try_acquire("demo/chair").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_acquire_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = publish::LockManager::new(dir.path())
+        .with_timeout(Some(Duration::from_millis(50)))
+        .with_poll_interval(Duration::from_millis(10));
+    let _lock = manager.acquire("chair").await.unwrap();
+
+    let result = manager.acquire("chair").await;
+
+    match result {
+        Err(publish::Error::Lock(message)) => {
+            assert!(message.contains(&format!("pid {}", std::process::id())))
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_acquire_waits_for_release() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = publish::LockManager::new(dir.path())
+        .with_timeout(None)
+        .with_poll_interval(Duration::from_millis(10));
+    let lock = manager.acquire("chair").await.unwrap();
+
+    let release = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        lock.release().await.unwrap();
+    };
+    let (_, acquired) = tokio::join!(release, manager.acquire("chair"));
+
+    assert!(acquired.is_ok());
+}
+
+#[tokio::test]
+async fn test_break_stale_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = publish::LockManager::new(dir.path()).with_stale_after(Duration::from_secs(60));
+    std::fs::write(
+        manager.path("chair"),
+        "owner=someone\npid=1\nhost=elsewhere\ntimestamp=1000.000000000\n",
+    )
+    .unwrap();
+
+    let stale = manager.info("chair").await.unwrap().unwrap();
+    assert_eq!(stale.owner, "someone");
+    assert_eq!(stale.host, "elsewhere");
+
+    let lock = manager.acquire("chair").await.unwrap();
+
+    assert_eq!(lock.info().pid, std::process::id());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn test_lock_names_do_not_clash() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = publish::LockManager::new(dir.path());
+
+    let _first = manager.acquire("demo/chair").await.unwrap();
+    let _second = manager.acquire("demo_chair").await.unwrap();
+    let _third = manager.acquire("demo%2Fchair").await.unwrap();
+
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 3);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_break_lock_of_exited_holder() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = publish::LockManager::new(dir.path());
+    let host = publish::LockInfo::current().host;
+    let mut child = std::process::Command::new("true").spawn().unwrap();
+    let exited = child.id();
+    child.wait().unwrap();
+
+    // A holder on this host that is still running is not stale, however old
+    // its lock is.
+    std::fs::write(
+        manager.path("chair"),
+        format!(
+            "owner=someone\npid={}\nhost={}\ntimestamp=1000.000000000\n",
+            std::process::id(),
+            host
+        ),
+    )
+    .unwrap();
+
+    assert!(manager
+        .clone()
+        .with_stale_after(Duration::from_secs(60))
+        .try_acquire("chair")
+        .await
+        .unwrap()
+        .is_none());
+
+    std::fs::write(
+        manager.path("chair"),
+        format!(
+            "owner=someone\npid={}\nhost={}\ntimestamp={}.000000000\n",
+            exited,
+            host,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        ),
+    )
+    .unwrap();
+
+    let lock = manager.acquire("chair").await.unwrap();
+
+    assert_eq!(lock.info().pid, std::process::id());
+}
+
+#[tokio::test]
+async fn test_break_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = publish::LockManager::new(dir.path());
+    let lock = manager.acquire("chair").await.unwrap();
+
+    assert!(manager.break_lock("chair").await.unwrap());
+    assert!(!manager.break_lock("chair").await.unwrap());
+
+    let _other = manager.acquire("chair").await.unwrap();
+
+    assert!(matches!(lock.release().await, Err(publish::Error::Lock(_))));
+    assert!(manager.path("chair").exists());
+}
+
+#[tokio::test]
+async fn test_lock_released_on_drop() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = publish::LockManager::new(dir.path());
+
+    drop(manager.acquire("chair").await.unwrap());
+
+    assert!(!manager.path("chair").exists());
+}
+
+#[tokio::test]
+async fn test_run_with_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(publish::LockManager::new(dir.path()));
+    let options = publish::RunOptions::new().with_lock(
+        manager.clone(),
+        publish::Template::new("{show}/{asset}").unwrap(),
+    );
+
+    for fail in [false, true] {
+        let publish = LockedPublish {
+            lock_path: manager.path("demo/chair"),
+            fail,
+        };
+
+        let result = publish::run_with_options(&publish, asset_context(), &options).await;
+
+        assert_eq!(result.is_ok(), !fail);
+        assert!(!manager.path("demo/chair").exists());
+    }
+}
+
+#[tokio::test]
+async fn test_run_with_held_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(publish::LockManager::new(dir.path()));
+    let options = publish::RunOptions::new().with_lock(
+        manager.clone(),
+        publish::Template::new("{show}/{asset}").unwrap(),
+    );
+    let _lock = manager.acquire("demo/chair").await.unwrap();
+    let publish = LockedPublish {
+        lock_path: manager.path("demo/chair"),
+        fail: false,
+    };
+
+    let result = publish::run_with_options(&publish, asset_context(), &options).await;
+
+    assert!(matches!(result, Err(publish::Error::Lock(_))));
+}
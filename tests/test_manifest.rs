@@ -0,0 +1,137 @@
+#![cfg(feature = "manifest")]
+
+use publish::Transaction;
+
+async fn write_publish(root: &std::path::Path) -> publish::FilesystemTransaction {
+    let transaction = publish::FilesystemTransaction::new();
+    transaction
+        .write(root.join("model.usd"), "model")
+        .await
+        .unwrap();
+    transaction
+        .write(root.join("textures/wood.png"), "wood")
+        .await
+        .unwrap();
+
+    transaction
+}
+
+#[tokio::test]
+async fn test_manifest_build_and_save() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("v001");
+    let transaction = write_publish(&root).await;
+
+    let manifest = publish::ManifestBuilder::new(&root)
+        .add_files(transaction.written_files())
+        .build()
+        .await
+        .unwrap();
+    let path = manifest.save(&transaction, &root).await.unwrap();
+
+    assert_eq!(path, root.join("manifest.json"));
+    assert_eq!(manifest.algorithm, publish::ChecksumAlgorithm::Sha256);
+    assert_eq!(
+        manifest
+            .files
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect::<Vec<_>>(),
+        vec!["model.usd", "textures/wood.png"]
+    );
+
+    let model = manifest.get("model.usd").unwrap();
+    assert_eq!(model.size, 5);
+    assert_eq!(
+        model.checksum,
+        "9372c470eeadd5ecd9c3c74c2b3cb633f8e2f2fad799250a0f70d652b6b825e4"
+    );
+    assert!(model.modified > 0);
+
+    // The manifest is written through the transaction, so it is rolled back
+    // with the rest of the publish.
+    assert_eq!(publish::Manifest::load(&root).await.unwrap(), manifest);
+    transaction.rollback().await.unwrap();
+    assert!(!root.exists());
+}
+
+#[tokio::test]
+async fn test_manifest_algorithm() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("v001");
+    write_publish(&root).await;
+
+    let manifest = publish::ManifestBuilder::new(&root)
+        .with_algorithm("sha512".parse().unwrap())
+        .with_concurrency(1)
+        .add_file("model.usd")
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(manifest.algorithm, publish::ChecksumAlgorithm::Sha512);
+    assert!(manifest.files[0]
+        .checksum
+        .starts_with("0444e9677f6fa85162b4"));
+    assert_eq!(
+        publish::Manifest::from_json(&manifest.to_json()).unwrap(),
+        manifest
+    );
+    assert!("md5".parse::<publish::ChecksumAlgorithm>().is_err());
+}
+
+#[tokio::test]
+async fn test_manifest_file_outside_root() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let result = publish::ManifestBuilder::new(dir.path().join("v001"))
+        .add_file(dir.path().join("v002/model.usd"))
+        .build()
+        .await;
+
+    assert!(matches!(result, Err(publish::Error::Manifest(_))));
+}
+
+#[tokio::test]
+async fn test_manifest_verify() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("v001");
+    let transaction = write_publish(&root).await;
+    transaction
+        .write(root.join("notes.txt"), "notes")
+        .await
+        .unwrap();
+    let manifest = publish::ManifestBuilder::new(&root)
+        .add_files(transaction.written_files())
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(manifest.verify(&root).await.unwrap(), vec![]);
+
+    std::fs::write(root.join("model.usd"), "MODEL").unwrap();
+    std::fs::write(root.join("notes.txt"), "longer notes").unwrap();
+    std::fs::remove_file(root.join("textures/wood.png")).unwrap();
+
+    let problems = manifest.verify(&root).await.unwrap();
+
+    assert_eq!(problems.len(), 3);
+    assert!(matches!(
+        &problems[0],
+        publish::ManifestProblem::ChecksumMismatch { path, .. } if path == "model.usd"
+    ));
+    assert_eq!(
+        problems[1],
+        publish::ManifestProblem::SizeMismatch {
+            path: "notes.txt".to_string(),
+            expected: 5,
+            actual: 12,
+        }
+    );
+    assert_eq!(
+        problems[2],
+        publish::ManifestProblem::Missing {
+            path: "textures/wood.png".to_string()
+        }
+    );
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "tokio")]
+
+use std::borrow::Cow;
+
+use publish::Transaction;
+
+struct PointerPublish {
+    transaction: publish::PointerTransaction,
+    version: &'static str,
+    fail: bool,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for PointerPublish {
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        Ok(Cow::Borrowed(context))
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        self.transaction.set("latest", self.version).await?;
+
+        if self.fail {
+            return Err(publish::Error::new_publish("post publish failed", None));
+        }
+
+        self.transaction.commit().await?;
+
+        Ok(Cow::Borrowed(context))
+    }
+
+    async fn rollback_post_publish(
+        &self,
+        _context: &publish::Context,
+    ) -> Result<(), publish::Error> {
+        self.transaction.rollback().await
+    }
+}
+
+#[tokio::test]
+async fn test_pointer_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = publish::PointerStore::new(dir.path().join("chair"));
+
+    assert_eq!(store.get("approved").await.unwrap(), None);
+    assert!(store.names().await.unwrap().is_empty());
+
+    store.set("approved", "v001").await.unwrap();
+    store.set("latest", "v002").await.unwrap();
+    store.set("approved", "v002").await.unwrap();
+
+    assert_eq!(
+        store.path("approved").unwrap(),
+        dir.path().join("chair/@approved")
+    );
+    assert_eq!(store.get("approved").await.unwrap(), Some("v002".into()));
+    assert_eq!(
+        store.resolve("latest").await.unwrap(),
+        Some(dir.path().join("chair/v002"))
+    );
+    assert_eq!(store.names().await.unwrap(), vec!["approved", "latest"]);
+    assert_eq!(
+        publish::PointerStore::resolve_reference(&format!(
+            "{}@approved",
+            dir.path().join("chair").display()
+        ))
+        .await
+        .unwrap(),
+        Some(dir.path().join("chair/v002"))
+    );
+
+    assert!(store.remove("latest").await.unwrap());
+    assert!(!store.remove("latest").await.unwrap());
+    assert_eq!(store.names().await.unwrap(), vec!["approved"]);
+}
+
+#[tokio::test]
+async fn test_pointer_invalid_names() {
+    let store = publish::PointerStore::new("chair");
+
+    for name in ["", "a/b", "a\\b", "..", "a@b", "latest.tmp"] {
+        assert!(matches!(
+            store.set(name, "v001").await,
+            Err(publish::Error::Pointer(_))
+        ));
+    }
+
+    assert!(matches!(
+        publish::PointerStore::resolve_reference("chair").await,
+        Err(publish::Error::Pointer(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_pointer_transaction_rollback() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = publish::PointerStore::new(dir.path());
+    store.set("approved", "v001").await.unwrap();
+    let transaction = publish::PointerTransaction::new(store.clone());
+
+    transaction.set("approved", "v002").await.unwrap();
+    transaction.set("approved", "v003").await.unwrap();
+    transaction.set("latest", "v003").await.unwrap();
+    transaction.rollback().await.unwrap();
+
+    assert_eq!(store.get("approved").await.unwrap(), Some("v001".into()));
+    assert_eq!(store.get("latest").await.unwrap(), None);
+
+    transaction.remove("approved").await.unwrap();
+    transaction.rollback().await.unwrap();
+
+    assert_eq!(store.get("approved").await.unwrap(), Some("v001".into()));
+
+    transaction.set("approved", "v002").await.unwrap();
+    transaction.commit().await.unwrap();
+    transaction.rollback().await.unwrap();
+
+    assert_eq!(store.get("approved").await.unwrap(), Some("v002".into()));
+}
+
+#[tokio::test]
+async fn test_pointer_transaction_with_runner() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = publish::PointerStore::new(dir.path());
+
+    for (version, fail) in [("v001", false), ("v002", true)] {
+        let publish = PointerPublish {
+            transaction: publish::PointerTransaction::new(store.clone()),
+            version,
+            fail,
+        };
+
+        let result = publish::run(&publish).await;
+
+        assert_eq!(result.is_ok(), !fail);
+    }
+
+    assert_eq!(store.get("latest").await.unwrap(), Some("v001".into()));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
@@ -0,0 +1,256 @@
+#![cfg(feature = "preflight")]
+
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+struct LargePublish {
+    root: std::path::PathBuf,
+    bytes: u64,
+    rolled_back: AtomicBool,
+    published: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for LargePublish {
+    async fn rollback_pre_publish(
+        &self,
+        _context: &publish::Context,
+    ) -> Result<(), publish::Error> {
+        self.rolled_back.store(true, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        self.published.store(true, Ordering::SeqCst);
+
+        Ok(Cow::Borrowed(context))
+    }
+
+    async fn estimate_space(
+        &self,
+        _context: &publish::Context,
+    ) -> Result<Vec<publish::SpaceEstimate>, publish::Error> {
+        Ok(vec![publish::SpaceEstimate::new(
+            self.root.join("chair/v001"),
+            self.bytes,
+        )])
+    }
+}
+
+fn show_context() -> publish::Context {
+    publish::Context::new([(
+        "show".to_string(),
+        publish::Value::String("demo".to_string()),
+    )])
+}
+
+#[tokio::test]
+async fn test_preflight_quota() {
+    let dir = tempfile::tempdir().unwrap();
+    let show = dir.path().join("demo");
+    std::fs::create_dir_all(show.join("table/v001")).unwrap();
+    std::fs::write(show.join("table/v001/model.usd"), vec![0; 600]).unwrap();
+    let preflight = publish::Preflight::new().with_quota(
+        publish::Template::new(&format!("{}/{{show}}", dir.path().display())).unwrap(),
+        1000,
+    );
+
+    preflight
+        .check(
+            &show_context(),
+            &[
+                publish::SpaceEstimate::new(show.join("chair/v001/model.usd"), 300),
+                publish::SpaceEstimate::new(dir.path().join("other/model.usd"), 300),
+            ],
+        )
+        .await
+        .unwrap();
+
+    let result = preflight
+        .check(
+            &show_context(),
+            &[
+                publish::SpaceEstimate::new(show.join("chair/v001/model.usd"), 300),
+                publish::SpaceEstimate::new(show.join("chair/v001/wood.png"), 200),
+            ],
+        )
+        .await;
+
+    match result {
+        Err(publish::Error::InsufficientSpace(shortfalls)) => {
+            assert_eq!(
+                shortfalls,
+                vec![publish::SpaceShortfall {
+                    kind: publish::ShortfallKind::Quota,
+                    path: show,
+                    required: 500,
+                    available: 400,
+                }]
+            );
+            assert_eq!(shortfalls[0].shortfall(), 100);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_preflight_quota_normalizes_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let show = dir.path().join("demo");
+    std::fs::create_dir_all(show.join("table/v001")).unwrap();
+    std::fs::write(show.join("table/v001/model.usd"), vec![0; 600]).unwrap();
+    std::os::unix::fs::symlink(&show, dir.path().join("link")).unwrap();
+
+    // Hard links under the root are only counted once.
+    std::fs::hard_link(
+        show.join("table/v001/model.usd"),
+        show.join("table/v001/model.usd.bak"),
+    )
+    .unwrap();
+
+    let preflight = publish::Preflight::new().with_quota(
+        publish::Template::new(&format!("{}/{{show}}", dir.path().display())).unwrap(),
+        1000,
+    );
+
+    preflight
+        .check(
+            &show_context(),
+            &[publish::SpaceEstimate::new(
+                show.join("chair/v001/model.usd"),
+                400,
+            )],
+        )
+        .await
+        .unwrap();
+
+    for path in [
+        dir.path().join("link/chair/v001/model.usd"),
+        show.join("chair/../chair/v001/model.usd"),
+    ] {
+        let result = preflight
+            .check(
+                &show_context(),
+                &[publish::SpaceEstimate::new(path.clone(), 500)],
+            )
+            .await;
+
+        assert!(
+            matches!(result, Err(publish::Error::InsufficientSpace(_))),
+            "{:?} is not under the quota root",
+            path
+        );
+    }
+
+    preflight
+        .check(
+            &show_context(),
+            &[publish::SpaceEstimate::new(
+                show.join("../other/model.usd"),
+                500,
+            )],
+        )
+        .await
+        .unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_preflight_free_space() {
+    let dir = tempfile::tempdir().unwrap();
+    let preflight = publish::Preflight::new();
+
+    preflight
+        .check(
+            &show_context(),
+            &[publish::SpaceEstimate::new(
+                dir.path().join("chair/v001"),
+                1,
+            )],
+        )
+        .await
+        .unwrap();
+
+    // Estimates on the same volume are added together.
+    let result = preflight
+        .check(
+            &show_context(),
+            &[
+                publish::SpaceEstimate::new(dir.path().join("chair/v001"), u64::MAX / 2),
+                publish::SpaceEstimate::new(dir.path().join("table/v001"), u64::MAX / 2),
+            ],
+        )
+        .await;
+
+    match result {
+        Err(publish::Error::InsufficientSpace(shortfalls)) => {
+            assert_eq!(shortfalls.len(), 1);
+            assert_eq!(shortfalls[0].kind, publish::ShortfallKind::FreeSpace);
+            assert_eq!(shortfalls[0].path, dir.path());
+            assert_eq!(shortfalls[0].required, u64::MAX - 1);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // The reserve is kept free on top of the estimates.
+    assert!(matches!(
+        publish::Preflight::new()
+            .with_reserve(u64::MAX)
+            .check(
+                &show_context(),
+                &[publish::SpaceEstimate::new(dir.path(), 1)]
+            )
+            .await,
+        Err(publish::Error::InsufficientSpace(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_run_with_preflight() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = publish::RunOptions::new().with_preflight(publish::Preflight::new().with_quota(
+        publish::Template::new(&format!("{}/{{show}}", dir.path().display())).unwrap(),
+        1000,
+    ));
+
+    for (bytes, fail) in [(1000, false), (1001, true)] {
+        let publish = LargePublish {
+            root: dir.path().join("demo"),
+            bytes,
+            rolled_back: AtomicBool::new(false),
+            published: AtomicBool::new(false),
+        };
+
+        let result = publish::run_with_options(&publish, show_context(), &options).await;
+
+        assert_eq!(
+            matches!(result, Err(publish::Error::InsufficientSpace(_))),
+            fail
+        );
+        assert_eq!(publish.published.load(Ordering::SeqCst), !fail);
+        assert_eq!(publish.rolled_back.load(Ordering::SeqCst), fail);
+    }
+}
+
+#[test]
+fn test_insufficient_space_message() {
+    let err = publish::Error::new_insufficient_space(vec![publish::SpaceShortfall {
+        kind: publish::ShortfallKind::FreeSpace,
+        path: "/publish".into(),
+        required: 2048,
+        available: 1024,
+    }]);
+
+    assert_eq!(
+        err.to_string(),
+        "Not enough space: \"/publish\" needs 2048 bytes of free space, but only 1024 bytes are \
+         available"
+    );
+}
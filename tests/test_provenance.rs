@@ -0,0 +1,131 @@
+use std::borrow::Cow;
+
+struct TrackedPublish;
+
+#[async_trait::async_trait]
+impl publish::Publish for TrackedPublish {
+    fn name(&self) -> &str {
+        "tracked"
+    }
+
+    async fn pre_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set("directory", publish::Value::String("/publish".to_string()));
+        context.set("status", publish::Value::String("pending".to_string()));
+
+        Ok(Cow::Owned(context))
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        // Rebuild the context from scratch, dropping the status.
+        let mut output = publish::Context::default();
+        output.set("directory", context.get("directory").unwrap().clone());
+        output.set("files", publish::Value::Integer(2));
+
+        Ok(Cow::Owned(output))
+    }
+
+    async fn post_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        Ok(Cow::Borrowed(context))
+    }
+}
+
+#[tokio::test]
+async fn test_provenance_recorded_by_runner() {
+    let context = publish::Context::new([("asset".to_string(), publish::Value::Integer(1))])
+        .with_provenance();
+
+    let result = publish::run_with_context(&TrackedPublish, context)
+        .await
+        .unwrap();
+
+    assert!(result.is_tracking_provenance());
+    assert_eq!(result.provenance("asset"), None);
+
+    let directory = result.provenance("directory").unwrap();
+    assert_eq!(directory.stage, publish::Stage::PrePublish);
+    assert_eq!(directory.publish, "tracked");
+
+    let files = result.provenance("files").unwrap();
+    assert_eq!(files.stage, publish::Stage::Publish);
+    assert!(files.timestamp >= directory.timestamp);
+
+    assert_eq!(result.provenance("status"), None);
+    assert_eq!(
+        result
+            .provenance_iter()
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>(),
+        vec!["directory", "files"]
+    );
+}
+
+#[tokio::test]
+async fn test_provenance_disabled_by_default() {
+    let result = publish::run(&TrackedPublish).await.unwrap();
+
+    assert!(!result.is_tracking_provenance());
+    assert_eq!(result.provenance("directory"), None);
+    assert_eq!(result.provenance_iter().count(), 0);
+}
+
+#[tokio::test]
+async fn test_provenance_in_run_report() {
+    let report = publish::run_with_options(
+        &TrackedPublish,
+        publish::Context::default().with_provenance(),
+        &publish::RunOptions::new(),
+    )
+    .await
+    .unwrap();
+
+    let provenance = report.provenance.unwrap();
+    assert_eq!(
+        provenance.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec!["directory", "files"]
+    );
+    assert_eq!(provenance["files"].stage, publish::Stage::Publish);
+
+    let report = publish::run_with_options(
+        &TrackedPublish,
+        publish::Context::default(),
+        &publish::RunOptions::new(),
+    )
+    .await
+    .unwrap();
+
+    assert!(report.provenance.is_none());
+}
+
+#[tokio::test]
+async fn test_provenance_uses_registry_name() {
+    let mut registry = publish::Registry::new();
+    registry
+        .register("model", publish::PublishMetadata::default(), || {
+            TrackedPublish
+        })
+        .unwrap();
+
+    let result = registry
+        .run("model", publish::Context::default().with_provenance())
+        .await
+        .unwrap();
+
+    assert_eq!(result.provenance("files").unwrap().publish, "model");
+}
+
+#[test]
+fn test_stage_display() {
+    assert_eq!(publish::Stage::PrePublish.to_string(), "pre_publish");
+    assert_eq!(publish::Stage::Publish.to_string(), "publish");
+    assert_eq!(publish::Stage::PostPublish.to_string(), "post_publish");
+}
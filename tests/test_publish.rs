@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+struct SetPublish {
+    value: i64,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for SetPublish {
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<std::borrow::Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set("test", publish::Value::Integer(self.value));
+
+        Ok(std::borrow::Cow::Owned(context))
+    }
+}
+
+struct FailPublish;
+
+#[async_trait::async_trait]
+impl publish::Publish for FailPublish {
+    async fn publish<'a>(
+        &self,
+        _context: &'a publish::Context,
+    ) -> Result<std::borrow::Cow<'a, publish::Context>, publish::Error> {
+        Err(publish::Error::new_publish("publish failed", None))
+    }
+}
+
+fn assert_send<T: Send>(value: T) -> T {
+    value
+}
+
+#[tokio::test]
+async fn test_publish_boxed_mixed_types() {
+    let publishes: Vec<Box<publish::DynPublish>> =
+        vec![Box::new(SetPublish { value: 1 }), Box::new(FailPublish)];
+
+    let result = publish::run(&publishes[0]).await.unwrap();
+    assert_eq!(result.get("test").unwrap(), &publish::Value::Integer(1));
+
+    let result = publish::run(publishes[1].as_ref()).await;
+    assert!(matches!(result, Err(publish::Error::Publish { .. })));
+}
+
+#[tokio::test]
+async fn test_publish_arc_and_reference() {
+    let arc_publish: Arc<publish::DynPublish> = Arc::new(SetPublish { value: 2 });
+    let result = publish::run(&arc_publish).await.unwrap();
+    assert_eq!(result.get("test").unwrap(), &publish::Value::Integer(2));
+
+    let set_publish = SetPublish { value: 3 };
+    let reference_publish = &set_publish;
+    let result = publish::run(&reference_publish).await.unwrap();
+    assert_eq!(result.get("test").unwrap(), &publish::Value::Integer(3));
+}
+
+#[tokio::test]
+async fn test_publish_futures_are_send() {
+    let publish: Arc<publish::DynPublish> = Arc::new(SetPublish { value: 4 });
+
+    let handle = tokio::spawn(assert_send(async move { publish::run(&publish).await }));
+    let result = handle.await.unwrap().unwrap();
+
+    assert_eq!(result.get("test").unwrap(), &publish::Value::Integer(4));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_publish_run_many_with_trait_objects() {
+    let jobs: Vec<publish::Job<Box<publish::DynPublish>>> = vec![
+        publish::Job::new(
+            Box::new(SetPublish { value: 5 }),
+            publish::Context::default(),
+        ),
+        publish::Job::new(Box::new(FailPublish), publish::Context::default()),
+    ];
+
+    let report = publish::run_many(jobs, 2).await;
+
+    assert_eq!(report.succeeded().count(), 1);
+    assert_eq!(report.failed().count(), 1);
+}
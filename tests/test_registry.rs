@@ -0,0 +1,110 @@
+struct TestPublish {
+    value: i64,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for TestPublish {
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<std::borrow::Cow<'a, publish::Context>, publish::Error> {
+        let mut context = context.to_owned();
+        context.set("test", publish::Value::Integer(self.value));
+
+        Ok(std::borrow::Cow::Owned(context))
+    }
+}
+
+fn registry() -> publish::Registry {
+    let mut registry = publish::Registry::new();
+
+    registry
+        .register(
+            "model",
+            publish::PublishMetadata::new(
+                "Publish a model",
+                "1.0.0",
+                publish::ContextSchema::new([publish::SchemaField::new(
+                    "asset",
+                    Some(publish::ValueType::String),
+                    true,
+                )]),
+            ),
+            || TestPublish { value: 1 },
+        )
+        .unwrap();
+    registry
+        .register("camera", publish::PublishMetadata::default(), || {
+            TestPublish { value: 2 }
+        })
+        .unwrap();
+
+    registry
+}
+
+#[test]
+fn test_registry_lookup_success() {
+    let registry = registry();
+
+    assert_eq!(registry.len(), 2);
+    assert!(registry.contains("model"));
+    assert!(!registry.contains("rig"));
+    assert_eq!(
+        registry.names().collect::<Vec<_>>(),
+        vec!["camera", "model"]
+    );
+    assert_eq!(
+        registry.metadata("model").unwrap().description,
+        "Publish a model"
+    );
+    assert_eq!(registry.metadata("model").unwrap().version, "1.0.0");
+    assert!(registry.metadata("rig").is_none());
+}
+
+#[test]
+fn test_registry_register_duplicate_failure() {
+    let mut registry = registry();
+
+    let result = registry.register("model", publish::PublishMetadata::default(), || {
+        TestPublish { value: 3 }
+    });
+
+    assert!(matches!(result, Err(publish::Error::Registry(_))));
+    assert!(registry.unregister("model"));
+    assert!(!registry.contains("model"));
+}
+
+#[tokio::test]
+async fn test_registry_run_success() {
+    let registry = registry();
+    let context = publish::Context::new([(
+        "asset".to_string(),
+        publish::Value::String("chair".to_string()),
+    )]);
+
+    let result = registry.run("model", context).await.unwrap();
+
+    assert_eq!(result.get("test").unwrap(), &publish::Value::Integer(1));
+    assert_eq!(
+        result.get("asset").unwrap(),
+        &publish::Value::String("chair".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_registry_run_invalid_context_failure() {
+    let registry = registry();
+
+    let missing = registry.run("model", publish::Context::default()).await;
+    let wrong_type = registry
+        .run(
+            "model",
+            publish::Context::new([("asset".to_string(), publish::Value::Integer(1))]),
+        )
+        .await;
+    let unknown = registry.run("rig", publish::Context::default()).await;
+
+    assert!(matches!(missing, Err(publish::Error::InvalidContext(_))));
+    assert!(matches!(wrong_type, Err(publish::Error::InvalidContext(_))));
+    assert!(matches!(unknown, Err(publish::Error::Registry(_))));
+}
@@ -0,0 +1,276 @@
+#![cfg(feature = "manifest")]
+
+use std::{borrow::Cow, sync::Arc};
+
+use publish::Transaction;
+
+struct WritePublish {
+    root: std::path::PathBuf,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for WritePublish {
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        std::fs::create_dir_all(self.root.join("chair/v001"))?;
+        std::fs::write(self.root.join("chair/v001/model.usd"), "model")?;
+
+        Ok(Cow::Borrowed(context))
+    }
+}
+
+async fn write_version(root: &std::path::Path) {
+    let transaction = publish::FilesystemTransaction::new();
+    transaction
+        .write(root.join("model.usd"), "model")
+        .await
+        .unwrap();
+    transaction
+        .write(root.join("textures/wood.png"), "wood")
+        .await
+        .unwrap();
+    publish::ManifestBuilder::new(root)
+        .add_files(transaction.written_files())
+        .build()
+        .await
+        .unwrap()
+        .save(&transaction, root)
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_replicate_to_targets() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("publish");
+    write_version(&source.join("chair/v001")).await;
+    let replicator = publish::Replicator::new(&source, dir.path().join("queue.json"))
+        .with_target(publish::ReplicationTarget::new("a", dir.path().join("a")))
+        .with_target(publish::ReplicationTarget::new("b", dir.path().join("b")));
+
+    let results = replicator
+        .replicate(source.join("chair/v001"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        results,
+        vec![
+            publish::ReplicationResult {
+                path: "chair/v001".into(),
+                target: "a".into(),
+                status: publish::ReplicationStatus::Replicated,
+            },
+            publish::ReplicationResult {
+                path: "chair/v001".into(),
+                target: "b".into(),
+                status: publish::ReplicationStatus::Replicated,
+            },
+        ]
+    );
+
+    for target in ["a", "b"] {
+        let root = dir.path().join(target).join("chair/v001");
+        let manifest = publish::Manifest::load(&root).await.unwrap();
+
+        assert!(manifest.verify(&root).await.unwrap().is_empty());
+        assert_eq!(
+            std::fs::read_dir(root.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    assert!(replicator.queue().await.unwrap().is_empty());
+    assert!(matches!(
+        replicator.replicate(dir.path().join("other")).await,
+        Err(publish::Error::Manifest(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_replicate_refuses_corrupt_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("publish");
+    write_version(&source.join("chair/v001")).await;
+    std::fs::write(source.join("chair/v001/model.usd"), "other").unwrap();
+    let replicator = publish::Replicator::new(&source, dir.path().join("queue.json"))
+        .with_target(publish::ReplicationTarget::new("a", dir.path().join("a")));
+
+    let results = replicator.replicate("chair/v001").await.unwrap();
+
+    assert!(matches!(
+        results[0].status,
+        publish::ReplicationStatus::Failed { attempts: 1, .. }
+    ));
+    assert!(!dir.path().join("a/chair/v001").exists());
+    assert_eq!(
+        std::fs::read_dir(dir.path().join("a/chair"))
+            .unwrap()
+            .count(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_retry_failed_replications() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("publish");
+    let target = dir.path().join("a");
+    write_version(&source.join("chair/v001")).await;
+
+    // The target cannot be created while a file is in the way.
+    std::fs::write(&target, "").unwrap();
+
+    let replicator = publish::Replicator::new(&source, dir.path().join("queue.json"))
+        .with_target(publish::ReplicationTarget::new("a", &target));
+
+    replicator.replicate("chair/v001").await.unwrap();
+    let results = replicator.retry().await.unwrap();
+
+    assert!(matches!(
+        results[0].status,
+        publish::ReplicationStatus::Failed { attempts: 2, .. }
+    ));
+
+    // The queue persists, so a new replicator can retry it.
+    let replicator = publish::Replicator::new(&source, dir.path().join("queue.json"))
+        .with_target(publish::ReplicationTarget::new("a", &target));
+    let queue = replicator.queue().await.unwrap();
+
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].path, "chair/v001");
+    assert_eq!(queue[0].target, "a");
+    assert_eq!(queue[0].attempts, 2);
+
+    std::fs::remove_file(&target).unwrap();
+    let results = replicator.retry().await.unwrap();
+
+    assert_eq!(results[0].status, publish::ReplicationStatus::Replicated);
+    assert!(replicator.queue().await.unwrap().is_empty());
+    assert!(target.join("chair/v001/textures/wood.png").exists());
+}
+
+#[tokio::test]
+async fn test_run_with_replication() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("publish");
+    let replicator = Arc::new(
+        publish::Replicator::new(&source, dir.path().join("queue.json"))
+            .with_target(publish::ReplicationTarget::new("a", dir.path().join("a"))),
+    );
+    let options = publish::RunOptions::new().with_replication(
+        replicator.clone(),
+        publish::Template::new("{asset}/v001").unwrap(),
+    );
+    let context = publish::Context::new([(
+        "asset".to_string(),
+        publish::Value::String("chair".to_string()),
+    )]);
+
+    let report = publish::run_with_options(&WritePublish { root: source }, context, &options)
+        .await
+        .unwrap();
+    let results = report.replication.unwrap().unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].status, publish::ReplicationStatus::Replicated);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("a/chair/v001/model.usd")).unwrap(),
+        "model"
+    );
+    assert!(replicator.queue().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_run_with_failed_replication() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("publish");
+    let target = dir.path().join("a");
+    std::fs::write(&target, "").unwrap();
+    let replicator = Arc::new(
+        publish::Replicator::new(&source, dir.path().join("queue.json"))
+            .with_target(publish::ReplicationTarget::new("a", &target)),
+    );
+
+    // The publish is committed before it is replicated, so neither a failed
+    // replication nor a bad version path fails the run.
+    for path in ["{asset}/v001", "{missing}/v001"] {
+        let options = publish::RunOptions::new()
+            .with_replication(replicator.clone(), publish::Template::new(path).unwrap());
+        let context = publish::Context::new([(
+            "asset".to_string(),
+            publish::Value::String("chair".to_string()),
+        )]);
+
+        let report = publish::run_with_options(
+            &WritePublish {
+                root: source.clone(),
+            },
+            context,
+            &options,
+        )
+        .await
+        .unwrap();
+
+        match report.replication.unwrap() {
+            Ok(results) => assert!(matches!(
+                results[0].status,
+                publish::ReplicationStatus::Failed { attempts: 1, .. }
+            )),
+            Err(err) => assert!(matches!(err, publish::Error::MissingTemplateKeys { .. })),
+        }
+
+        assert_eq!(replicator.queue().await.unwrap().len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn test_replicators_share_the_queue() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("publish");
+    let target = dir.path().join("a");
+    std::fs::write(&target, "").unwrap();
+    let versions = ["chair/v001", "chair/v002", "table/v001", "table/v002"];
+
+    for version in versions {
+        write_version(&source.join(version)).await;
+    }
+
+    // Each replicator has its own in-process lock, like replicators in
+    // different processes, so only the lock file keeps their updates.
+    let replicators = versions
+        .iter()
+        .map(|_| {
+            Arc::new(
+                publish::Replicator::new(&source, dir.path().join("queue.json"))
+                    .with_target(publish::ReplicationTarget::new("a", &target)),
+            )
+        })
+        .collect::<Vec<_>>();
+    let handles = replicators
+        .iter()
+        .zip(versions)
+        .map(|(replicator, version)| {
+            let replicator = replicator.clone();
+            tokio::spawn(async move { replicator.replicate(version).await })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+
+    let mut queued = replicators[0]
+        .queue()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|queued| queued.path)
+        .collect::<Vec<_>>();
+    queued.sort();
+
+    assert_eq!(queued, versions);
+}
@@ -0,0 +1,66 @@
+#[test]
+fn test_secret_is_redacted() {
+    let secret = publish::Secret::new("hunter2");
+
+    assert_eq!(secret.expose(), "hunter2");
+    assert_eq!(secret.to_string(), "[REDACTED]");
+    assert!(!format!("{:?}", secret).contains("hunter2"));
+    assert!(!format!("{:#?}", secret).contains("hunter2"));
+}
+
+#[test]
+fn test_secret_value_in_context_is_redacted() {
+    let mut context = publish::Context::default();
+    context.set("password", publish::Secret::new("hunter2").into());
+    context.set(
+        "database",
+        publish::Value::Object(
+            [(
+                "token".to_string(),
+                publish::Value::Secret(publish::Secret::new("abc123")),
+            )]
+            .into_iter()
+            .collect(),
+        ),
+    );
+
+    let debug = format!("{:?}", context);
+
+    assert!(!debug.contains("hunter2"));
+    assert!(!debug.contains("abc123"));
+    assert_eq!(
+        context.get("password").unwrap().value_type(),
+        publish::ValueType::Secret
+    );
+    match context.get("password") {
+        Some(publish::Value::Secret(secret)) => assert_eq!(secret.expose(), "hunter2"),
+        _ => panic!("Expected a secret"),
+    }
+}
+
+#[test]
+fn test_secret_value_type_from_str() {
+    assert_eq!(
+        "secret".parse::<publish::ValueType>().unwrap(),
+        publish::ValueType::Secret
+    );
+    assert_eq!(publish::ValueType::Secret.to_string(), "secret");
+}
+
+#[test]
+fn test_secret_schema_type_error_is_redacted() {
+    let schema = publish::ContextSchema::new([publish::SchemaField::new(
+        "password",
+        Some(publish::ValueType::String),
+        true,
+    )]);
+    let context = publish::Context::new([(
+        "password".to_string(),
+        publish::Secret::new("hunter2").into(),
+    )]);
+
+    let err = schema.validate(&context).unwrap_err();
+
+    assert!(!err.to_string().contains("hunter2"));
+    assert!(!format!("{:?}", err).contains("hunter2"));
+}
@@ -0,0 +1,144 @@
+fn shot_context() -> publish::Context {
+    let mut asset = im::OrdMap::new();
+    asset.insert(
+        "name".to_string(),
+        publish::Value::String("Chair".to_string()),
+    );
+
+    publish::Context::new([
+        (
+            "show".to_string(),
+            publish::Value::String("demo".to_string()),
+        ),
+        (
+            "seq".to_string(),
+            publish::Value::String("sq010".to_string()),
+        ),
+        (
+            "shot".to_string(),
+            publish::Value::String("sh0100".to_string()),
+        ),
+        ("asset".to_string(), publish::Value::Object(asset)),
+        ("version".to_string(), publish::Value::Integer(7)),
+        ("note".to_string(), publish::Value::None),
+        ("token".to_string(), publish::Secret::new("hunter2").into()),
+    ])
+}
+
+#[test]
+fn test_template_render_success() {
+    let context = shot_context();
+
+    let template =
+        publish::Template::new("{show}/{seq}/{shot}/{asset.name:lower}/v{version:03}").unwrap();
+    assert_eq!(
+        template.render(&context).unwrap(),
+        "demo/sq010/sh0100/chair/v007"
+    );
+
+    let template = publish::Template::new("{show:upper}_{step=main}_{note=none}_{{v}}").unwrap();
+    assert_eq!(template.render(&context).unwrap(), "DEMO_main_none_{v}");
+
+    let template = publish::Template::new("{take:04=12}").unwrap();
+    assert_eq!(template.render(&context).unwrap(), "0012");
+}
+
+#[test]
+fn test_template_render_missing_keys() {
+    let template = publish::Template::new("{show}/{step}/{task}/{step}").unwrap();
+
+    let result = template.render(&shot_context());
+
+    match result {
+        Err(publish::Error::MissingTemplateKeys { template, keys }) => {
+            assert_eq!(template, "{show}/{step}/{task}/{step}");
+            assert_eq!(keys, vec!["step".to_string(), "task".to_string()]);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_template_render_secret_failure() {
+    let template = publish::Template::new("{token}").unwrap();
+
+    let err = template.render(&shot_context()).unwrap_err();
+
+    assert!(matches!(err, publish::Error::Template(_)));
+    assert!(!err.to_string().contains("hunter2"));
+}
+
+#[test]
+fn test_template_new_failure() {
+    for source in [
+        "{show",
+        "show}",
+        "{}",
+        "{show:03x}",
+        "{show:title}",
+        "{a{b}}",
+    ] {
+        assert!(
+            matches!(
+                publish::Template::new(source),
+                Err(publish::Error::Template(_))
+            ),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_template_fields() {
+    let template = publish::Template::new("{show}/v{version:03=1}").unwrap();
+
+    let fields = template.fields().collect::<Vec<_>>();
+
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].key, "show");
+    assert_eq!(fields[1].format, Some(publish::TemplateFormat::ZeroPad(3)));
+    assert_eq!(fields[1].default.as_deref(), Some("1"));
+    assert_eq!(template.to_string(), "{show}/v{version:03=1}");
+}
+
+#[test]
+fn test_template_parse_success() {
+    let template = publish::Template::new("{show}/{seq}/{shot}/{asset}/v{version:03}").unwrap();
+
+    let context = template.parse("demo/sq010/sh0100/chair/v007").unwrap();
+
+    assert_eq!(
+        context.get("show"),
+        Some(&publish::Value::String("demo".to_string()))
+    );
+    assert_eq!(
+        context.get("asset"),
+        Some(&publish::Value::String("chair".to_string()))
+    );
+    assert_eq!(context.get("version"), Some(&publish::Value::Integer(7)));
+    assert_eq!(
+        template.render(&context).unwrap(),
+        "demo/sq010/sh0100/chair/v007"
+    );
+}
+
+#[test]
+fn test_template_parse_failure() {
+    let template = publish::Template::new("{asset}/v{version:03}/{asset}.usd").unwrap();
+
+    assert!(template.parse("chair/v007/chair.usd").is_ok());
+
+    for text in [
+        "chair/v007/table.usd",
+        "chair/vabc/chair.usd",
+        "chair/extra/v007/chair.usd",
+        "chair/v007/chair.abc",
+    ] {
+        assert!(
+            matches!(template.parse(text), Err(publish::Error::Template(_))),
+            "{}",
+            text
+        );
+    }
+}
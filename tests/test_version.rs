@@ -0,0 +1,167 @@
+#![cfg(feature = "tokio")]
+
+use std::borrow::Cow;
+
+fn allocator(root: &std::path::Path) -> publish::VersionAllocator {
+    let template =
+        publish::Template::new(&format!("{}/{{asset}}/v{{version:03}}", root.display())).unwrap();
+
+    publish::VersionAllocator::new(template)
+}
+
+fn asset_context(asset: &str) -> publish::Context {
+    publish::Context::new([(
+        "asset".to_string(),
+        publish::Value::String(asset.to_string()),
+    )])
+}
+
+struct VersionedPublish {
+    versions: publish::VersionAllocator,
+    reservation: std::sync::Mutex<Option<publish::VersionReservation>>,
+    fail: bool,
+}
+
+#[async_trait::async_trait]
+impl publish::Publish for VersionedPublish {
+    async fn pre_publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        let reservation = self.versions.reserve(context).await?;
+        let mut context = context.to_owned();
+        context.set("version", publish::Value::Integer(reservation.version));
+        *self.reservation.lock().unwrap() = Some(reservation);
+
+        Ok(Cow::Owned(context))
+    }
+
+    async fn rollback_pre_publish(
+        &self,
+        _context: &publish::Context,
+    ) -> Result<(), publish::Error> {
+        let reservation = self.reservation.lock().unwrap().take();
+
+        match reservation {
+            Some(reservation) => self.versions.release(&reservation).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn publish<'a>(
+        &self,
+        context: &'a publish::Context,
+    ) -> Result<Cow<'a, publish::Context>, publish::Error> {
+        if self.fail {
+            Err(publish::Error::new_publish("publish failed", None))
+        } else {
+            Ok(Cow::Borrowed(context))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_reserve_next_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let versions = allocator(dir.path());
+    let context = asset_context("chair");
+
+    for name in ["v001", "v003", "v0x", "v003.bak", "notes.txt"] {
+        std::fs::create_dir_all(dir.path().join("chair").join(name)).unwrap();
+    }
+    std::fs::create_dir_all(dir.path().join("table").join("v009")).unwrap();
+
+    assert_eq!(
+        versions.existing_versions(&context).await.unwrap(),
+        vec![1, 3]
+    );
+
+    let reservation = versions.reserve(&context).await.unwrap();
+
+    assert_eq!(reservation.version, 4);
+    assert_eq!(reservation.path, dir.path().join("chair").join("v004"));
+    assert!(reservation.path.is_dir());
+    assert_eq!(versions.reservations(), vec![reservation]);
+}
+
+#[tokio::test]
+async fn test_reserve_concurrently() {
+    let dir = tempfile::tempdir().unwrap();
+    let versions = allocator(dir.path());
+    let context = asset_context("chair");
+
+    let reservations = futures::future::join_all((0..8).map(|_| versions.reserve(&context))).await;
+    let mut numbers = reservations
+        .into_iter()
+        .map(|reservation| reservation.unwrap().version)
+        .collect::<Vec<_>>();
+    numbers.sort_unstable();
+
+    assert_eq!(numbers, (1..=8).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_release_on_rollback() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("chair").join("v001")).unwrap();
+    let publish = VersionedPublish {
+        versions: allocator(dir.path()),
+        reservation: std::sync::Mutex::new(None),
+        fail: true,
+    };
+
+    let result = publish::run_with_context(&publish, asset_context("chair")).await;
+
+    assert!(result.is_err());
+    assert!(!dir.path().join("chair").join("v002").exists());
+    assert!(dir.path().join("chair").join("v001").exists());
+    assert!(publish.versions.reservations().is_empty());
+}
+
+#[tokio::test]
+async fn test_release_only_the_reservation() {
+    let dir = tempfile::tempdir().unwrap();
+    let versions = allocator(dir.path());
+    let context = asset_context("chair");
+    let first = versions.reserve(&context).await.unwrap();
+    let second = versions.reserve(&context).await.unwrap();
+
+    versions.release(&first).await.unwrap();
+    versions.release(&first).await.unwrap();
+
+    assert_eq!(versions.reservations(), vec![second.clone()]);
+    assert!(!first.path.exists());
+    assert!(second.path.is_dir());
+}
+
+#[tokio::test]
+async fn test_reserve_in_pre_publish() {
+    let dir = tempfile::tempdir().unwrap();
+    let publish = VersionedPublish {
+        versions: allocator(dir.path()),
+        reservation: std::sync::Mutex::new(None),
+        fail: false,
+    };
+
+    let result = publish::run_with_context(&publish, asset_context("chair"))
+        .await
+        .unwrap();
+
+    assert_eq!(result.get("version"), Some(&publish::Value::Integer(1)));
+    assert!(dir.path().join("chair").join("v001").is_dir());
+}
+
+#[tokio::test]
+async fn test_version_not_in_last_component() {
+    let dir = tempfile::tempdir().unwrap();
+    let template = publish::Template::new(&format!(
+        "{}/v{{version:03}}/{{asset}}",
+        dir.path().display()
+    ))
+    .unwrap();
+    let versions = publish::VersionAllocator::new(template);
+
+    let result = versions.reserve(&asset_context("chair")).await;
+
+    assert!(matches!(result, Err(publish::Error::Template(_))));
+}